@@ -0,0 +1,104 @@
+use glow::HasContext;
+use minvect::*;
+extern crate glow_mesh;
+use glow_mesh::xyzrgba::*;
+use glow_mesh::post::*;
+use glutin::event::{Event, WindowEvent};
+
+pub struct Demo {
+    xres: i32,
+    yres: i32,
+    window: glutin::ContextWrapper<glutin::PossiblyCurrent, glutin::window::Window>,
+    gl: glow::Context,
+
+    scene_prog: ProgramXYZRGBA,
+    scene: HandleXYZRGBA,
+    chain: PostChain,
+}
+
+impl Demo {
+    pub fn new(event_loop: &glutin::event_loop::EventLoop<()>) -> Self {
+        let xres = 1024;
+        let yres = 768;
+
+        unsafe {
+            let window_builder = glutin::window::WindowBuilder::new()
+                .with_title("post processing")
+                .with_inner_size(glutin::dpi::PhysicalSize::new(xres, yres));
+            let window = glutin::ContextBuilder::new()
+                .with_pixel_format(8, 0)
+                .with_vsync(true)
+                .build_windowed(window_builder, event_loop)
+                .unwrap()
+                .make_current()
+                .unwrap();
+
+            let gl = glow::Context::from_loader_function(|s| window.get_proc_address(s) as *const _);
+            gl.enable(glow::BLEND);
+            gl.blend_func(glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA);
+
+            let mat4_ident = [1.0f32, 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1. ];
+
+            let scene_prog = ProgramXYZRGBA::default(&gl).unwrap();
+            let buf = &mut vec![];
+            for i in 0..7 {
+                let phase = i as f32 * 0.9;
+                let c = vec2(phase.cos() * 0.5, phase.sin() * 0.5);
+                put_poly(buf, c, 0.2, 3 + i, phase, vec4(1.2 * i as f32 / 7.0, 0.6, 1.0 - i as f32 / 7.0, 1.0), -0.5);
+            }
+            let scene = upload_xyzrgba_mesh(buf, &gl).unwrap();
+            scene_prog.bind(&gl);
+            scene_prog.set_proj(&mat4_ident, &gl);
+
+            let mut chain = PostChain::new(&gl, xres as usize, yres as usize).unwrap();
+            chain.effects.push(PostEffect::Bloom { threshold: 0.7, intensity: 1.5, radius: 6.0 });
+            chain.effects.push(PostEffect::Vignette { strength: 0.6, radius: 0.5 });
+            chain.effects.push(PostEffect::Crt { curvature: 0.05, scanlines: 0.3 });
+
+            Demo {
+                xres,
+                yres,
+                window,
+                gl,
+                scene_prog,
+                scene,
+                chain,
+            }
+        }
+    }
+
+    pub fn handle_event(&mut self, event: glutin::event::Event<()>) {
+        unsafe {
+            match event {
+                Event::LoopDestroyed |
+                Event::WindowEvent {event: WindowEvent::CloseRequested, ..} => {
+                    std::process::exit(0);
+                },
+
+                Event::WindowEvent {event: WindowEvent::Resized(size), .. } => {
+                    self.xres = size.width as i32;
+                    self.yres = size.height as i32;
+                    self.window.resize(size);
+                    self.gl.viewport(0, 0, size.width as i32, size.height as i32);
+                    self.chain.resize(&self.gl, size.width as usize, size.height as usize).unwrap();
+                },
+                Event::MainEventsCleared => {
+                    self.chain.begin(&self.gl);
+                    self.gl.clear_color(0.1, 0.1, 0.1, 1.0);
+                    self.gl.clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT);
+                    self.scene_prog.bind(&self.gl);
+                    self.scene.render(&self.gl);
+                    self.chain.end(&self.gl, self.xres as usize, self.yres as usize).unwrap();
+                    self.window.swap_buffers().unwrap();
+                },
+                _ => {},
+            }
+        }
+    }
+}
+
+pub fn main() {
+        let event_loop = glutin::event_loop::EventLoop::new();
+        let mut demo = Demo::new(&event_loop);
+        event_loop.run(move |event, _, _| demo.handle_event(event));
+}
@@ -0,0 +1,102 @@
+use glow::HasContext;
+use minvect::*;
+extern crate glow_mesh;
+use glow_mesh::sprite::*;
+use glow_mesh::texture::*;
+use glutin::event::{Event, WindowEvent};
+extern crate minimg;
+
+pub struct Demo {
+    xres: i32,
+    yres: i32,
+    window: glutin::ContextWrapper<glutin::PossiblyCurrent, glutin::window::Window>,
+    gl: glow::Context,
+
+    batch: SpriteBatch,
+    texture: Texture,
+    t: f32,
+}
+
+impl Demo {
+    pub fn new(event_loop: &glutin::event_loop::EventLoop<()>) -> Self {
+        let xres = 1024;
+        let yres = 768;
+    
+        unsafe {
+            let window_builder = glutin::window::WindowBuilder::new()
+                .with_title("sprites")
+                .with_inner_size(glutin::dpi::PhysicalSize::new(xres, yres));
+            let window = glutin::ContextBuilder::new()
+                .with_pixel_format(8, 0)
+                .with_vsync(true)
+                .build_windowed(window_builder, event_loop)
+                .unwrap()
+                .make_current()
+                .unwrap();
+    
+            let gl = glow::Context::from_loader_function(|s| window.get_proc_address(s) as *const _);
+            gl.enable(glow::BLEND);
+            gl.blend_func(glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA);
+    
+            let img = minimg::ImageBuffer::from_bytes(include_bytes!("testimage.png"));
+            let texture = Texture::new(&gl, &img, &SamplerDesc::nearest()).unwrap();
+            let batch = SpriteBatch::new(&gl, 256).unwrap();
+
+            Demo {
+                xres,
+                yres,
+                window,
+                gl,
+                batch,
+                texture,
+                t: 0.0,
+            }
+        }
+    }
+
+    pub fn handle_event(&mut self, event: glutin::event::Event<()>) {
+        unsafe {
+            match event {
+                Event::LoopDestroyed |
+                Event::WindowEvent {event: WindowEvent::CloseRequested, ..} => {
+                    std::process::exit(0);
+                },
+
+                Event::WindowEvent {event: WindowEvent::Resized(size), .. } => {
+                    self.xres = size.width as i32;
+                    self.yres = size.height as i32;
+                    self.window.resize(size);
+                    self.gl.viewport(0, 0, size.width as i32, size.height as i32);
+                },
+                Event::MainEventsCleared => {
+                    self.gl.clear_color(0.5, 0.5, 0.5, 1.0);
+                    self.gl.clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT); 
+                    // pixel coordinates, y down
+                    let (w, h) = (self.xres as f32, self.yres as f32);
+                    let proj = [2.0 / w, 0., 0., -1., 0., -2.0 / h, 0., 1., 0., 0., 1., 0., 0., 0., 0., 1.];
+                    self.batch.set_proj(&proj, &self.gl);
+                    self.t += 1.0 / 60.0;
+                    for i in 0..1000 {
+                        let x = (i % 40) as f32 * w / 40.0;
+                        let y = (i / 40) as f32 * h / 25.0;
+                        // each sprite is one quadrant of the test image
+                        let src = rect(((i % 2) * 32) as f32, (((i / 2) % 2) * 32) as f32, 32.0, 32.0);
+                        let sprite = Sprite::new(rect(x, y, 24.0, 24.0), src)
+                            .with_rotation(self.t + i as f32 * 0.1, vec2(12.0, 12.0))
+                            .with_tint(vec4(1.0, 1.0 - (i % 7) as f32 / 7.0, 1.0, 1.0));
+                        self.batch.draw(&self.gl, &self.texture, &sprite);
+                    }
+                    self.batch.flush(&self.gl);
+                    self.window.swap_buffers().unwrap();
+                },
+                _ => {},
+            }
+        }
+    }
+}
+
+pub fn main() {
+        let event_loop = glutin::event_loop::EventLoop::new();
+        let mut triangle_demo = Demo::new(&event_loop);
+        event_loop.run(move |event, _, _| triangle_demo.handle_event(event));
+}
\ No newline at end of file
@@ -2,6 +2,7 @@ use glow::HasContext;
 use minvect::*;
 extern crate glow_mesh;
 use glow_mesh::xyzrgbauv::*;
+use glow_mesh::texture::*;
 use glutin::event::{Event, WindowEvent};
 extern crate minimg;
 
@@ -12,6 +13,7 @@ pub struct Demo {
     gl: glow::Context,
 
     prog: ProgramXYZRGBAUV,
+    texture: Texture,
     h: HandleXYZRGBAUV,
 }
 
@@ -27,7 +29,7 @@ impl Demo {
             let window = glutin::ContextBuilder::new()
                 .with_pixel_format(8, 0)
                 .with_vsync(true)
-                .build_windowed(window_builder, &event_loop)
+                .build_windowed(window_builder, event_loop)
                 .unwrap()
                 .make_current()
                 .unwrap();
@@ -36,11 +38,12 @@ impl Demo {
             gl.blend_func(glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA);
     
             let img = minimg::ImageBuffer::from_bytes(include_bytes!("testimage.png"));
-            let prog = ProgramXYZRGBAUV::default(&gl, &img);
+            let texture = Texture::new(&gl, &img, &SamplerDesc::nearest()).unwrap();
+            let prog = ProgramXYZRGBAUV::default(&gl).unwrap();
     
             let buf = &mut vec![];
             put_quad(buf, vec2(-1.0, -1.0), vec2(1.0, -1.0), vec2(1.0, 1.0), vec2(-1.0, 1.0), vec4(1.0, 1.0, 1.0, 1.0), vec2(0.0, 0.0), vec2(1.0, 1.0), -0.5);
-            let h = upload_xyzrgbauv_mesh(buf, &gl);
+            let h = upload_xyzrgbauv_mesh(buf, &gl).unwrap();
             prog.bind(&gl);
             let mat4_ident = [1.0f32, 0., 0., 0., 0., -1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1. ];
             prog.set_proj(&mat4_ident, &gl);
@@ -51,6 +54,7 @@ impl Demo {
                 window,
                 gl,
                 prog,
+                texture,
                 h,
             }
         }
@@ -64,21 +68,17 @@ impl Demo {
                     std::process::exit(0);
                 },
 
-                Event::WindowEvent {event, .. } => {
-                    match event {
-                        WindowEvent::Resized(size) => {
-                            self.xres = size.width as i32;
-                            self.yres = size.height as i32;
-                            self.window.resize(size);
-                            self.gl.viewport(0, 0, size.width as i32, size.height as i32);
-                        },
-                        _ => {},
-                    }
+                Event::WindowEvent {event: WindowEvent::Resized(size), .. } => {
+                    self.xres = size.width as i32;
+                    self.yres = size.height as i32;
+                    self.window.resize(size);
+                    self.gl.viewport(0, 0, size.width as i32, size.height as i32);
                 },
                 Event::MainEventsCleared => {
                     self.gl.clear_color(0.5, 0.5, 0.5, 1.0);
                     self.gl.clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT); 
                     self.prog.bind(&self.gl);
+                    self.prog.bind_texture(&self.texture, &self.gl);
                     self.h.render(&self.gl);
                     self.window.swap_buffers().unwrap();
                 },
@@ -2,6 +2,7 @@ use glow::HasContext;
 use minvect::*;
 extern crate glow_mesh;
 use glow_mesh::xyzrgba::*;
+use glow_mesh::mesh::*;
 use glutin::event::{Event, WindowEvent};
 
 pub struct TriangleDemo {
@@ -11,6 +12,7 @@ pub struct TriangleDemo {
     gl: glow::Context,
 
     prog: ProgramXYZRGBA,
+    h: DynamicMesh<XYZRGBA>,
 }
 
 impl TriangleDemo {
@@ -24,14 +26,15 @@ impl TriangleDemo {
                 .with_inner_size(glutin::dpi::PhysicalSize::new(xres, yres));
             let window = glutin::ContextBuilder::new()
                 .with_vsync(true)
-                .build_windowed(window_builder, &event_loop)
+                .build_windowed(window_builder, event_loop)
                 .unwrap()
                 .make_current()
                 .unwrap();
     
             let gl = glow::Context::from_loader_function(|s| window.get_proc_address(s) as *const _);
     
-            let prog = ProgramXYZRGBA::default(&gl);
+            let prog = ProgramXYZRGBA::default(&gl).unwrap();
+            let h = DynamicMesh::new(Usage::Stream, &gl).unwrap();
     
 
             TriangleDemo {
@@ -40,6 +43,7 @@ impl TriangleDemo {
                 window,
                 gl,
                 prog,
+                h,
             }
         }
     }
@@ -52,16 +56,11 @@ impl TriangleDemo {
                     std::process::exit(0);
                 },
 
-                Event::WindowEvent {event, .. } => {
-                    match event {
-                        WindowEvent::Resized(size) => {
-                            self.xres = size.width as i32;
-                            self.yres = size.height as i32;
-                            self.window.resize(size);
-                            self.gl.viewport(0, 0, size.width as i32, size.height as i32);
-                        },
-                        _ => {},
-                    }
+                Event::WindowEvent {event: WindowEvent::Resized(size), .. } => {
+                    self.xres = size.width as i32;
+                    self.yres = size.height as i32;
+                    self.window.resize(size);
+                    self.gl.viewport(0, 0, size.width as i32, size.height as i32);
                 },
                 Event::MainEventsCleared => {
                     self.gl.clear_color(0.5, 0.5, 0.5, 1.0);
@@ -69,14 +68,14 @@ impl TriangleDemo {
 
                     let buf = &mut vec![];
                     put_triangle(buf, vec2(0.0, 1.0), vec2(1.0, 0.0), vec2(-1.0, 0.0), vec4(1.0, 0.0, 0.0, 1.0), -0.5);
-                    let h = upload_xyzrgba_mesh(buf, &self.gl);
+                    // same buffer every frame, just overwritten
+                    self.h.update(buf, &self.gl);
                     self.prog.bind(&self.gl);
                     let mat4_ident = [1.0f32, 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1. ];
                     self.prog.set_proj(&mat4_ident, &self.gl);
 
-                    h.render(&self.gl);
+                    self.h.render(&self.gl);
                     self.window.swap_buffers().unwrap();
-                    h.free(&self.gl);
                 },
                 _ => {},
             }
@@ -1,5 +1,4 @@
 use glow::HasContext;
-use minimg::ImageBuffer;
 use minvect::*;
 extern crate glow_mesh;
 use glow_mesh::xyzrgbauv::*;
@@ -26,18 +25,18 @@ impl TriangleDemo {
                 .with_inner_size(glutin::dpi::PhysicalSize::new(xres, yres));
             let window = glutin::ContextBuilder::new()
                 .with_vsync(true)
-                .build_windowed(window_builder, &event_loop)
+                .build_windowed(window_builder, event_loop)
                 .unwrap()
                 .make_current()
                 .unwrap();
     
             let gl = glow::Context::from_loader_function(|s| window.get_proc_address(s) as *const _);
     
-            let prog = ProgramXYZRGBAUV::new(&gl, &DEFAULT_VS, &FS_UV_DEMO, &ImageBuffer::new(512, 512));
+            let prog = ProgramXYZRGBAUV::new(&gl, DEFAULT_VS, FS_UV_DEMO).unwrap();
     
             let buf = &mut vec![];
             put_quad(buf, vec2(-1.0, -1.0), vec2(1.0, -1.0), vec2(1.0, 1.0), vec2(-1.0, 1.0), vec4(1.0, 0.0, 0.0, 1.0), vec2(0.0, 0.0), vec2(1.0, 1.0), -0.5);
-            let h = upload_xyzrgbauv_mesh(buf, &gl);
+            let h = upload_xyzrgbauv_mesh(buf, &gl).unwrap();
             prog.bind(&gl);
             let mat4_ident = [1.0f32, 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1. ];
             prog.set_proj(&mat4_ident, &gl);
@@ -61,20 +60,16 @@ impl TriangleDemo {
                     std::process::exit(0);
                 },
 
-                Event::WindowEvent {event, .. } => {
-                    match event {
-                        WindowEvent::Resized(size) => {
-                            self.xres = size.width as i32;
-                            self.yres = size.height as i32;
-                            self.window.resize(size);
-                            self.gl.viewport(0, 0, size.width as i32, size.height as i32);
-                        },
-                        _ => {},
-                    }
+                Event::WindowEvent {event: WindowEvent::Resized(size), .. } => {
+                    self.xres = size.width as i32;
+                    self.yres = size.height as i32;
+                    self.window.resize(size);
+                    self.gl.viewport(0, 0, size.width as i32, size.height as i32);
                 },
                 Event::MainEventsCleared => {
                     self.gl.clear_color(0.5, 0.5, 0.5, 1.0);
                     self.gl.clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT); 
+                    self.prog.bind(&self.gl);
                     self.h.render(&self.gl);
                     self.window.swap_buffers().unwrap();
                 },
@@ -0,0 +1,136 @@
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Expr, Fields, Lit, Type};
+
+/// #[derive(Vertex)] for a #[repr(C)] / #[repr(C, packed)] struct.
+/// Each field becomes an attribute at location = field index, offset taken from the actual struct layout.
+///
+/// Field types:
+/// * f32, Vec2, Vec3, Vec4, [f32; N] -> float
+/// * u8, i8, u16, i16 and arrays of them -> normalized to 0..1 (or -1..1)
+/// * u32, i32 and arrays of them -> integer attribute (ivec / uvec in the shader)
+///
+/// Generic structs are rejected, and so are structs with padding since every byte gets uploaded.
+#[proc_macro_derive(Vertex)]
+pub fn derive_vertex(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand(input: &DeriveInput) -> Result<proc_macro2::TokenStream, Error> {
+    let name = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(&input.generics, "derive(Vertex) doesn't support generic structs, the attribute formats have to be known here"));
+    }
+
+    let repr_c = input.attrs.iter().any(|a| {
+        if !a.path().is_ident("repr") {
+            return false;
+        }
+        let mut c = false;
+        let _ = a.parse_nested_meta(|m| {
+            if m.path.is_ident("C") {
+                c = true;
+            }
+            if m.input.peek(syn::token::Paren) {
+                let _content;
+                syn::parenthesized!(_content in m.input);
+            }
+            Ok(())
+        });
+        c
+    });
+    if !repr_c {
+        return Err(Error::new_spanned(name, "Vertex types need #[repr(C)] or #[repr(C, packed)] so the field offsets are stable"));
+    }
+
+    let fields = match &input.data {
+        Data::Struct(s) => match &s.fields {
+            Fields::Named(f) => &f.named,
+            _ => return Err(Error::new_spanned(name, "derive(Vertex) needs a struct with named fields")),
+        },
+        _ => return Err(Error::new_spanned(name, "derive(Vertex) only works on structs")),
+    };
+
+    let mut attribs = vec![];
+    let types = fields.iter().map(|f| &f.ty);
+    for (location, field) in fields.iter().enumerate() {
+        let ident = field.ident.as_ref().unwrap();
+        let (size, data_type, normalized) = attrib_format(&field.ty)?;
+        let location = location as u32;
+        let data_type = syn::Ident::new(data_type, Span::call_site());
+        attribs.push(quote! {
+            ::glow_mesh::vertex::VertexAttrib {
+                location: #location,
+                size: #size,
+                data_type: ::glow_mesh::glow::#data_type,
+                normalized: #normalized,
+                offset: ::std::mem::offset_of!(#name, #ident) as i32,
+            }
+        });
+    }
+
+    // padding would be uploaded as uninitialized bytes, so the fields have to fill the struct exactly
+    Ok(quote! {
+        const _: () = assert!(
+            ::std::mem::size_of::<#name>() == 0 #(+ ::std::mem::size_of::<#types>())*,
+            "Vertex types cant have padding, reorder the fields or use #[repr(C, packed)]",
+        );
+        unsafe impl ::glow_mesh::vertex::Vertex for #name {
+            const ATTRIBS: &'static [::glow_mesh::vertex::VertexAttrib] = &[
+                #(#attribs),*
+            ];
+        }
+    })
+}
+
+/// (components, glow type constant, normalized)
+fn attrib_format(ty: &Type) -> Result<(i32, &'static str, bool), Error> {
+    match ty {
+        Type::Array(a) => {
+            let n = match &a.len {
+                Expr::Lit(l) => match &l.lit {
+                    Lit::Int(i) => i.base10_parse::<i32>()?,
+                    _ => return Err(Error::new_spanned(&a.len, "array length must be an integer literal")),
+                },
+                _ => return Err(Error::new_spanned(&a.len, "array length must be an integer literal")),
+            };
+            if !(1..=4).contains(&n) {
+                return Err(Error::new_spanned(ty, "vertex attributes have 1 to 4 components"));
+            }
+            let (_, data_type, normalized) = scalar_format(&a.elem)?;
+            Ok((n, data_type, normalized))
+        },
+        Type::Path(p) => {
+            let last = p.path.segments.last().unwrap().ident.to_string();
+            match last.as_str() {
+                "Vec2" => Ok((2, "FLOAT", false)),
+                "Vec3" => Ok((3, "FLOAT", false)),
+                "Vec4" => Ok((4, "FLOAT", false)),
+                _ => scalar_format(ty),
+            }
+        },
+        _ => Err(Error::new_spanned(ty, "unsupported vertex field type")),
+    }
+}
+
+fn scalar_format(ty: &Type) -> Result<(i32, &'static str, bool), Error> {
+    let name = match ty {
+        Type::Path(p) => p.path.segments.last().unwrap().ident.to_string(),
+        _ => String::new(),
+    };
+    match name.as_str() {
+        "f32" => Ok((1, "FLOAT", false)),
+        "u8" => Ok((1, "UNSIGNED_BYTE", true)),
+        "i8" => Ok((1, "BYTE", true)),
+        "u16" => Ok((1, "UNSIGNED_SHORT", true)),
+        "i16" => Ok((1, "SHORT", true)),
+        "u32" => Ok((1, "UNSIGNED_INT", false)),
+        "i32" => Ok((1, "INT", false)),
+        _ => Err(Error::new_spanned(ty, "unsupported vertex field type, expected f32, Vec2, Vec3, Vec4, an integer or an array of those")),
+    }
+}
@@ -0,0 +1,378 @@
+//! Packing lots of little images into a few big ones so they can share a texture.
+//! AtlasBuilder packs with a skyline packer onto as many pages as it needs. Each image gets padding around it and its
+//! edge pixels extruded into that, so linear filtering and mipmaps dont bleed the neighbours in.
+//! The resulting Atlas maps names to uv Rects for put_rect, or pixel rects for Sprite::src, and can be saved
+//! as a text manifest + page pngs so atlases can be baked at build time:
+//! ```text
+//! atlas <page w> <page h> <pages>
+//! <page> <x> <y> <w> <h> <name>
+//! ...
+//! ```
+
+use minimg::*;
+use minvect::*;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use crate::error::*;
+use crate::screenshot::*;
+
+/// where an image ended up, in pixels of its page, not counting padding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AtlasEntry {
+    pub page: usize,
+    pub x: usize,
+    pub y: usize,
+    pub w: usize,
+    pub h: usize,
+}
+
+impl AtlasEntry {
+    /// pixel rect, eg. for Sprite::src
+    pub fn src_rect(&self) -> Rect {
+        rect(self.x as f32, self.y as f32, self.w as f32, self.h as f32)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Atlas {
+    pub page_w: usize,
+    pub page_h: usize,
+    pub num_pages: usize,
+    pub entries: BTreeMap<String, AtlasEntry>,
+}
+
+impl Atlas {
+    pub fn get(&self, name: &str) -> Option<&AtlasEntry> {
+        self.entries.get(name)
+    }
+    /// normalized uvs for put_rect. Check get(name).page for which texture
+    pub fn uv(&self, name: &str) -> Option<Rect> {
+        self.get(name).map(|e| rect(
+            e.x as f32 / self.page_w as f32,
+            e.y as f32 / self.page_h as f32,
+            e.w as f32 / self.page_w as f32,
+            e.h as f32 / self.page_h as f32,
+        ))
+    }
+    pub fn to_manifest(&self) -> String {
+        let mut s = format!("atlas {} {} {}\n", self.page_w, self.page_h, self.num_pages);
+        for (name, e) in &self.entries {
+            s += &format!("{} {} {} {} {} {}\n", e.page, e.x, e.y, e.w, e.h, name);
+        }
+        s
+    }
+    /// what is for error messages, eg. the file name
+    pub fn from_manifest(manifest: &str, what: &str) -> Result<Atlas, GlowMeshError> {
+        let err = |line: usize, message: &str| GlowMeshError::Parse { what: what.to_string(), line, message: message.to_string() };
+        let mut lines = manifest.lines().enumerate().filter(|(_, l)| !l.trim().is_empty());
+        let header: Vec<&str> = match lines.next() {
+            Some((_, l)) => l.split_whitespace().collect(),
+            None => return Err(err(1, "empty manifest")),
+        };
+        let (page_w, page_h, num_pages) = match header.as_slice() {
+            ["atlas", w, h, n] => match (w.parse(), h.parse(), n.parse()) {
+                (Ok(w), Ok(h), Ok(n)) => (w, h, n),
+                _ => return Err(err(1, "expected atlas <page w> <page h> <pages>")),
+            },
+            _ => return Err(err(1, "expected atlas <page w> <page h> <pages>")),
+        };
+        let mut entries = BTreeMap::new();
+        for (i, line) in lines {
+            // name goes last so it can have spaces in it
+            let mut fields = line.trim_start().splitn(6, ' ');
+            let mut num = || fields.next().and_then(|f| f.parse::<usize>().ok());
+            let (page, x, y, w, h) = match (num(), num(), num(), num(), num()) {
+                (Some(page), Some(x), Some(y), Some(w), Some(h)) => (page, x, y, w, h),
+                _ => return Err(err(i + 1, "expected <page> <x> <y> <w> <h> <name>")),
+            };
+            let name = match fields.next() {
+                Some(name) if !name.is_empty() => name.to_string(),
+                _ => return Err(err(i + 1, "missing name")),
+            };
+            if page >= num_pages || x.checked_add(w).is_none_or(|r| r > page_w) || y.checked_add(h).is_none_or(|b| b > page_h) {
+                return Err(err(i + 1, "rect is outside the pages"));
+            }
+            entries.insert(name, AtlasEntry { page, x, y, w, h });
+        }
+        Ok(Atlas { page_w, page_h, num_pages, entries })
+    }
+    /// write the manifest to path and the pages next to it as <stem>.<page>.png
+    pub fn save<P: AsRef<Path>>(&self, pages: &[ImageBuffer], path: P) -> Result<(), GlowMeshError> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_manifest()).map_err(|error| GlowMeshError::Io { path: path.to_path_buf(), error })?;
+        for (i, page) in pages.iter().enumerate() {
+            save_png(page, page_path(path, i))?;
+        }
+        Ok(())
+    }
+    /// read back what save wrote
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<(Atlas, Vec<ImageBuffer>), GlowMeshError> {
+        let path = path.as_ref();
+        let manifest = std::fs::read_to_string(path).map_err(|error| GlowMeshError::Io { path: path.to_path_buf(), error })?;
+        let atlas = Atlas::from_manifest(&manifest, &path.display().to_string())?;
+        let pages = (0..atlas.num_pages).map(|i| load_png(page_path(path, i))).collect::<Result<Vec<_>, _>>()?;
+        Ok((atlas, pages))
+    }
+}
+
+fn page_path(manifest: &Path, page: usize) -> PathBuf {
+    let stem = manifest.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    manifest.with_file_name(format!("{}.{}.png", stem, page))
+}
+
+/// one page's skyline: runs of x with the lowest free y above them
+struct Skyline {
+    /// (x, y, w), sorted by x, covering the page
+    segments: Vec<(usize, usize, usize)>,
+    w: usize,
+    h: usize,
+}
+
+impl Skyline {
+    fn new(w: usize, h: usize) -> Self {
+        Skyline { segments: vec![(0, 0, w)], w, h }
+    }
+    /// y if a w x h box fits with its left edge at segment i
+    fn fits(&self, i: usize, w: usize, h: usize) -> Option<usize> {
+        let x = self.segments[i].0;
+        if x + w > self.w {
+            return None;
+        }
+        let mut y = 0;
+        for &(_, sy, _) in self.segments[i..].iter().take_while(|s| s.0 < x + w) {
+            y = y.max(sy);
+        }
+        if y + h > self.h { None } else { Some(y) }
+    }
+    /// lowest then leftmost spot for a w x h box
+    fn find(&self, w: usize, h: usize) -> Option<(usize, usize)> {
+        (0..self.segments.len())
+            .filter_map(|i| self.fits(i, w, h).map(|y| (self.segments[i].0, y)))
+            .min_by_key(|&(x, y)| (y, x))
+    }
+    fn place(&mut self, x: usize, y: usize, w: usize, h: usize) {
+        let mut segments = vec![];
+        let mut inserted = false;
+        for &(sx, sy, sw) in &self.segments {
+            let end = sx + sw;
+            if end <= x || sx >= x + w {
+                if sx >= x + w && !inserted {
+                    segments.push((x, y + h, w));
+                    inserted = true;
+                }
+                segments.push((sx, sy, sw));
+                continue;
+            }
+            // bits of the segment sticking out either side of the box
+            if sx < x {
+                segments.push((sx, sy, x - sx));
+            }
+            if !inserted {
+                segments.push((x, y + h, w));
+                inserted = true;
+            }
+            if end > x + w {
+                segments.push((x + w, sy, end - (x + w)));
+            }
+        }
+        if !inserted {
+            segments.push((x, y + h, w));
+        }
+        // merge neighbours at the same height
+        let mut merged: Vec<(usize, usize, usize)> = vec![];
+        for s in segments {
+            match merged.last_mut() {
+                Some(last) if last.1 == s.1 => last.2 += s.2,
+                _ => merged.push(s),
+            }
+        }
+        self.segments = merged;
+    }
+}
+
+pub struct AtlasBuilder {
+    pub page_w: usize,
+    pub page_h: usize,
+    /// empty pixels between images and around the page edge
+    pub padding: usize,
+    /// how many pixels of each image's edge get repeated outwards
+    pub extrude: usize,
+    images: Vec<(String, ImageBuffer)>,
+}
+
+impl AtlasBuilder {
+    /// 1 pixel of padding and extrusion
+    pub fn new(page_w: usize, page_h: usize) -> Self {
+        AtlasBuilder { page_w, page_h, padding: 1, extrude: 1, images: vec![] }
+    }
+    pub fn with_padding(self, padding: usize) -> Self {
+        AtlasBuilder { padding, ..self }
+    }
+    pub fn with_extrude(self, extrude: usize) -> Self {
+        AtlasBuilder { extrude, ..self }
+    }
+    /// adding the same name twice replaces the first
+    pub fn add(&mut self, name: &str, image: ImageBuffer) {
+        self.images.retain(|(n, _)| n != name);
+        self.images.push((name.to_string(), image));
+    }
+    pub fn len(&self) -> usize {
+        self.images.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.images.is_empty()
+    }
+    /// pack everything, returning the atlas and its pages. Biggest images go first, so the result
+    /// only depends on what was added and not the order
+    pub fn build(&self) -> Result<(Atlas, Vec<ImageBuffer>), GlowMeshError> {
+        let mut order: Vec<&(String, ImageBuffer)> = self.images.iter().collect();
+        order.sort_by(|a, b| (b.1.h, b.1.w, &a.0).cmp(&(a.1.h, a.1.w, &b.0)));
+
+        let border = self.extrude + self.padding;
+        let inner_w = self.page_w.saturating_sub(self.padding);
+        let inner_h = self.page_h.saturating_sub(self.padding);
+        let mut skylines: Vec<Skyline> = vec![];
+        let mut pages: Vec<ImageBuffer> = vec![];
+        let mut entries = BTreeMap::new();
+
+        for (name, image) in order {
+            // each box is the image, extruded edges and padding on the right / bottom. The page starts with padding on the left / top
+            let bw = image.w + 2 * self.extrude + self.padding;
+            let bh = image.h + 2 * self.extrude + self.padding;
+            let spot = skylines.iter().enumerate().find_map(|(i, s)| s.find(bw, bh).map(|(x, y)| (i, x, y)));
+            let (page, x, y) = match spot {
+                Some(spot) => spot,
+                None => {
+                    let skyline = Skyline::new(inner_w, inner_h);
+                    let (x, y) = skyline.find(bw, bh).ok_or_else(|| GlowMeshError::AtlasImageTooBig { name: name.clone(), w: image.w, h: image.h })?;
+                    skylines.push(skyline);
+                    pages.push(ImageBuffer::new(self.page_w, self.page_h));
+                    (skylines.len() - 1, x, y)
+                },
+            };
+            skylines[page].place(x, y, bw, bh);
+            let entry = AtlasEntry { page, x: x + border, y: y + border, w: image.w, h: image.h };
+            blit_extruded(&mut pages[page], image, entry.x, entry.y, self.extrude);
+            entries.insert(name.clone(), entry);
+        }
+
+        let atlas = Atlas { page_w: self.page_w, page_h: self.page_h, num_pages: pages.len(), entries };
+        Ok((atlas, pages))
+    }
+}
+
+/// copy image to x, y with its edge pixels repeated extrude pixels outwards
+fn blit_extruded(page: &mut ImageBuffer, image: &ImageBuffer, x: usize, y: usize, extrude: usize) {
+    if image.w == 0 || image.h == 0 {
+        return;
+    }
+    let e = extrude as isize;
+    for dy in -e..image.h as isize + e {
+        let sy = dy.clamp(0, image.h as isize - 1) as usize;
+        let py = (y as isize + dy) as usize;
+        for dx in -e..image.w as isize + e {
+            let sx = dx.clamp(0, image.w as isize - 1) as usize;
+            let px = (x as isize + dx) as usize;
+            let src = (sy * image.w + sx) * 4;
+            let dst = (py * page.w + px) * 4;
+            page.data[dst..dst + 4].copy_from_slice(&image.data[src..src + 4]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(w: usize, h: usize, shade: u8) -> ImageBuffer {
+        let mut img = ImageBuffer::new(w, h);
+        img.data.iter_mut().for_each(|b| *b = shade);
+        img
+    }
+
+    fn builder() -> AtlasBuilder {
+        let mut b = AtlasBuilder::new(64, 64).with_padding(2).with_extrude(1);
+        for i in 0..40 {
+            b.add(&format!("image {}", i), image(3 + i % 7 * 2, 2 + i % 5 * 3, i as u8 + 1));
+        }
+        b
+    }
+
+    #[test]
+    fn placements_dont_overlap() {
+        let (atlas, pages) = builder().build().unwrap();
+        assert!(atlas.num_pages > 1);
+        assert_eq!(pages.len(), atlas.num_pages);
+        // each image plus its extruded border, which is still padding apart from the next
+        let border = 1;
+        let boxes: Vec<(usize, usize, usize, usize, usize)> = atlas.entries.values()
+            .map(|e| (e.page, e.x - border, e.y - border, e.x + e.w + border, e.y + e.h + border))
+            .collect();
+        for (i, a) in boxes.iter().enumerate() {
+            assert!(a.1 >= 2 && a.2 >= 2 && a.3 <= 64 && a.4 <= 64, "{:?} is in the page padding", a);
+            for b in &boxes[i + 1..] {
+                let apart = a.0 != b.0 || a.3 + 2 <= b.1 || b.3 + 2 <= a.1 || a.4 + 2 <= b.2 || b.4 + 2 <= a.2;
+                assert!(apart, "{:?} and {:?} overlap", a, b);
+            }
+        }
+        // and the pixels got there, extruded one pixel out
+        for (i, (name, e)) in atlas.entries.iter().enumerate() {
+            let shade = name[6..].parse::<u8>().unwrap() + 1;
+            let page = &pages[e.page];
+            for (x, y) in [(e.x - 1, e.y - 1), (e.x + e.w, e.y + e.h), (e.x + e.w / 2, e.y + e.h / 2)] {
+                assert_eq!(page.data[(y * page.w + x) * 4], shade, "entry {} at {},{}", i, x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn same_result_whatever_order_they_were_added() {
+        let mut reversed = AtlasBuilder::new(64, 64).with_padding(2).with_extrude(1);
+        for (name, img) in builder().images.into_iter().rev() {
+            reversed.add(&name, img);
+        }
+        assert_eq!(builder().build().unwrap().0, reversed.build().unwrap().0);
+    }
+
+    #[test]
+    fn too_big_for_a_page() {
+        let mut b = AtlasBuilder::new(32, 32);
+        b.add("small", image(4, 4, 1));
+        // fits exactly without the padding and extrusion
+        b.add("big", image(32, 8, 1));
+        match b.build() {
+            Err(GlowMeshError::AtlasImageTooBig { name, w, h }) => assert_eq!((name.as_str(), w, h), ("big", 32, 8)),
+            other => panic!("expected AtlasImageTooBig, got {:?}", other.map(|r| r.0)),
+        }
+        let mut b = AtlasBuilder::new(32, 32).with_padding(0).with_extrude(0);
+        b.add("big", image(32, 8, 1));
+        assert!(b.build().is_ok());
+    }
+
+    #[test]
+    fn manifest_round_trips() {
+        let mut b = builder();
+        b.add("name with spaces", image(5, 5, 9));
+        let (atlas, _) = b.build().unwrap();
+        let manifest = atlas.to_manifest();
+        assert!(manifest.starts_with(&format!("atlas 64 64 {}\n", atlas.num_pages)));
+        assert_eq!(Atlas::from_manifest(&manifest, "test").unwrap(), atlas);
+    }
+
+    #[test]
+    fn manifest_errors() {
+        let line = |manifest: &str| match Atlas::from_manifest(manifest, "test") {
+            Err(GlowMeshError::Parse { line, .. }) => line,
+            other => panic!("expected a parse error, got {:?}", other),
+        };
+        assert_eq!(line(""), 1);
+        assert_eq!(line("atlas 64 64"), 1);
+        assert_eq!(line("atlas 64 64 1\n0 0 0 4 4 a\n0 0 0 4 b"), 3);
+        assert_eq!(line("atlas 64 64 1\n\n1 0 0 4 4 a"), 3);
+        assert_eq!(line("atlas 64 64 1\n0 62 0 4 4 a"), 2);
+        // too big to add up
+        let max = usize::MAX;
+        assert_eq!(line(&format!("atlas {} {} 1\n0 {} 0 2 2 a", max, max, max)), 2);
+        assert_eq!(line(&format!("atlas {} {} 1\n0 0 2 2 {} a", max, max, max)), 2);
+    }
+}
@@ -0,0 +1,89 @@
+use std::fmt;
+use std::path::PathBuf;
+use crate::shader_log::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShaderStage {
+    Vertex,
+    Fragment,
+}
+
+impl fmt::Display for ShaderStage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShaderStage::Vertex => write!(f, "vertex"),
+            ShaderStage::Fragment => write!(f, "fragment"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum GlowMeshError {
+    /// shader didnt compile. log is the driver's info log, entries is it parsed, source is what we gave it
+    ShaderCompile { stage: ShaderStage, log: String, entries: Vec<LogEntry>, source: String },
+    /// shaders compiled but the program didnt link
+    Link { log: String },
+    /// glCreate* failed, string is whatever glow gave us
+    ResourceCreation(String),
+    /// couldnt read a file, eg. a shader being hot reloaded
+    Io { path: PathBuf, error: std::io::Error },
+    /// check_framebuffer_status wasnt FRAMEBUFFER_COMPLETE, u32 is what it was instead
+    IncompleteFramebuffer(u32),
+    /// a text format we read didnt make sense. what is the file or kind of file, line counts from 1
+    Parse { what: String, line: usize, message: String },
+    /// a binary format we read didnt make sense. offset is in bytes from the start of the file
+    BinaryParse { what: String, offset: usize, message: String },
+    /// font file wouldnt load, message is from the font library
+    FontLoad { what: String, message: String },
+    /// an image is bigger than an atlas page, even with nothing else on it
+    AtlasImageTooBig { name: String, w: usize, h: usize },
+    /// png encoder or decoder didnt like it, string is the png crate's error
+    Png { path: PathBuf, error: String },
+    /// a glyph cache w x h cant be split into slots of slot_size, ie. slot_size is under 3 (a pixel and its gutters) or bigger than the cache
+    GlyphCacheSize { w: usize, h: usize, slot_size: usize },
+    /// a polygon ring has its points all in a line (or fewer than 3, or not finite). ring 0 is the outside, then the holes in order
+    DegenerateRing { ring: usize },
+    /// a PostEffect::Custom has a shader index add_custom never returned, count is how many there are
+    NoCustomEffect { shader: usize, count: usize },
+}
+
+impl fmt::Display for GlowMeshError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GlowMeshError::ShaderCompile { stage, log, entries, source } => {
+                if entries.iter().any(|e| e.line.is_some()) {
+                    write!(f, "{} shader failed to compile:\n{}", stage, annotate_source(source, entries))
+                } else {
+                    write!(f, "{} shader failed to compile:\n{}", stage, log)
+                }
+            },
+            GlowMeshError::Link { log } => write!(f, "program failed to link:\n{}", log),
+            GlowMeshError::ResourceCreation(e) => write!(f, "failed to create gl resource: {}", e),
+            GlowMeshError::Io { path, error } => write!(f, "{}: {}", path.display(), error),
+            GlowMeshError::IncompleteFramebuffer(status) => write!(f, "framebuffer incomplete: 0x{:x}", status),
+            GlowMeshError::Parse { what, line, message } => write!(f, "{}:{}: {}", what, line, message),
+            GlowMeshError::BinaryParse { what, offset, message } => write!(f, "{}: byte {}: {}", what, offset, message),
+            GlowMeshError::FontLoad { what, message } => write!(f, "{}: {}", what, message),
+            GlowMeshError::AtlasImageTooBig { name, w, h } => write!(f, "atlas image {} ({}x{}) doesnt fit on a page", name, w, h),
+            GlowMeshError::Png { path, error } => write!(f, "{}: {}", path.display(), error),
+            GlowMeshError::GlyphCacheSize { w, h, slot_size } => write!(f, "glyph cache {}x{} cant be split into {}x{} slots", w, h, slot_size, slot_size),
+            GlowMeshError::DegenerateRing { ring } => write!(f, "polygon ring {} is degenerate, its points are all in a line", ring),
+            GlowMeshError::NoCustomEffect { shader, count } => write!(f, "no custom post effect {}, only {} were added", shader, count),
+        }
+    }
+}
+
+impl GlowMeshError {
+    pub(crate) fn shader_compile(stage: ShaderStage, log: String, source: &str) -> Self {
+        GlowMeshError::ShaderCompile { stage, entries: parse_info_log(&log), log, source: source.to_string() }
+    }
+}
+
+impl std::error::Error for GlowMeshError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GlowMeshError::Io { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
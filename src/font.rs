@@ -0,0 +1,549 @@
+//! Bitmap fonts in the BMFont format (text or binary .fnt) and laying text out as textured quads.
+//! Text goes in y down with pos at the top left of the first line, like sprites, so draw it with a y down projection.
+//! size is the height of a line. Quads for every page go into the same buffer, fonts with more than one page
+//! can use layout_text and split by page. \r is ignored and a tab is 4 spaces.
+
+use minimg::*;
+use minvect::*;
+use std::collections::HashMap;
+use std::path::Path;
+use crate::error::*;
+use crate::screenshot::*;
+use crate::xyzrgbauv::*;
+
+/// one character, in pixels of its page
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Glyph {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+    /// from the pen position to the top left of the quad
+    pub xoffset: f32,
+    pub yoffset: f32,
+    pub xadvance: f32,
+    pub page: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct BitmapFont {
+    pub face: String,
+    pub line_height: f32,
+    /// from the top of a line to the baseline
+    pub base: f32,
+    /// size of the pages
+    pub scale_w: f32,
+    pub scale_h: f32,
+    /// page image file names, relative to the .fnt
+    pub pages: Vec<String>,
+    pub glyphs: HashMap<char, Glyph>,
+    /// extra advance between a pair
+    pub kerning: HashMap<(char, char), f32>,
+}
+
+impl BitmapFont {
+    /// text or binary, whichever it is
+    pub fn parse(bytes: &[u8], what: &str) -> Result<Self, GlowMeshError> {
+        if bytes.starts_with(b"BMF") {
+            Self::from_binary(bytes, what)
+        } else {
+            match std::str::from_utf8(bytes) {
+                Ok(s) => Self::from_text(s, what),
+                Err(_) => Err(GlowMeshError::Parse { what: what.to_string(), line: 1, message: "not utf8 text or a binary BMF".to_string() }),
+            }
+        }
+    }
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, GlowMeshError> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(|error| GlowMeshError::Io { path: path.to_path_buf(), error })?;
+        Self::parse(&bytes, &path.display().to_string())
+    }
+    /// the page pngs, fnt_path being where the font was loaded from
+    pub fn load_pages<P: AsRef<Path>>(&self, fnt_path: P) -> Result<Vec<ImageBuffer>, GlowMeshError> {
+        let dir = fnt_path.as_ref().parent().unwrap_or(Path::new(""));
+        self.pages.iter().map(|page| load_png(dir.join(page))).collect()
+    }
+
+    pub fn from_text(text: &str, what: &str) -> Result<Self, GlowMeshError> {
+        let mut font = BitmapFont::default();
+        // from common, page ids have to be below it
+        let mut page_count = 0;
+        for (i, line) in text.lines().enumerate() {
+            let err = |message: String| GlowMeshError::Parse { what: what.to_string(), line: i + 1, message };
+            let (tag, fields) = match tokenize(line) {
+                Some(t) => t,
+                None => continue,
+            };
+            let num = |key: &str| -> Result<f32, GlowMeshError> {
+                let v = fields.get(key).ok_or_else(|| err(format!("{} missing {}", tag, key)))?;
+                v.parse::<f32>().map_err(|_| err(format!("{}={} isnt a number", key, v)))
+            };
+            match tag {
+                "info" => font.face = fields.get("face").cloned().unwrap_or_default(),
+                "common" => {
+                    font.line_height = num("lineHeight")?;
+                    font.base = num("base")?;
+                    font.scale_w = num("scaleW")?;
+                    font.scale_h = num("scaleH")?;
+                    page_count = num("pages").unwrap_or(0.0) as usize;
+                },
+                "page" => {
+                    let id = num("id")? as usize;
+                    if id >= page_count {
+                        return Err(err(format!("page id {} isnt below common pages={}", id, page_count)));
+                    }
+                    // in order, so a bad id cant make room for pages that arent there
+                    if id > font.pages.len() {
+                        return Err(err(format!("page id {} comes before page {}", id, font.pages.len())));
+                    }
+                    let file = fields.get("file").ok_or_else(|| err("page missing file".to_string()))?;
+                    if id == font.pages.len() {
+                        font.pages.push(file.clone());
+                    } else {
+                        font.pages[id] = file.clone();
+                    }
+                },
+                "char" => {
+                    let id = num("id")? as u32;
+                    let c = char::from_u32(id).ok_or_else(|| err(format!("char id {} isnt a char", id)))?;
+                    font.glyphs.insert(c, Glyph {
+                        x: num("x")?,
+                        y: num("y")?,
+                        w: num("width")?,
+                        h: num("height")?,
+                        xoffset: num("xoffset")?,
+                        yoffset: num("yoffset")?,
+                        xadvance: num("xadvance")?,
+                        page: num("page").unwrap_or(0.0) as usize,
+                    });
+                },
+                "kerning" => {
+                    let first = char::from_u32(num("first")? as u32);
+                    let second = char::from_u32(num("second")? as u32);
+                    if let (Some(first), Some(second)) = (first, second) {
+                        font.kerning.insert((first, second), num("amount")?);
+                    }
+                },
+                _ => {},
+            }
+        }
+        if font.line_height == 0.0 || font.scale_w == 0.0 {
+            return Err(GlowMeshError::Parse { what: what.to_string(), line: 1, message: "no common line".to_string() });
+        }
+        Ok(font)
+    }
+
+    /// version 3 of the binary format
+    pub fn from_binary(bytes: &[u8], what: &str) -> Result<Self, GlowMeshError> {
+        let err = |offset: usize, message: &str| GlowMeshError::BinaryParse { what: what.to_string(), offset, message: message.to_string() };
+        if bytes.len() < 4 || &bytes[..3] != b"BMF" {
+            return Err(err(0, "not a binary BMF"));
+        }
+        if bytes[3] != 3 {
+            return Err(err(3, "only version 3 is supported"));
+        }
+        let u16_at = |b: &[u8], i: usize| u16::from_le_bytes([b[i], b[i + 1]]);
+        let i16_at = |b: &[u8], i: usize| i16::from_le_bytes([b[i], b[i + 1]]);
+        let u32_at = |b: &[u8], i: usize| u32::from_le_bytes([b[i], b[i + 1], b[i + 2], b[i + 3]]);
+
+        let mut font = BitmapFont::default();
+        let mut at = 4;
+        while at < bytes.len() {
+            if at + 5 > bytes.len() {
+                return Err(err(at, "truncated block header"));
+            }
+            let kind = bytes[at];
+            let len = u32_at(bytes, at + 1) as usize;
+            let start = at + 5;
+            let block = bytes.get(start..start + len).ok_or_else(|| err(at, "truncated block"))?;
+            match kind {
+                1 if block.len() > 14 => {
+                    let name = &block[14..];
+                    let end = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+                    font.face = String::from_utf8_lossy(&name[..end]).into_owned();
+                },
+                2 => {
+                    if block.len() < 10 {
+                        return Err(err(start, "common block too short"));
+                    }
+                    font.line_height = u16_at(block, 0) as f32;
+                    font.base = u16_at(block, 2) as f32;
+                    font.scale_w = u16_at(block, 4) as f32;
+                    font.scale_h = u16_at(block, 6) as f32;
+                },
+                3 => {
+                    font.pages = block.split(|&b| b == 0)
+                        .filter(|s| !s.is_empty())
+                        .map(|s| String::from_utf8_lossy(s).into_owned())
+                        .collect();
+                },
+                4 => {
+                    for c in block.chunks_exact(20) {
+                        let ch = match char::from_u32(u32_at(c, 0)) {
+                            Some(ch) => ch,
+                            None => continue,
+                        };
+                        font.glyphs.insert(ch, Glyph {
+                            x: u16_at(c, 4) as f32,
+                            y: u16_at(c, 6) as f32,
+                            w: u16_at(c, 8) as f32,
+                            h: u16_at(c, 10) as f32,
+                            xoffset: i16_at(c, 12) as f32,
+                            yoffset: i16_at(c, 14) as f32,
+                            xadvance: i16_at(c, 16) as f32,
+                            page: c[18] as usize,
+                        });
+                    }
+                },
+                5 => {
+                    for k in block.chunks_exact(10) {
+                        if let (Some(first), Some(second)) = (char::from_u32(u32_at(k, 0)), char::from_u32(u32_at(k, 4))) {
+                            font.kerning.insert((first, second), i16_at(k, 8) as f32);
+                        }
+                    }
+                },
+                _ => {},
+            }
+            at = start + len;
+        }
+        if font.line_height == 0.0 || font.scale_w == 0.0 {
+            return Err(err(4, "no common block"));
+        }
+        Ok(font)
+    }
+
+    /// the glyph for c, or ? if it doesnt have one
+    pub fn glyph(&self, c: char) -> Option<&Glyph> {
+        self.glyphs.get(&c).or_else(|| self.glyphs.get(&'?'))
+    }
+    pub fn kerning(&self, a: char, b: char) -> f32 {
+        self.kerning.get(&(a, b)).copied().unwrap_or(0.0)
+    }
+    /// width of a line of text in font pixels, not counting trailing spaces
+    fn line_width(&self, line: &[char]) -> f32 {
+        let end = line.iter().rposition(|&c| c != ' ').map(|i| i + 1).unwrap_or(0);
+        self.advance_width(&line[..end])
+    }
+    /// width of a line of text in font pixels, trailing spaces and all
+    fn advance_width(&self, line: &[char]) -> f32 {
+        let mut w = 0.0;
+        let mut prev = None;
+        for &c in line {
+            w += self.advance(prev, c);
+            prev = Some(c);
+        }
+        w
+    }
+    /// how far c moves the pen, coming after prev
+    fn advance(&self, prev: Option<char>, c: char) -> f32 {
+        let kerning = prev.map(|p| self.kerning(p, c)).unwrap_or(0.0);
+        kerning + self.glyph(c).map(|g| g.xadvance).unwrap_or(0.0)
+    }
+}
+
+/// key=value pairs of one text .fnt line, values unquoted
+fn tokenize(line: &str) -> Option<(&str, HashMap<&str, String>)> {
+    let line = line.trim();
+    let (tag, mut rest) = match line.find(' ') {
+        Some(i) => (&line[..i], line[i..].trim_start()),
+        None if !line.is_empty() => (line, ""),
+        None => return None,
+    };
+    let mut fields = HashMap::new();
+    while let Some(eq) = rest.find('=') {
+        let key = rest[..eq].trim();
+        let after = &rest[eq + 1..];
+        let (value, remaining) = if let Some(quoted) = after.strip_prefix('"') {
+            match quoted.find('"') {
+                Some(end) => (quoted[..end].to_string(), &quoted[end + 1..]),
+                None => (quoted.to_string(), ""),
+            }
+        } else {
+            match after.find(' ') {
+                Some(end) => (after[..end].to_string(), &after[end..]),
+                None => (after.to_string(), ""),
+            }
+        };
+        fields.insert(key, value);
+        rest = remaining.trim_start();
+    }
+    Some((tag, fields))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Align {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TextLayout {
+    /// wrap at spaces to stay within this, or anywhere if a word is longer than a line. Same units as pos
+    pub max_width: Option<f32>,
+    /// within max_width if there is one, otherwise within the widest line
+    pub align: Align,
+}
+
+/// a glyph with where it goes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlacedGlyph {
+    pub dest: Rect,
+    /// in pixels of the page
+    pub src: Rect,
+    pub page: usize,
+}
+
+/// a tab is this many spaces
+const TAB_SPACES: usize = 4;
+
+/// break s into lines of chars. \r is dropped and tabs become spaces
+fn break_lines(font: &BitmapFont, scale: f32, s: &str, max_width: Option<f32>) -> Vec<Vec<char>> {
+    let mut lines = vec![];
+    for paragraph in s.split('\n') {
+        let mut line: Vec<char> = vec![];
+        // of line, in font pixels, kept as it grows rather than measured every char
+        let mut w = 0.0;
+        let chars = paragraph.chars().flat_map(|c| match c {
+            '\t' => std::iter::repeat(' ').take(TAB_SPACES),
+            '\r' => std::iter::repeat(c).take(0),
+            _ => std::iter::repeat(c).take(1),
+        });
+        for c in chars {
+            w += font.advance(line.last().copied(), c);
+            line.push(c);
+            let max = match max_width {
+                Some(max) => max,
+                None => continue,
+            };
+            if c == ' ' || w * scale <= max || line.len() == 1 {
+                continue;
+            }
+            // too long, break after the last space or failing that before c
+            let at = match line.iter().rposition(|&c| c == ' ') {
+                Some(space) => space + 1,
+                None => line.len() - 1,
+            };
+            let rest = line.split_off(at);
+            lines.push(std::mem::replace(&mut line, rest));
+            w = font.advance_width(&line);
+            // the word fit before c so only c can be pushing it over
+            if line.len() > 1 && w * scale > max {
+                let rest = line.split_off(line.len() - 1);
+                lines.push(std::mem::replace(&mut line, rest));
+                w = font.advance_width(&line);
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// where every glyph of s goes
+pub fn layout_text(font: &BitmapFont, pos: Vec2, size: f32, s: &str, layout: &TextLayout) -> Vec<PlacedGlyph> {
+    let scale = size / font.line_height;
+    let lines = break_lines(font, scale, s, layout.max_width);
+    let widths: Vec<f32> = lines.iter().map(|l| font.line_width(l) * scale).collect();
+    let block_w = layout.max_width.unwrap_or_else(|| widths.iter().cloned().fold(0.0, f32::max));
+
+    let mut glyphs = vec![];
+    for (i, line) in lines.iter().enumerate() {
+        let mut x = match layout.align {
+            Align::Left => 0.0,
+            Align::Center => (block_w - widths[i]) / 2.0,
+            Align::Right => block_w - widths[i],
+        };
+        let y = i as f32 * size;
+        for (j, &c) in line.iter().enumerate() {
+            let g = match font.glyph(c) {
+                Some(g) => g,
+                None => continue,
+            };
+            if g.w > 0.0 && g.h > 0.0 {
+                glyphs.push(PlacedGlyph {
+                    dest: rect(pos.x + x + g.xoffset * scale, pos.y + y + g.yoffset * scale, g.w * scale, g.h * scale),
+                    src: rect(g.x, g.y, g.w, g.h),
+                    page: g.page,
+                });
+            }
+            x += g.xadvance * scale;
+            if let Some(&next) = line.get(j + 1) {
+                x += font.kerning(c, next) * scale;
+            }
+        }
+    }
+    glyphs
+}
+
+pub fn put_text(buf: &mut Vec<XYZRGBAUV>, font: &BitmapFont, pos: Vec2, size: f32, s: &str, col: Vec4, depth: f32) {
+    put_text_layout(buf, font, pos, size, s, &TextLayout::default(), col, depth);
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn put_text_layout(buf: &mut Vec<XYZRGBAUV>, font: &BitmapFont, pos: Vec2, size: f32, s: &str, layout: &TextLayout, col: Vec4, depth: f32) {
+    for g in layout_text(font, pos, size, s, layout) {
+        let uv = rect(g.src.xy.x / font.scale_w, g.src.xy.y / font.scale_h, g.src.wh.x / font.scale_w, g.src.wh.y / font.scale_h);
+        put_rect(buf, g.dest, uv, col, depth);
+    }
+}
+
+/// width and height s would take up
+pub fn measure_text(font: &BitmapFont, size: f32, s: &str) -> Vec2 {
+    measure_text_layout(font, size, s, &TextLayout::default())
+}
+
+/// width is of the widest line, even with a max_width
+pub fn measure_text_layout(font: &BitmapFont, size: f32, s: &str, layout: &TextLayout) -> Vec2 {
+    let scale = size / font.line_height;
+    let lines = break_lines(font, scale, s, layout.max_width);
+    let w = lines.iter().map(|l| font.line_width(l) * scale).fold(0.0, f32::max);
+    vec2(w, lines.len() as f32 * size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FNT: &str = "info face=\"Test Face\" size=8 bold=0\r
+common lineHeight=10 base=8 scaleW=64 scaleH=32 pages=1 packed=0\r
+page id=0 file=\"test_0.png\"\r
+chars count=4\r
+char id=32   x=0  y=0 width=0 height=0 xoffset=0 yoffset=0 xadvance=4 page=0 chnl=15\r
+char id=97   x=1  y=2 width=5 height=6 xoffset=0 yoffset=2 xadvance=6 page=0 chnl=15\r
+char id=98   x=7  y=2 width=5 height=8 xoffset=1 yoffset=0 xadvance=6 page=0 chnl=15\r
+char id=63   x=13 y=2 width=4 height=8 xoffset=0 yoffset=0 xadvance=5 page=0 chnl=15\r
+kernings count=1\r
+kerning first=97 second=98 amount=-1\r
+";
+
+    fn font() -> BitmapFont {
+        BitmapFont::parse(FNT.as_bytes(), "test.fnt").unwrap()
+    }
+
+    fn binary() -> Vec<u8> {
+        let mut b = b"BMF\x03".to_vec();
+        let mut block = |kind: u8, data: Vec<u8>| {
+            b.push(kind);
+            b.extend((data.len() as u32).to_le_bytes());
+            b.extend(data);
+        };
+        let mut info = vec![8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        info.extend(b"Test Face\0");
+        block(1, info);
+        let mut common = vec![];
+        for x in [10u16, 8, 64, 32, 1] {
+            common.extend(x.to_le_bytes());
+        }
+        common.extend([0, 0, 0, 0, 0]);
+        block(2, common);
+        block(3, b"test_0.png\0".to_vec());
+        let mut chars = vec![];
+        for (id, x, w, h, xoffset, yoffset, xadvance) in [(32u32, 0u16, 0u16, 0u16, 0i16, 0i16, 4i16), (97, 1, 5, 6, 0, 2, 6), (98, 7, 5, 8, 1, 0, 6), (63, 13, 4, 8, 0, 0, 5)] {
+            chars.extend(id.to_le_bytes());
+            chars.extend(x.to_le_bytes());
+            chars.extend((if id == 32 { 0u16 } else { 2 }).to_le_bytes());
+            chars.extend(w.to_le_bytes());
+            chars.extend(h.to_le_bytes());
+            chars.extend(xoffset.to_le_bytes());
+            chars.extend(yoffset.to_le_bytes());
+            chars.extend(xadvance.to_le_bytes());
+            chars.extend([0, 15]);
+        }
+        block(4, chars);
+        let mut kerning = vec![];
+        kerning.extend(97u32.to_le_bytes());
+        kerning.extend(98u32.to_le_bytes());
+        kerning.extend((-1i16).to_le_bytes());
+        block(5, kerning);
+        b
+    }
+
+    fn lines(s: &str, max_width: Option<f32>) -> Vec<String> {
+        break_lines(&font(), 1.0, s, max_width).into_iter().map(|l| l.into_iter().collect()).collect()
+    }
+
+    #[test]
+    fn text_format() {
+        let f = font();
+        assert_eq!(f.face, "Test Face");
+        assert_eq!((f.line_height, f.base, f.scale_w, f.scale_h), (10.0, 8.0, 64.0, 32.0));
+        assert_eq!(f.pages, vec!["test_0.png".to_string()]);
+        assert_eq!(f.glyphs.len(), 4);
+        assert_eq!(f.glyphs[&'b'], Glyph { x: 7.0, y: 2.0, w: 5.0, h: 8.0, xoffset: 1.0, yoffset: 0.0, xadvance: 6.0, page: 0 });
+        assert_eq!(f.kerning('a', 'b'), -1.0);
+        assert_eq!(f.kerning('b', 'a'), 0.0);
+        assert_eq!(f.glyph('z'), f.glyph('?'));
+    }
+
+    #[test]
+    fn text_format_errors() {
+        let line = |fnt: &str| match BitmapFont::from_text(fnt, "test.fnt") {
+            Err(GlowMeshError::Parse { line, .. }) => line,
+            other => panic!("expected a parse error, got {:?}", other),
+        };
+        assert_eq!(line("info face=x\n"), 1);
+        assert_eq!(line("common lineHeight=10 base=8 scaleW=64 scaleH=x\n"), 1);
+        assert_eq!(line("common lineHeight=10 base=8 scaleW=64 scaleH=32\nchar id=97 x=0\n"), 2);
+        // page ids have to be below common pages and come in order
+        assert_eq!(line("common lineHeight=10 base=8 scaleW=64 scaleH=32 pages=1\npage id=4000000000 file=\"x.png\"\n"), 2);
+        assert_eq!(line("common lineHeight=10 base=8 scaleW=64 scaleH=32 pages=2\npage id=1 file=\"x.png\"\n"), 2);
+        assert_eq!(line("page id=0 file=\"x.png\"\ncommon lineHeight=10 base=8 scaleW=64 scaleH=32 pages=1\n"), 1);
+        let two = "common lineHeight=10 base=8 scaleW=64 scaleH=32 pages=2\npage id=0 file=\"a.png\"\npage id=1 file=\"b.png\"\n";
+        assert_eq!(BitmapFont::from_text(two, "test.fnt").unwrap().pages, vec!["a.png".to_string(), "b.png".to_string()]);
+    }
+
+    #[test]
+    fn binary_format() {
+        assert_eq!(BitmapFont::parse(&binary(), "test.fnt").unwrap(), font());
+
+        let mut v2 = binary();
+        v2[3] = 2;
+        let err = BitmapFont::from_binary(&v2, "test.fnt").unwrap_err();
+        assert!(matches!(err, GlowMeshError::BinaryParse { offset: 3, .. }));
+        assert_eq!(err.to_string(), "test.fnt: byte 3: only version 3 is supported");
+        assert!(matches!(BitmapFont::from_binary(b"BMX\x03", "test.fnt"), Err(GlowMeshError::BinaryParse { offset: 0, .. })));
+        // the info block is a 5 byte header and 24 bytes from 4, so the cut off common block starts at 33
+        let truncated = &binary()[..40];
+        assert!(matches!(BitmapFont::from_binary(truncated, "test.fnt"), Err(GlowMeshError::BinaryParse { offset: 33, .. })));
+    }
+
+    #[test]
+    fn wrapping() {
+        // a and b are 6 wide, space 4, a b kerns by -1
+        assert_eq!(lines("aa ab bb", Some(30.0)), vec!["aa ab ", "bb"]);
+        assert_eq!(lines("aa ab bb", Some(43.0)), vec!["aa ab bb"]);
+        assert_eq!(lines("aa ab bb", None), vec!["aa ab bb"]);
+        // words longer than a line break anywhere
+        assert_eq!(lines("aaaaaaa", Some(20.0)), vec!["aaa", "aaa", "a"]);
+        assert_eq!(lines("b aaaaaaa", Some(20.0)), vec!["b ", "aaa", "aaa", "a"]);
+        // nothing fits, one char a line
+        assert_eq!(lines("ab", Some(1.0)), vec!["a", "b"]);
+        assert_eq!(lines("a\n\nb", Some(30.0)), vec!["a", "", "b"]);
+
+        let size = measure_text_layout(&font(), 20.0, "aa ab bb", &TextLayout { max_width: Some(60.0), align: Align::Left });
+        assert_eq!(size, vec2(54.0, 40.0));
+    }
+
+    #[test]
+    fn carriage_returns_and_tabs() {
+        assert_eq!(lines("ab\r\nb\r", None), vec!["ab", "b"]);
+        assert_eq!(lines("a\tb", None), vec!["a    b"]);
+        // no ? glyphs for either
+        assert_eq!(measure_text(&font(), 10.0, "a\tb\r\n"), vec2(6.0 + 16.0 + 6.0, 20.0));
+        assert_eq!(lines("aa\tbb", Some(30.0)), vec!["aa    ", "bb"]);
+    }
+
+    #[test]
+    fn layout() {
+        let glyphs = layout_text(&font(), vec2(100.0, 50.0), 20.0, "ab", &TextLayout::default());
+        assert_eq!(glyphs.len(), 2);
+        assert_eq!(glyphs[0].dest, rect(100.0, 54.0, 10.0, 12.0));
+        // 6 advance, -1 kerning, 1 xoffset, all doubled
+        assert_eq!(glyphs[1].dest, rect(112.0, 50.0, 10.0, 16.0));
+        assert_eq!(glyphs[1].src, rect(7.0, 2.0, 5.0, 8.0));
+
+        let right = layout_text(&font(), vec2(0.0, 0.0), 10.0, "a", &TextLayout { max_width: Some(20.0), align: Align::Right });
+        assert_eq!(right[0].dest.xy.x, 14.0);
+    }
+}
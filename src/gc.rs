@@ -0,0 +1,100 @@
+//! Opt-in ownership for gpu resources.
+//! Handles dont have the context so they cant free themselves on drop. Instead wrap them with
+//! Renderer::own: dropping the Owned handle queues it, and Renderer::collect_garbage frees
+//! everything queued, eg. once a frame.
+//! Handles that arent owned still need free() and complain in debug builds if they are dropped without it.
+
+use glow::HasContext;
+use std::cell::{Cell, RefCell};
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+
+/// a handle to something on the gpu that can be freed given the context
+pub trait Free<G: HasContext = glow::Context> {
+    fn free(&self, gl: &G);
+}
+
+type Garbage<G> = Rc<RefCell<Vec<Box<dyn Free<G>>>>>;
+
+pub struct Renderer<G: HasContext + 'static = glow::Context> {
+    garbage: Garbage<G>,
+}
+
+impl<G: HasContext + 'static> Default for Renderer<G> {
+    fn default() -> Self {
+        Renderer { garbage: Rc::new(RefCell::new(vec![])) }
+    }
+}
+
+impl<G: HasContext + 'static> Renderer<G> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// take ownership of a handle, it gets freed by collect_garbage after the Owned is dropped.
+    /// Dont call free on it yourself.
+    pub fn own<T: Free<G> + 'static>(&self, handle: T) -> Owned<T, G> {
+        Owned {
+            handle: Some(handle),
+            garbage: self.garbage.clone(),
+        }
+    }
+    /// free everything that was dropped since last time
+    pub fn collect_garbage(&self, gl: &G) {
+        let garbage = std::mem::take(&mut *self.garbage.borrow_mut());
+        for handle in garbage {
+            handle.free(gl);
+        }
+    }
+    pub fn garbage_len(&self) -> usize {
+        self.garbage.borrow().len()
+    }
+}
+
+pub struct Owned<T: Free<G> + 'static, G: HasContext + 'static = glow::Context> {
+    handle: Option<T>,
+    garbage: Garbage<G>,
+}
+
+impl<T: Free<G> + 'static, G: HasContext + 'static> Deref for Owned<T, G> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.handle.as_ref().unwrap()
+    }
+}
+
+impl<T: Free<G> + 'static, G: HasContext + 'static> DerefMut for Owned<T, G> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.handle.as_mut().unwrap()
+    }
+}
+
+impl<T: Free<G> + 'static, G: HasContext + 'static> Drop for Owned<T, G> {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            self.garbage.borrow_mut().push(Box::new(handle));
+        }
+    }
+}
+
+/// goes in each handle, warns in debug builds if it gets dropped before free() was called
+pub(crate) struct LeakCheck {
+    freed: Cell<bool>,
+    what: &'static str,
+}
+
+impl LeakCheck {
+    pub(crate) fn new(what: &'static str) -> Self {
+        LeakCheck { freed: Cell::new(false), what }
+    }
+    pub(crate) fn freed(&self) {
+        self.freed.set(true);
+    }
+}
+
+impl Drop for LeakCheck {
+    fn drop(&mut self) {
+        if cfg!(debug_assertions) && !self.freed.get() && !std::thread::panicking() {
+            eprintln!("glow_mesh: {} dropped without being freed, its gpu memory is leaked. Call free() or use Renderer::own", self.what);
+        }
+    }
+}
@@ -1,2 +1,30 @@
+// so derive(Vertex) output can name ::glow_mesh from inside this crate too
+extern crate self as glow_mesh;
+
+pub use glow;
+pub use glow_mesh_derive::Vertex;
+
+pub mod error;
+pub mod shader_log;
+pub mod gc;
+pub mod vertex;
+pub mod program;
+pub mod mesh;
+pub mod texture;
+pub mod triangulate;
+pub mod path;
 pub mod xyzrgba;
-pub mod xyzrgbauv;
\ No newline at end of file
+pub mod xyzrgbauv;
+pub mod raster;
+pub mod screenshot;
+pub mod render_target;
+pub mod post;
+pub mod sprite;
+pub mod atlas;
+pub mod font;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+#[cfg(any(test, feature = "testing"))]
+pub mod snapshot;
+#[cfg(feature = "truetype")]
+pub mod truetype;
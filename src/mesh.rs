@@ -0,0 +1,259 @@
+use glow::HasContext;
+use std::marker::PhantomData;
+use crate::vertex::*;
+use crate::error::*;
+use crate::gc::*;
+
+/// buffer usage hint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Usage {
+    /// uploaded once, drawn lots
+    #[default]
+    Static,
+    /// updated sometimes
+    Dynamic,
+    /// updated every frame
+    Stream,
+}
+
+impl Usage {
+    pub fn gl_enum(&self) -> u32 {
+        match self {
+            Usage::Static => glow::STATIC_DRAW,
+            Usage::Dynamic => glow::DYNAMIC_DRAW,
+            Usage::Stream => glow::STREAM_DRAW,
+        }
+    }
+}
+
+/// u16 or u32 element indices
+pub trait Index: Copy {
+    const GL_TYPE: u32;
+    /// panics if i doesnt fit, eg. more than 65536 vertices with u16 indices
+    fn from_usize(i: usize) -> Self;
+}
+
+impl Index for u16 {
+    const GL_TYPE: u32 = glow::UNSIGNED_SHORT;
+    fn from_usize(i: usize) -> Self {
+        u16::try_from(i).expect("too many vertices for u16 indices")
+    }
+}
+
+impl Index for u32 {
+    const GL_TYPE: u32 = glow::UNSIGNED_INT;
+    fn from_usize(i: usize) -> Self {
+        u32::try_from(i).expect("too many vertices for u32 indices")
+    }
+}
+
+/// cpu side vertices + indices, for building up shared vertex geometry before uploading
+pub struct IndexedMesh<V: Vertex, I: Index = u32> {
+    pub vertices: Vec<V>,
+    pub indices: Vec<I>,
+}
+
+impl<V: Vertex, I: Index> Default for IndexedMesh<V, I> {
+    fn default() -> Self {
+        IndexedMesh { vertices: vec![], indices: vec![] }
+    }
+}
+
+impl<V: Vertex, I: Index> IndexedMesh<V, I> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// returns the index of the vertex for use in push_triangle
+    pub fn push_vertex(&mut self, v: V) -> I {
+        self.vertices.push(v);
+        I::from_usize(self.vertices.len() - 1)
+    }
+    pub fn push_triangle(&mut self, a: I, b: I, c: I) {
+        self.indices.push(a);
+        self.indices.push(b);
+        self.indices.push(c);
+    }
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+        self.indices.clear();
+    }
+}
+
+/// element buffer part of an indexed Mesh
+pub struct IndexBuffer<G: HasContext = glow::Context> {
+    pub ebo: G::Buffer,
+    pub num_indices: usize,
+    /// glow::UNSIGNED_SHORT or glow::UNSIGNED_INT
+    pub index_type: u32,
+}
+
+/// a vertex buffer on the gpu, for any vertex type. Drawn with draw_elements if it has indices
+pub struct Mesh<V: Vertex, G: HasContext = glow::Context> {
+    pub vao: G::VertexArray,
+    pub vbo: G::Buffer,
+    pub num_verts: usize,
+    pub indices: Option<IndexBuffer<G>>,
+    vertex: PhantomData<V>,
+    leak: LeakCheck,
+}
+
+unsafe fn create_vao_vbo<V: Vertex, G: HasContext>(gl: &G) -> Result<(G::VertexArray, G::Buffer), GlowMeshError> {
+    let vbo = gl.create_buffer().map_err(GlowMeshError::ResourceCreation)?;
+    gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
+    let vao = match gl.create_vertex_array() {
+        Ok(vao) => vao,
+        Err(e) => {
+            gl.delete_buffer(vbo);
+            return Err(GlowMeshError::ResourceCreation(e));
+        },
+    };
+    gl.bind_vertex_array(Some(vao));
+    bind_attribs::<V, G>(gl);
+    Ok((vao, vbo))
+}
+
+/// # Safety
+/// gl has to be current. The mesh belongs to that context, render and free it there
+pub unsafe fn upload_mesh<V: Vertex, G: HasContext>(mesh: &[V], gl: &G) -> Result<Mesh<V, G>, GlowMeshError> {
+    upload_mesh_with_usage(mesh, Usage::Static, gl)
+}
+
+/// # Safety
+/// same as upload_mesh
+pub unsafe fn upload_mesh_with_usage<V: Vertex, G: HasContext>(mesh: &[V], usage: Usage, gl: &G) -> Result<Mesh<V, G>, GlowMeshError> {
+    let (vao, vbo) = create_vao_vbo::<V, G>(gl)?;
+    gl.buffer_data_u8_slice(glow::ARRAY_BUFFER, as_bytes(mesh), usage.gl_enum());
+    Ok(Mesh {vao, vbo, num_verts: mesh.len(), indices: None, vertex: PhantomData, leak: LeakCheck::new("Mesh")})
+}
+
+/// # Safety
+/// gl has to be current, and every index has to be less than the number of vertices or drawing reads past the buffer
+pub unsafe fn upload_indexed_mesh<V: Vertex, I: Index, G: HasContext>(mesh: &IndexedMesh<V, I>, gl: &G) -> Result<Mesh<V, G>, GlowMeshError> {
+    upload_indexed_mesh_with_usage(mesh, Usage::Static, gl)
+}
+
+/// # Safety
+/// same as upload_indexed_mesh
+pub unsafe fn upload_indexed_mesh_with_usage<V: Vertex, I: Index, G: HasContext>(mesh: &IndexedMesh<V, I>, usage: Usage, gl: &G) -> Result<Mesh<V, G>, GlowMeshError> {
+    let (vao, vbo) = create_vao_vbo::<V, G>(gl)?;
+    gl.buffer_data_u8_slice(glow::ARRAY_BUFFER, as_bytes(&mesh.vertices), usage.gl_enum());
+    let ebo = match gl.create_buffer() {
+        Ok(ebo) => ebo,
+        Err(e) => {
+            gl.delete_vertex_array(vao);
+            gl.delete_buffer(vbo);
+            return Err(GlowMeshError::ResourceCreation(e));
+        },
+    };
+    // bound while the vao is, so the vao remembers it
+    gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(ebo));
+    let index_bytes: &[u8] = std::slice::from_raw_parts(
+        mesh.indices.as_ptr() as *const u8,
+        std::mem::size_of_val(mesh.indices.as_slice()),
+    );
+    gl.buffer_data_u8_slice(glow::ELEMENT_ARRAY_BUFFER, index_bytes, usage.gl_enum());
+    Ok(Mesh {
+        vao,
+        vbo,
+        num_verts: mesh.vertices.len(),
+        indices: Some(IndexBuffer { ebo, num_indices: mesh.indices.len(), index_type: I::GL_TYPE }),
+        vertex: PhantomData,
+        leak: LeakCheck::new("Mesh"),
+    })
+}
+
+impl<V: Vertex, G: HasContext> Mesh<V, G> {
+    /// # Safety
+    /// gl has to be the context this was uploaded on, current, with a program bound that takes V
+    pub unsafe fn render(&self, gl: &G) {
+        gl.bind_vertex_array(Some(self.vao));
+        match &self.indices {
+            Some(ib) => gl.draw_elements(glow::TRIANGLES, ib.num_indices as i32, ib.index_type, 0),
+            None => gl.draw_arrays(glow::TRIANGLES, 0, self.num_verts as i32),
+        }
+    }
+
+    /// definitely want to call this when dropping the buffer, or hand it to Renderer::own
+    pub fn free(&self, gl: &G) {
+        unsafe {
+            gl.delete_vertex_array(self.vao);
+            gl.delete_buffer(self.vbo);
+            if let Some(ib) = &self.indices {
+                gl.delete_buffer(ib.ebo);
+            }
+        }
+        self.leak.freed();
+    }
+}
+
+impl<V: Vertex + 'static, G: HasContext> Free<G> for Mesh<V, G> {
+    fn free(&self, gl: &G) {
+        Mesh::free(self, gl)
+    }
+}
+
+/// a mesh that keeps its buffer around to be overwritten, eg. geometry rebuilt every frame.
+/// update uses buffer_sub_data while the new data fits and only reallocates when it has to grow.
+pub struct DynamicMesh<V: Vertex, G: HasContext = glow::Context> {
+    pub vao: G::VertexArray,
+    pub vbo: G::Buffer,
+    pub num_verts: usize,
+    /// in vertices
+    pub capacity: usize,
+    pub usage: Usage,
+    vertex: PhantomData<V>,
+    leak: LeakCheck,
+}
+
+impl<V: Vertex, G: HasContext> DynamicMesh<V, G> {
+    /// # Safety
+    /// gl has to be current
+    pub unsafe fn new(usage: Usage, gl: &G) -> Result<Self, GlowMeshError> {
+        Self::with_capacity(0, usage, gl)
+    }
+    /// # Safety
+    /// gl has to be current
+    pub unsafe fn with_capacity(capacity: usize, usage: Usage, gl: &G) -> Result<Self, GlowMeshError> {
+        let (vao, vbo) = create_vao_vbo::<V, G>(gl)?;
+        if capacity > 0 {
+            gl.buffer_data_size(glow::ARRAY_BUFFER, (capacity * std::mem::size_of::<V>()) as i32, usage.gl_enum());
+        }
+        Ok(DynamicMesh {vao, vbo, num_verts: 0, capacity, usage, vertex: PhantomData, leak: LeakCheck::new("DynamicMesh")})
+    }
+    /// replace the contents with mesh
+    ///
+    /// # Safety
+    /// gl has to be the context this was made on, and current. Leaves the vbo bound
+    pub unsafe fn update(&mut self, mesh: &[V], gl: &G) {
+        gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
+        if mesh.len() > self.capacity {
+            // grow geometrically so a slowly growing mesh doesnt realloc every frame
+            self.capacity = mesh.len().max(self.capacity * 2);
+            gl.buffer_data_size(glow::ARRAY_BUFFER, (self.capacity * std::mem::size_of::<V>()) as i32, self.usage.gl_enum());
+        }
+        gl.buffer_sub_data_u8_slice(glow::ARRAY_BUFFER, 0, as_bytes(mesh));
+        self.num_verts = mesh.len();
+    }
+    /// # Safety
+    /// same as Mesh::render
+    pub unsafe fn render(&self, gl: &G) {
+        if self.num_verts == 0 {
+            return;
+        }
+        gl.bind_vertex_array(Some(self.vao));
+        gl.draw_arrays(glow::TRIANGLES, 0, self.num_verts as i32);
+    }
+    pub fn free(&self, gl: &G) {
+        unsafe {
+            gl.delete_vertex_array(self.vao);
+            gl.delete_buffer(self.vbo);
+        }
+        self.leak.freed();
+    }
+}
+
+impl<V: Vertex + 'static, G: HasContext> Free<G> for DynamicMesh<V, G> {
+    fn free(&self, gl: &G) {
+        DynamicMesh::free(self, gl)
+    }
+}
@@ -0,0 +1,933 @@
+//! Curves and paths made of them, flattened into polylines when drawn so they stay smooth at any scale.
+//! Flattening takes a tolerance: how far the straight pieces are allowed to stray from the true curve, in the same units
+//! as the points. 0.25 is about right in pixels; with some other projection, divide by pixels per unit.
+//! ```ignore
+//! let mut path = Path::new();
+//! path.move_to(vec2(10.0, 10.0)).cubic_to(vec2(40.0, 0.0), vec2(60.0, 40.0), vec2(90.0, 10.0)).line_to(vec2(90.0, 60.0)).close();
+//! put_path(buf, &path, FillRule::NonZero, 0.25, col, depth);
+//! put_path_stroke(buf, &path, &StrokeStyle::new(2.0), outline_col, depth);
+//! ```
+//! Path::from_svg reads svg path data, the d attribute, so shapes can come straight out of Inkscape and the like.
+//! Paths can overlap themselves and have holes going either way round, and the fill rule decides what's inside.
+//! Filling ear clips (see triangulate) when no edges cross, otherwise it cuts the shape into horizontal
+//! trapezoids between every vertex and crossing.
+
+use minvect::*;
+use std::f32::consts::PI;
+use crate::error::*;
+use crate::triangulate::*;
+
+/// how many straight pieces an arc of radius r needs to stay within tolerance of the circle
+pub(crate) fn arc_steps(r: f32, angle: f32, tolerance: f32) -> usize {
+    let step = if tolerance < r { 2.0 * (1.0 - tolerance / r).acos() } else { PI / 2.0 };
+    ((angle.abs() / step.min(PI / 2.0)).ceil() as usize).clamp(1, 256)
+}
+
+/// pieces for a bezier whose second differences are at most dd long, by Wang's formula
+fn bezier_steps(dd: f32, degree: f32, tolerance: f32) -> usize {
+    let steps = (degree * (degree - 1.0) * dd / (8.0 * tolerance)).sqrt().ceil();
+    if steps.is_finite() { (steps as usize).clamp(1, 1024) } else { 1024 }
+}
+
+fn length(v: Vec2) -> f32 {
+    v.dot(v).sqrt()
+}
+
+/// append points along a quadratic bezier from p0, not including p0 itself
+pub fn flatten_quadratic(out: &mut Vec<Vec2>, p0: Vec2, p1: Vec2, p2: Vec2, tolerance: f32) {
+    let steps = bezier_steps(length(p0 - 2.0 * p1 + p2), 2.0, tolerance);
+    for i in 1..steps {
+        let t = i as f32 / steps as f32;
+        let u = 1.0 - t;
+        out.push(u * u * p0 + 2.0 * u * t * p1 + t * t * p2);
+    }
+    out.push(p2);
+}
+
+/// append points along a cubic bezier from p0, not including p0 itself
+pub fn flatten_cubic(out: &mut Vec<Vec2>, p0: Vec2, p1: Vec2, p2: Vec2, p3: Vec2, tolerance: f32) {
+    let dd = length(p0 - 2.0 * p1 + p2).max(length(p1 - 2.0 * p2 + p3));
+    let steps = bezier_steps(dd, 3.0, tolerance);
+    for i in 1..steps {
+        let t = i as f32 / steps as f32;
+        let u = 1.0 - t;
+        out.push(u * u * u * p0 + 3.0 * u * u * t * p1 + 3.0 * u * t * t * p2 + t * t * t * p3);
+    }
+    out.push(p3);
+}
+
+/// point on an ellipse with radii turned by rotation, at angle (before the rotation)
+pub fn ellipse_point(centre: Vec2, radii: Vec2, rotation: f32, angle: f32) -> Vec2 {
+    let (s, c) = rotation.sin_cos();
+    let p = vec2(radii.x * angle.cos(), radii.y * angle.sin());
+    centre + vec2(p.x * c - p.y * s, p.x * s + p.y * c)
+}
+
+/// append points along an elliptical arc, not including the one at start. Angles in radians, positive sweeps go from +x towards +y
+pub fn flatten_arc(out: &mut Vec<Vec2>, centre: Vec2, radii: Vec2, rotation: f32, start: f32, sweep: f32, tolerance: f32) {
+    let steps = arc_steps(radii.x.abs().max(radii.y.abs()), sweep, tolerance);
+    for i in 1..=steps {
+        out.push(ellipse_point(centre, radii, rotation, start + sweep * i as f32 / steps as f32));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathSegment {
+    /// start a new subpath
+    MoveTo(Vec2),
+    LineTo(Vec2),
+    /// control point, end
+    QuadTo(Vec2, Vec2),
+    /// two control points, end
+    CubicTo(Vec2, Vec2, Vec2),
+    /// around an ellipse with radii turned by rotation, from angle start by sweep radians. to is where that ends
+    /// up, kept so the next segment joins on exactly
+    ArcTo { centre: Vec2, radii: Vec2, rotation: f32, start: f32, sweep: f32, to: Vec2 },
+    /// line back to the start of the subpath and join it up
+    Close,
+}
+
+/// a flattened subpath
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subpath {
+    pub points: Vec<Vec2>,
+    pub closed: bool,
+}
+
+/// which parts of a path that crosses over itself count as inside, same as svg's fill-rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FillRule {
+    /// inside if the path winds round it at all, so holes have to go the other way round
+    #[default]
+    NonZero,
+    /// inside if crossed an odd number of times, so any overlap is a hole
+    EvenOdd,
+}
+
+/// lines and curves, in subpaths started by move_to. Drawing without a move_to starts at the origin
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path {
+    segments: Vec<PathSegment>,
+    start: Vec2,
+    current: Vec2,
+}
+
+impl Default for Path {
+    fn default() -> Self {
+        Path::new()
+    }
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Path { segments: vec![], start: vec2(0.0, 0.0), current: vec2(0.0, 0.0) }
+    }
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+    /// where the next segment starts from
+    pub fn current(&self) -> Vec2 {
+        self.current
+    }
+    /// start of the current subpath, where close goes back to
+    pub fn subpath_start(&self) -> Vec2 {
+        self.start
+    }
+    pub fn move_to(&mut self, p: Vec2) -> &mut Self {
+        self.segments.push(PathSegment::MoveTo(p));
+        self.start = p;
+        self.current = p;
+        self
+    }
+    pub fn line_to(&mut self, p: Vec2) -> &mut Self {
+        self.segments.push(PathSegment::LineTo(p));
+        self.current = p;
+        self
+    }
+    pub fn quad_to(&mut self, control: Vec2, p: Vec2) -> &mut Self {
+        self.segments.push(PathSegment::QuadTo(control, p));
+        self.current = p;
+        self
+    }
+    pub fn cubic_to(&mut self, control1: Vec2, control2: Vec2, p: Vec2) -> &mut Self {
+        self.segments.push(PathSegment::CubicTo(control1, control2, p));
+        self.current = p;
+        self
+    }
+    /// elliptical arc around centre from angle start by sweep radians, with a line from the current point to where it
+    /// starts. A sweep of 2 PI or more goes all the way round
+    pub fn arc(&mut self, centre: Vec2, radii: Vec2, rotation: f32, start: f32, sweep: f32) -> &mut Self {
+        let from = ellipse_point(centre, radii, rotation, start);
+        if self.segments.is_empty() {
+            self.move_to(from);
+        } else if from != self.current {
+            self.line_to(from);
+        }
+        let to = ellipse_point(centre, radii, rotation, start + sweep);
+        self.segments.push(PathSegment::ArcTo { centre, radii, rotation, start, sweep, to });
+        self.current = to;
+        self
+    }
+    /// svg style arc from the current point to p, on an ellipse with radii turned by rotation. Of the four arcs that
+    /// fit, large_arc picks one of the two going more than half way round, sweep one of the two going in the positive
+    /// angle direction. Radii too small to reach get scaled up, and zero radii make a straight line
+    pub fn arc_to(&mut self, radii: Vec2, rotation: f32, large_arc: bool, sweep: bool, p: Vec2) -> &mut Self {
+        let from = self.current;
+        if from == p {
+            return self;
+        }
+        let (mut rx, mut ry) = (radii.x.abs(), radii.y.abs());
+        if rx == 0.0 || ry == 0.0 {
+            return self.line_to(p);
+        }
+        // svg spec appendix F.6.5, endpoints to centre
+        let (s, c) = rotation.sin_cos();
+        let h = (from - p) / 2.0;
+        let x1 = c * h.x + s * h.y;
+        let y1 = -s * h.x + c * h.y;
+        let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+        if lambda > 1.0 {
+            rx *= lambda.sqrt();
+            ry *= lambda.sqrt();
+        }
+        let num = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+        let den = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+        let mut coef = (num / den).max(0.0).sqrt();
+        if large_arc == sweep {
+            coef = -coef;
+        }
+        let cx1 = coef * rx * y1 / ry;
+        let cy1 = -coef * ry * x1 / rx;
+        let mid = (from + p) / 2.0;
+        let centre = vec2(c * cx1 - s * cy1 + mid.x, s * cx1 + c * cy1 + mid.y);
+        let u = vec2((x1 - cx1) / rx, (y1 - cy1) / ry);
+        let v = vec2((-x1 - cx1) / rx, (-y1 - cy1) / ry);
+        let start = u.y.atan2(u.x);
+        let mut delta = u.cross(v).atan2(u.dot(v));
+        if !sweep && delta > 0.0 {
+            delta -= 2.0 * PI;
+        } else if sweep && delta < 0.0 {
+            delta += 2.0 * PI;
+        }
+        self.segments.push(PathSegment::ArcTo { centre, radii: vec2(rx, ry), rotation, start, sweep: delta, to: p });
+        self.current = p;
+        self
+    }
+    pub fn close(&mut self) -> &mut Self {
+        self.segments.push(PathSegment::Close);
+        self.current = self.start;
+        self
+    }
+
+    /// the subpaths as polylines, curves within tolerance of the real thing
+    pub fn flatten(&self, tolerance: f32) -> Vec<Subpath> {
+        let mut subpaths = vec![];
+        let mut points: Vec<Vec2> = vec![];
+        let mut start = vec2(0.0, 0.0);
+        for segment in &self.segments {
+            // drawing after a close carries on from the closed subpath's start
+            if points.is_empty() && !matches!(segment, PathSegment::MoveTo(_)) {
+                points.push(start);
+            }
+            let from = *points.last().unwrap_or(&start);
+            match *segment {
+                PathSegment::MoveTo(p) => {
+                    if points.len() > 1 {
+                        subpaths.push(Subpath { points: std::mem::take(&mut points), closed: false });
+                    }
+                    points.clear();
+                    points.push(p);
+                    start = p;
+                },
+                PathSegment::LineTo(p) => points.push(p),
+                PathSegment::QuadTo(c, p) => flatten_quadratic(&mut points, from, c, p, tolerance),
+                PathSegment::CubicTo(c1, c2, p) => flatten_cubic(&mut points, from, c1, c2, p, tolerance),
+                PathSegment::ArcTo { centre, radii, rotation, start, sweep, to } => {
+                    flatten_arc(&mut points, centre, radii, rotation, start, sweep, tolerance);
+                    points.pop();
+                    points.push(to);
+                },
+                PathSegment::Close => {
+                    if points.len() > 1 && points.last() == Some(&start) {
+                        points.pop();
+                    }
+                    subpaths.push(Subpath { points: std::mem::take(&mut points), closed: true });
+                },
+            }
+        }
+        if points.len() > 1 {
+            subpaths.push(Subpath { points, closed: false });
+        }
+        subpaths
+    }
+
+    /// triangles filling the path, with every subpath taken as closed
+    pub fn fill(&self, rule: FillRule, tolerance: f32) -> Vec<[Vec2; 3]> {
+        let rings: Vec<Vec<Vec2>> = self.flatten(tolerance).into_iter().map(|s| s.points).collect();
+        fill_rings(&rings, rule)
+    }
+}
+
+impl Path {
+    /// parse svg path data, eg. "M10 10 h 20 c 5 0 10 5 10 10 Z". Takes all of M L H V C S Q T A Z, absolute and
+    /// relative. Anything malformed is a GlowMeshError::Parse with the column in the message
+    pub fn from_svg(d: &str) -> Result<Path, GlowMeshError> {
+        let mut p = SvgParser { s: d.as_bytes(), d, pos: 0 };
+        let mut path = Path::new();
+        let mut command: Option<u8> = None;
+        // previous command and its last control point, for S and T to reflect
+        let mut prev: (u8, Vec2) = (b'M', vec2(0.0, 0.0));
+        loop {
+            p.skip_separators();
+            let c = match p.s.get(p.pos) {
+                Some(&c) => c,
+                None => break,
+            };
+            let cmd = if c.is_ascii_alphabetic() {
+                p.pos += 1;
+                c
+            } else {
+                match command {
+                    // numbers after a moveto are linetos
+                    Some(b'M') => b'L',
+                    Some(b'm') => b'l',
+                    Some(c) if !c.eq_ignore_ascii_case(&b'z') => c,
+                    _ => return Err(p.error("expected a command")),
+                }
+            };
+            if path.segments.is_empty() && !cmd.eq_ignore_ascii_case(&b'm') {
+                return Err(p.error_at(p.pos - 1, "path data has to start with M or m"));
+            }
+            let rel = cmd.is_ascii_lowercase();
+            let origin = if rel { path.current() } else { vec2(0.0, 0.0) };
+            let cur = path.current();
+            let mut control = cur;
+            match cmd.to_ascii_uppercase() {
+                b'M' => {
+                    path.move_to(origin + p.point()?);
+                },
+                b'L' => {
+                    path.line_to(origin + p.point()?);
+                },
+                b'H' => {
+                    let x = p.number()?;
+                    path.line_to(vec2(if rel { cur.x + x } else { x }, cur.y));
+                },
+                b'V' => {
+                    let y = p.number()?;
+                    path.line_to(vec2(cur.x, if rel { cur.y + y } else { y }));
+                },
+                b'C' => {
+                    let c1 = origin + p.point()?;
+                    control = origin + p.point()?;
+                    path.cubic_to(c1, control, origin + p.point()?);
+                },
+                b'S' => {
+                    let c1 = if matches!(prev.0, b'C' | b'S') { 2.0 * cur - prev.1 } else { cur };
+                    control = origin + p.point()?;
+                    path.cubic_to(c1, control, origin + p.point()?);
+                },
+                b'Q' => {
+                    control = origin + p.point()?;
+                    path.quad_to(control, origin + p.point()?);
+                },
+                b'T' => {
+                    control = if matches!(prev.0, b'Q' | b'T') { 2.0 * cur - prev.1 } else { cur };
+                    path.quad_to(control, origin + p.point()?);
+                },
+                b'A' => {
+                    let radii = p.point()?;
+                    let rotation = p.number()?.to_radians();
+                    let large_arc = p.flag()?;
+                    let sweep = p.flag()?;
+                    path.arc_to(radii, rotation, large_arc, sweep, origin + p.point()?);
+                },
+                b'Z' => {
+                    path.close();
+                },
+                _ => return Err(p.error_at(p.pos - 1, &format!("unknown command {}", cmd as char))),
+            }
+            command = Some(cmd);
+            prev = (cmd.to_ascii_uppercase(), control);
+        }
+        Ok(path)
+    }
+}
+
+struct SvgParser<'a> {
+    s: &'a [u8],
+    d: &'a str,
+    pos: usize,
+}
+
+impl SvgParser<'_> {
+    fn error_at(&self, pos: usize, message: &str) -> GlowMeshError {
+        let before = &self.d[..pos.min(self.d.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before[before.rfind('\n').map(|i| i + 1).unwrap_or(0)..].chars().count() + 1;
+        GlowMeshError::Parse { what: "svg path".to_string(), line, message: format!("column {}: {}", column, message) }
+    }
+    fn error(&self, message: &str) -> GlowMeshError {
+        self.error_at(self.pos, message)
+    }
+    fn skip_separators(&mut self) {
+        while let Some(c) = self.s.get(self.pos) {
+            if c.is_ascii_whitespace() || *c == b',' {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+    }
+    /// numbers can run into each other, eg. "1.5.5-2" is 1.5, .5 and -2
+    fn number(&mut self) -> Result<f32, GlowMeshError> {
+        self.skip_separators();
+        let start = self.pos;
+        let digits = |p: &mut Self| {
+            let from = p.pos;
+            while p.s.get(p.pos).is_some_and(|c| c.is_ascii_digit()) {
+                p.pos += 1;
+            }
+            p.pos > from
+        };
+        if matches!(self.s.get(self.pos), Some(b'+' | b'-')) {
+            self.pos += 1;
+        }
+        let mut any = digits(self);
+        if self.s.get(self.pos) == Some(&b'.') {
+            self.pos += 1;
+            any |= digits(self);
+        }
+        if !any {
+            self.pos = start;
+            return Err(self.error("expected a number"));
+        }
+        if matches!(self.s.get(self.pos), Some(b'e' | b'E')) {
+            let mantissa_end = self.pos;
+            self.pos += 1;
+            if matches!(self.s.get(self.pos), Some(b'+' | b'-')) {
+                self.pos += 1;
+            }
+            if !digits(self) {
+                self.pos = mantissa_end;
+            }
+        }
+        match self.d[start..self.pos].parse::<f32>() {
+            Ok(n) if n.is_finite() => Ok(n),
+            Ok(_) => Err(self.error_at(start, "number out of range")),
+            Err(_) => Err(self.error_at(start, "bad number")),
+        }
+    }
+    fn point(&mut self) -> Result<Vec2, GlowMeshError> {
+        Ok(vec2(self.number()?, self.number()?))
+    }
+    /// arc flags are one digit and dont need separating, eg. "a5 5 0 0110 10"
+    fn flag(&mut self) -> Result<bool, GlowMeshError> {
+        self.skip_separators();
+        match self.s.get(self.pos) {
+            Some(b'0') => {
+                self.pos += 1;
+                Ok(false)
+            },
+            Some(b'1') => {
+                self.pos += 1;
+                Ok(true)
+            },
+            _ => Err(self.error("expected an arc flag, 0 or 1")),
+        }
+    }
+}
+
+/// triangles covering what's inside the closed rings according to rule. They can cross themselves and each other
+pub fn fill_rings(rings: &[Vec<Vec2>], rule: FillRule) -> Vec<[Vec2; 3]> {
+    let edges = ring_edges(rings);
+    if !any_crossings(&edges) {
+        if let Some(triangles) = fill_simple(rings, rule) {
+            return triangles;
+        }
+    }
+    fill_trapezoids(edges, rule)
+}
+
+/// (top, bottom, +1 or -1 for going down or up and 0 for horizontal, ring)
+type Edge = (Vec2, Vec2, i32, usize);
+
+fn ring_edges(rings: &[Vec<Vec2>]) -> Vec<Edge> {
+    let mut edges = vec![];
+    for (r, ring) in rings.iter().enumerate() {
+        for (i, &a) in ring.iter().enumerate() {
+            let b = ring[(i + 1) % ring.len()];
+            if !(a.x.is_finite() && a.y.is_finite() && b.x.is_finite() && b.y.is_finite()) {
+                continue;
+            }
+            if a.y < b.y {
+                edges.push((a, b, 1, r));
+            } else if a.y > b.y {
+                edges.push((b, a, -1, r));
+            } else if a.x != b.x {
+                edges.push((a, b, 0, r));
+            }
+        }
+    }
+    edges.sort_by(|e, f| e.0.y.total_cmp(&f.0.y));
+    edges
+}
+
+fn x_at(e: &Edge, y: f32) -> f32 {
+    if y <= e.0.y {
+        e.0.x
+    } else if y >= e.1.y {
+        e.1.x
+    } else {
+        e.0.x + (y - e.0.y) * (e.1.x - e.0.x) / (e.1.y - e.0.y)
+    }
+}
+
+/// whether any two edges cross, or touch other than end to end within a ring. Only edges whose y ranges
+/// overlap get checked against each other. edges are sorted by top
+fn any_crossings(edges: &[Edge]) -> bool {
+    let mut active: Vec<&Edge> = vec![];
+    for e in edges {
+        active.retain(|f| f.1.y >= e.0.y);
+        for f in &active {
+            if segments_meet(e.0, e.1, f.0, f.1, e.3 == f.3) {
+                return true;
+            }
+        }
+        active.push(e);
+    }
+    false
+}
+
+/// segments cross or touch, other than sharing an end (if allowed) where neither continues along the other
+fn segments_meet(a: Vec2, b: Vec2, c: Vec2, d: Vec2, can_share_end: bool) -> bool {
+    let r = b - a;
+    let s = d - c;
+    let denom = r.cross(s);
+    if denom == 0.0 {
+        // parallel, overlapping only if in a line and their extents overlap by more than a point
+        if (c - a).cross(r) != 0.0 {
+            return false;
+        }
+        let rr = r.dot(r);
+        let (t0, t1) = ((c - a).dot(r) / rr, (d - a).dot(r) / rr);
+        return t0.max(t1) > 0.0 && t0.min(t1) < 1.0;
+    }
+    let t = (c - a).cross(s) / denom;
+    let u = (c - a).cross(r) / denom;
+    let shared_end = can_share_end && (a == c || a == d || b == c || b == d) && (t == 0.0 || t == 1.0) && (u == 0.0 || u == 1.0);
+    (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) && !shared_end
+}
+
+/// rings that dont cross or touch: each ring with its inside filled gets ear clipped with the rings directly
+/// inside it as holes. None if a ring is degenerate in a way that needs the general case
+fn fill_simple(rings: &[Vec<Vec2>], rule: FillRule) -> Option<Vec<[Vec2; 3]>> {
+    let rings: Vec<&[Vec2]> = rings.iter().map(|r| r.as_slice()).filter(|r| !r.is_empty()).collect();
+    if rings.iter().any(|r| r.iter().any(|p| !p.x.is_finite() || !p.y.is_finite())) {
+        return None;
+    }
+    // in a line encloses nothing
+    let rings: Vec<&[Vec2]> = rings.into_iter().filter(|r| !is_degenerate(r)).collect();
+    let areas: Vec<f32> = rings.iter().map(|r| signed_area(r)).collect();
+    // the smallest ring around each one. Nothing touches so any point of a ring says which side it's on
+    let parents: Vec<Option<usize>> = (0..rings.len()).map(|i| {
+        (0..rings.len())
+            .filter(|&j| j != i && areas[j].abs() > areas[i].abs() && contains(rings[j], rings[i][0]))
+            .min_by(|&a, &b| areas[a].abs().total_cmp(&areas[b].abs()))
+    }).collect();
+    // winding and depth just inside each ring
+    let mut winding = vec![None; rings.len()];
+    fn wind(i: usize, areas: &[f32], parents: &[Option<usize>], winding: &mut Vec<Option<(i32, usize)>>) -> (i32, usize) {
+        if let Some(w) = winding[i] {
+            return w;
+        }
+        let (w, depth) = parents[i].map(|p| wind(p, areas, parents, winding)).unwrap_or((0, 0));
+        let here = (w + if areas[i] > 0.0 { 1 } else { -1 }, depth + 1);
+        winding[i] = Some(here);
+        here
+    }
+    let mut triangles = vec![];
+    for i in 0..rings.len() {
+        let (w, depth) = wind(i, &areas, &parents, &mut winding);
+        let inside = match rule {
+            FillRule::NonZero => w != 0,
+            FillRule::EvenOdd => depth % 2 == 1,
+        };
+        if inside {
+            let holes: Vec<&[Vec2]> = (0..rings.len()).filter(|&j| parents[j] == Some(i)).map(|j| rings[j]).collect();
+            triangles.extend(triangulate(rings[i], &holes).ok()?);
+        }
+    }
+    Some(triangles)
+}
+
+/// even-odd point in polygon
+fn contains(ring: &[Vec2], p: Vec2) -> bool {
+    let mut inside = false;
+    let mut j = ring.len() - 1;
+    for i in 0..ring.len() {
+        let (a, b) = (ring[i], ring[j]);
+        if (a.y > p.y) != (b.y > p.y) && p.x < a.x + (p.y - a.y) * (b.x - a.x) / (b.y - a.y) {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+/// the general case: sweep down through slabs between vertices, keeping the edges crossing the sweep line.
+/// A slab gets cut short where a pair of neighbouring edges cross, so each slab is trapezoids between
+/// edges that dont cross within it. edges are sorted by top
+fn fill_trapezoids(mut edges: Vec<Edge>, rule: FillRule) -> Vec<[Vec2; 3]> {
+    // horizontal ones dont bound any slab
+    edges.retain(|e| e.2 != 0);
+    let mut ys: Vec<f32> = edges.iter().flat_map(|e| [e.0.y, e.1.y]).collect();
+    ys.sort_by(|a, b| a.total_cmp(b));
+    ys.dedup();
+
+    let mut triangles = vec![];
+    let mut active: Vec<usize> = vec![];
+    let mut next = 0;
+    let mut y0 = match ys.first() {
+        Some(&y) => y,
+        None => return triangles,
+    };
+    let mut k = 1;
+    // (x at mid for sorting, x at y0, x at y1, winding)
+    let mut slab: Vec<(f32, f32, f32, i32)> = vec![];
+    while k < ys.len() {
+        while next < edges.len() && edges[next].0.y <= y0 {
+            active.push(next);
+            next += 1;
+        }
+        active.retain(|&e| edges[e].1.y > y0);
+
+        let mut y1 = ys[k];
+        active.sort_by(|&a, &b| {
+            let (a, b) = (&edges[a], &edges[b]);
+            x_at(a, y0).total_cmp(&x_at(b, y0)).then(x_at(a, y1).total_cmp(&x_at(b, y1)))
+        });
+        // the first crossing below y0 is between edges next to each other at y0
+        for pair in active.windows(2) {
+            let (e, f) = (&edges[pair[0]], &edges[pair[1]]);
+            if x_at(e, y1) > x_at(f, y1) {
+                if let Some(y) = crossing_y(e.0, e.1, f.0, f.1) {
+                    if y > y0 && y < y1 {
+                        y1 = y;
+                    }
+                }
+            }
+        }
+
+        let mid = 0.5 * (y0 + y1);
+        slab.clear();
+        slab.extend(active.iter().map(|&e| (x_at(&edges[e], mid), x_at(&edges[e], y0), x_at(&edges[e], y1), edges[e].2)));
+        slab.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut winding = 0;
+        for pair in slab.windows(2) {
+            winding += pair[0].3;
+            let inside = match rule {
+                FillRule::NonZero => winding != 0,
+                FillRule::EvenOdd => winding % 2 != 0,
+            };
+            if inside {
+                let (l, r) = (pair[0], pair[1]);
+                let (a, b, c, d) = (vec2(l.1, y0), vec2(r.1, y0), vec2(r.2, y1), vec2(l.2, y1));
+                if b.x > a.x {
+                    triangles.push([a, b, c]);
+                }
+                if c.x > d.x {
+                    triangles.push([a, c, d]);
+                }
+            }
+        }
+
+        if y1 >= ys[k] {
+            k += 1;
+        }
+        y0 = y1;
+    }
+    triangles
+}
+
+/// y where segments a b and c d cross, if they do
+fn crossing_y(a: Vec2, b: Vec2, c: Vec2, d: Vec2) -> Option<f32> {
+    let r = b - a;
+    let s = d - c;
+    let denom = r.cross(s);
+    if denom == 0.0 {
+        return None;
+    }
+    let t = (c - a).cross(s) / denom;
+    let u = (c - a).cross(r) / denom;
+    if t > 0.0 && t < 1.0 && u > 0.0 && u < 1.0 {
+        Some(a.y + t * r.y)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area(triangles: &[[Vec2; 3]]) -> f32 {
+        triangles.iter().map(|[a, b, c]| (*b - *a).cross(*c - *a).abs() / 2.0).sum()
+    }
+
+    fn covered(triangles: &[[Vec2; 3]], p: Vec2) -> bool {
+        triangles.iter().any(|&[a, b, c]| {
+            let (d0, d1, d2) = ((b - a).cross(p - a), (c - b).cross(p - b), (a - c).cross(p - c));
+            (d0 > 0.0 && d1 > 0.0 && d2 > 0.0) || (d0 < 0.0 && d1 < 0.0 && d2 < 0.0)
+        })
+    }
+
+    fn square(x: f32, y: f32, size: f32, clockwise: bool) -> Vec<Vec2> {
+        let mut ring = vec![vec2(x, y), vec2(x + size, y), vec2(x + size, y + size), vec2(x, y + size)];
+        if clockwise {
+            ring.reverse();
+        }
+        ring
+    }
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-3, "{} != {}", a, b);
+    }
+
+    /// furthest any point of the curve gets from the polyline
+    fn max_deviation(points: &[Vec2], curve: impl Fn(f32) -> Vec2) -> f32 {
+        let to_segment = |p: Vec2, a: Vec2, b: Vec2| {
+            let d = b - a;
+            let t = ((p - a).dot(d) / d.dot(d)).clamp(0.0, 1.0);
+            length(p - (a + d * t))
+        };
+        (0..=1000).map(|i| {
+            let p = curve(i as f32 / 1000.0);
+            points.windows(2).map(|w| to_segment(p, w[0], w[1])).fold(f32::INFINITY, f32::min)
+        }).fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn flattening_stays_within_tolerance() {
+        let (p0, p1, p2, p3) = (vec2(0.0, 0.0), vec2(30.0, 90.0), vec2(70.0, -60.0), vec2(100.0, 20.0));
+        let cubic = |t: f32| {
+            let u = 1.0 - t;
+            u * u * u * p0 + 3.0 * u * u * t * p1 + 3.0 * u * t * t * p2 + t * t * t * p3
+        };
+        let quadratic = |t: f32| (1.0 - t) * (1.0 - t) * p0 + 2.0 * (1.0 - t) * t * p1 + t * t * p3;
+        let arc = |t: f32| ellipse_point(vec2(5.0, 5.0), vec2(40.0, 15.0), 0.5, 0.3 + 4.0 * t);
+        let mut counts = vec![];
+        for tolerance in [1.0, 0.25, 0.05] {
+            let mut points = vec![p0];
+            flatten_cubic(&mut points, p0, p1, p2, p3, tolerance);
+            assert_eq!(*points.last().unwrap(), p3);
+            assert!(max_deviation(&points, cubic) <= tolerance, "cubic at {}", tolerance);
+            counts.push(points.len());
+
+            let mut points = vec![p0];
+            flatten_quadratic(&mut points, p0, p1, p3, tolerance);
+            assert!(max_deviation(&points, quadratic) <= tolerance, "quadratic at {}", tolerance);
+
+            let mut points = vec![arc(0.0)];
+            flatten_arc(&mut points, vec2(5.0, 5.0), vec2(40.0, 15.0), 0.5, 0.3, 4.0, tolerance);
+            assert!(max_deviation(&points, arc) <= tolerance, "arc at {}", tolerance);
+        }
+        assert!(counts[0] < counts[1] && counts[1] < counts[2], "{:?}", counts);
+        // a straight "curve" is one piece
+        let mut points = vec![p0];
+        flatten_cubic(&mut points, p0, vec2(1.0, 1.0), vec2(2.0, 2.0), vec2(3.0, 3.0), 0.25);
+        assert_eq!(points.len(), 2);
+    }
+
+    fn last_arc(path: &Path) -> (Vec2, Vec2, f32, f32) {
+        match path.segments().last() {
+            Some(&PathSegment::ArcTo { centre, radii, start, sweep, .. }) => (centre, radii, start, sweep),
+            other => panic!("expected an arc, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn arc_endpoints_to_centre() {
+        let r3 = 3.0f32.sqrt();
+        // the four arcs of radius 2 from (0, 0) to (2, 0)
+        for (large_arc, sweep, centre, angle) in [
+            (false, true, vec2(1.0, r3), PI / 3.0),
+            (true, true, vec2(1.0, -r3), 5.0 * PI / 3.0),
+            (false, false, vec2(1.0, -r3), -PI / 3.0),
+            (true, false, vec2(1.0, r3), -5.0 * PI / 3.0),
+        ] {
+            let mut path = Path::new();
+            path.move_to(vec2(0.0, 0.0)).arc_to(vec2(2.0, 2.0), 0.0, large_arc, sweep, vec2(2.0, 0.0));
+            let (c, radii, _, s) = last_arc(&path);
+            assert!(length(c - centre) < 1e-5, "{} {}: centre {:?}", large_arc, sweep, c);
+            assert_eq!(radii, vec2(2.0, 2.0));
+            assert_close(s, angle);
+        }
+
+        // too small to reach, scaled up to a half circle
+        let mut path = Path::new();
+        path.move_to(vec2(0.0, 0.0)).arc_to(vec2(0.5, 0.5), 0.0, false, true, vec2(2.0, 0.0));
+        let (centre, radii, _, sweep) = last_arc(&path);
+        assert!(length(centre - vec2(1.0, 0.0)) < 1e-5);
+        assert_close(radii.x, 1.0);
+        assert_close(sweep.abs(), PI);
+
+        // a turned ellipse starts and ends where it should
+        let (from, to, rotation) = (vec2(1.0, 2.0), vec2(4.0, 3.0), 0.5);
+        let mut path = Path::new();
+        path.move_to(from).arc_to(vec2(3.0, 1.0), rotation, true, false, to);
+        let (centre, radii, start, sweep) = last_arc(&path);
+        assert!(length(ellipse_point(centre, radii, rotation, start) - from) < 1e-4);
+        assert!(length(ellipse_point(centre, radii, rotation, start + sweep) - to) < 1e-4);
+        assert!(sweep < -PI);
+
+        // zero radii is a line
+        let mut path = Path::new();
+        path.move_to(from).arc_to(vec2(0.0, 1.0), 0.0, false, false, to);
+        assert_eq!(path.segments().last(), Some(&PathSegment::LineTo(to)));
+    }
+
+    #[test]
+    fn nested_rings() {
+        let outer = square(0.0, 0.0, 4.0, false);
+        let same = square(1.0, 1.0, 2.0, false);
+        let opposite = square(1.0, 1.0, 2.0, true);
+        assert_close(area(&fill_rings(&[outer.clone(), same.clone()], FillRule::NonZero)), 16.0);
+        assert_close(area(&fill_rings(&[outer.clone(), same.clone()], FillRule::EvenOdd)), 12.0);
+        assert_close(area(&fill_rings(&[outer.clone(), opposite.clone()], FillRule::NonZero)), 12.0);
+        assert_close(area(&fill_rings(&[outer.clone(), opposite.clone()], FillRule::EvenOdd)), 12.0);
+        // an island in the hole
+        let island = square(1.5, 1.5, 1.0, false);
+        assert_close(area(&fill_rings(&[island.clone(), outer.clone(), opposite], FillRule::NonZero)), 13.0);
+        assert_close(area(&fill_rings(&[island, outer, same], FillRule::EvenOdd)), 13.0);
+    }
+
+    #[test]
+    fn overlapping_rings() {
+        let a = square(0.0, 0.0, 2.0, false);
+        let b = square(1.0, 1.0, 2.0, false);
+        assert_close(area(&fill_rings(&[a.clone(), b.clone()], FillRule::NonZero)), 7.0);
+        assert_close(area(&fill_rings(&[a.clone(), b], FillRule::EvenOdd)), 6.0);
+        // only crossing where horizontal edges are involved
+        let c = vec![vec2(1.0, -1.0), vec2(3.0, -1.0), vec2(3.0, 1.0), vec2(1.0, 1.0)];
+        assert_close(area(&fill_rings(&[a.clone(), c.clone()], FillRule::NonZero)), 7.0);
+        assert_close(area(&fill_rings(&[a, c], FillRule::EvenOdd)), 6.0);
+    }
+
+    #[test]
+    fn star_fill_rules() {
+        // a pentagram crosses itself, the middle is wound twice
+        let star: Vec<Vec2> = (0..5).map(|i| {
+            let a = PI / 2.0 + i as f32 * 4.0 * PI / 5.0;
+            vec2(a.cos(), a.sin())
+        }).collect();
+        let nonzero = fill_rings(std::slice::from_ref(&star), FillRule::NonZero);
+        let evenodd = fill_rings(&[star], FillRule::EvenOdd);
+        assert!(covered(&nonzero, vec2(0.0, 0.0)));
+        assert!(!covered(&evenodd, vec2(0.0, 0.0)));
+        assert!(covered(&evenodd, vec2(0.0, 0.8)));
+        assert!(area(&nonzero) > area(&evenodd));
+    }
+
+    #[test]
+    fn rings_that_dont_cross_are_ear_clipped() {
+        let comb = vec![vec2(0.0, 0.0), vec2(5.0, 0.0), vec2(5.0, 3.0), vec2(4.0, 3.0), vec2(4.0, 1.0), vec2(3.0, 1.0), vec2(3.0, 3.0), vec2(2.0, 3.0), vec2(2.0, 1.0), vec2(1.0, 1.0), vec2(1.0, 3.0), vec2(0.0, 3.0)];
+        let triangles = fill_rings(std::slice::from_ref(&comb), FillRule::NonZero);
+        assert_eq!(triangles.len(), comb.len() - 2);
+        assert_close(area(&triangles), 11.0);
+        // same area as the general case
+        assert_close(area(&fill_trapezoids(ring_edges(&[comb]), FillRule::NonZero)), 11.0);
+    }
+
+    #[test]
+    fn path_fill() {
+        let mut path = Path::new();
+        path.move_to(vec2(0.0, 0.0)).line_to(vec2(4.0, 0.0)).line_to(vec2(4.0, 4.0)).line_to(vec2(0.0, 4.0)).close();
+        path.move_to(vec2(3.0, 2.0)).arc(vec2(2.0, 2.0), vec2(1.0, 1.0), 0.0, 0.0, -2.0 * PI).close();
+        assert_eq!(path.segments().len(), 8);
+        let circle_area = PI;
+        assert!((area(&path.fill(FillRule::NonZero, 0.001)) - (16.0 - circle_area)).abs() < 0.01);
+        assert!((area(&path.fill(FillRule::EvenOdd, 0.001)) - (16.0 - circle_area)).abs() < 0.01);
+    }
+
+    fn svg(d: &str) -> Vec<PathSegment> {
+        Path::from_svg(d).unwrap().segments().to_vec()
+    }
+
+    #[test]
+    fn svg_relative_commands() {
+        assert_eq!(svg("M10 10 l5 0 h5 v5 c1 0 1 1 1 1 q0 1 -1 1 z m1 1 2 2"), vec![
+            PathSegment::MoveTo(vec2(10.0, 10.0)),
+            PathSegment::LineTo(vec2(15.0, 10.0)),
+            PathSegment::LineTo(vec2(20.0, 10.0)),
+            PathSegment::LineTo(vec2(20.0, 15.0)),
+            PathSegment::CubicTo(vec2(21.0, 15.0), vec2(21.0, 16.0), vec2(21.0, 16.0)),
+            PathSegment::QuadTo(vec2(21.0, 17.0), vec2(20.0, 17.0)),
+            PathSegment::Close,
+            PathSegment::MoveTo(vec2(11.0, 11.0)),
+            PathSegment::LineTo(vec2(13.0, 13.0)),
+        ]);
+        assert_eq!(svg("M1 1 H3 V4 L0 0"), svg("m1 1 h2 v3 l-3 -4"));
+    }
+
+    #[test]
+    fn svg_reflected_control_points() {
+        let cubic = PathSegment::CubicTo(vec2(5.0, -2.0), vec2(7.0, -2.0), vec2(8.0, 0.0));
+        assert_eq!(svg("M0 0 C1 2 3 2 4 0 S7 -2 8 0")[2], cubic);
+        assert_eq!(svg("M0 0 c1 2 3 2 4 0 s3 -2 4 0")[2], cubic);
+        // nothing to reflect, the first control point is the current point
+        assert_eq!(svg("M0 0 L1 1 S2 2 3 3")[2], PathSegment::CubicTo(vec2(1.0, 1.0), vec2(2.0, 2.0), vec2(3.0, 3.0)));
+        assert_eq!(svg("M0 0 Q1 2 2 0 T4 0 T6 0")[2..], [
+            PathSegment::QuadTo(vec2(3.0, -2.0), vec2(4.0, 0.0)),
+            PathSegment::QuadTo(vec2(5.0, 2.0), vec2(6.0, 0.0)),
+        ]);
+        assert_eq!(svg("M0 0 C1 2 3 2 4 0 T6 0")[2], PathSegment::QuadTo(vec2(4.0, 0.0), vec2(6.0, 0.0)));
+    }
+
+    #[test]
+    fn svg_packed_numbers() {
+        assert_eq!(svg("M1.5.5-2,1e1L-2.25e1-3E-1"), vec![
+            PathSegment::MoveTo(vec2(1.5, 0.5)),
+            PathSegment::LineTo(vec2(-2.0, 10.0)),
+            PathSegment::LineTo(vec2(-22.5, -0.3)),
+        ]);
+        assert_eq!(svg("M0,0 1+2"), svg("M 0 0 L 1 2"));
+    }
+
+    #[test]
+    fn svg_packed_arc_flags() {
+        let arc = |d: &str| match svg(d)[1] {
+            PathSegment::ArcTo { radii, sweep, to, .. } => (radii, sweep, to),
+            ref other => panic!("expected an arc, got {:?}", other),
+        };
+        let (radii, sweep, to) = arc("M0 0a1 1 0 00 1 1");
+        assert_eq!((radii, to), (vec2(1.0, 1.0), vec2(1.0, 1.0)));
+        assert!(sweep < 0.0 && sweep > -PI);
+        let (radii, sweep, to) = arc("M0 0a10 10 0 0110 10");
+        assert_eq!((radii, to), (vec2(10.0, 10.0), vec2(10.0, 10.0)));
+        assert!(sweep > 0.0 && sweep < PI);
+        let (_, sweep, _) = arc("M0 0A10,10,0,1,1,10,10");
+        assert!(sweep > PI);
+    }
+
+    #[test]
+    fn svg_errors() {
+        let error = |d: &str| match Path::from_svg(d) {
+            Err(GlowMeshError::Parse { line, message, .. }) => (line, message),
+            other => panic!("expected a parse error, got {:?}", other),
+        };
+        assert_eq!(error("L1 1"), (1, "column 1: path data has to start with M or m".to_string()));
+        assert_eq!(error("M0 0 L1"), (1, "column 8: expected a number".to_string()));
+        assert_eq!(error("M0 0\nL1 x"), (2, "column 4: expected a number".to_string()));
+        assert_eq!(error("M0 0 X1 1"), (1, "column 6: unknown command X".to_string()));
+        assert_eq!(error("M0 0 1 1 z 2 2"), (1, "column 12: expected a command".to_string()));
+        assert_eq!(error("M0 0 A1 1 0 2 1 1 1"), (1, "column 13: expected an arc flag, 0 or 1".to_string()));
+        assert_eq!(error("M0 0 L1e99 0"), (1, "column 7: number out of range".to_string()));
+        assert_eq!(error("M0 0\n  l-1e39,0"), (2, "column 4: number out of range".to_string()));
+        assert_eq!(error("M0 0 L1 \u{fc}"), (1, "column 9: expected a number".to_string()));
+        // columns count chars, not bytes
+        let d = "M0 0\n\u{fc}\u{fc} 1";
+        let p = SvgParser { s: d.as_bytes(), d, pos: 0 };
+        assert_eq!(p.error_at(d.len() - 1, "x").to_string(), "svg path:2: column 4: x");
+    }
+}
@@ -0,0 +1,565 @@
+//! Post processing: render the scene into a PostChain, then its effects run one after the other as fullscreen
+//! fragment shader passes, ping-ponging between two offscreen targets, with the last one drawing to the screen.
+//! ```ignore
+//! let mut chain = PostChain::new(&gl, w, h)?;
+//! chain.effects.push(PostEffect::Bloom { threshold: 0.8, intensity: 1.0, radius: 4.0 });
+//! chain.effects.push(PostEffect::Vignette { strength: 0.5, radius: 0.6 });
+//! // each frame
+//! chain.begin(&gl);
+//! // ... draw the scene as usual
+//! chain.end(&gl, window_w, window_h)?;
+//! ```
+//! effects is just a Vec, change parameters or reorder it whenever.
+//!
+//! Custom passes get `in vec2 uv`, `uniform sampler2D tex` (the previous pass), `uniform sampler2D scene`
+//! (what was drawn between begin and end), `uniform vec2 texel` (1 / size) and `uniform vec2 resolution`, plus their params.
+
+use glow::HasContext;
+use minimg::*;
+use crate::error::*;
+use crate::gc::*;
+use crate::program::*;
+use crate::render_target::*;
+
+/// draws one triangle covering the screen with uv 0..1 over it, no vertex buffer needed
+pub struct FullscreenTriangle<G: HasContext = glow::Context> {
+    pub vao: G::VertexArray,
+    leak: LeakCheck,
+}
+
+impl<G: HasContext> FullscreenTriangle<G> {
+    /// # Safety
+    /// gl has to be current
+    pub unsafe fn new(gl: &G) -> Result<Self, GlowMeshError> {
+        let vao = gl.create_vertex_array().map_err(GlowMeshError::ResourceCreation)?;
+        Ok(FullscreenTriangle { vao, leak: LeakCheck::new("FullscreenTriangle") })
+    }
+    /// bind a program using FULLSCREEN_VS first
+    ///
+    /// # Safety
+    /// gl has to be the context this was made on, and current
+    pub unsafe fn render(&self, gl: &G) {
+        gl.bind_vertex_array(Some(self.vao));
+        gl.draw_arrays(glow::TRIANGLES, 0, 3);
+    }
+    pub fn free(&self, gl: &G) {
+        unsafe {
+            gl.delete_vertex_array(self.vao);
+        }
+        self.leak.freed();
+    }
+}
+
+impl<G: HasContext> Free<G> for FullscreenTriangle<G> {
+    fn free(&self, gl: &G) {
+        FullscreenTriangle::free(self, gl)
+    }
+}
+
+pub enum PostEffect<G: HasContext = glow::Context> {
+    /// separable gaussian, radius is sigma in pixels
+    Blur { radius: f32 },
+    /// adds a blurred copy of whatever is brighter than threshold back on top
+    Bloom { threshold: f32, intensity: f32, radius: f32 },
+    /// darkens towards the corners, starting radius from the centre (1.0 being the corners)
+    Vignette { strength: f32, radius: f32 },
+    /// colour lookup table as a 2d strip: n slices of n x n side by side, blue picks the slice, red goes across, green goes down.
+    /// See identity_lut. Texture should be linear and clamped
+    ColourGrade { lut: G::Texture, strength: f32 },
+    /// curved screen + scanlines
+    Crt { curvature: f32, scanlines: f32 },
+    /// shader is the index add_custom returned
+    Custom { shader: usize, params: Vec<(String, f32)> },
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Slot {
+    Scene,
+    Ping,
+    Pong,
+    Scratch,
+}
+
+enum Input<G: HasContext> {
+    Slot(Slot),
+    Texture(G::Texture),
+}
+
+/// one fullscreen draw
+struct Step<'a, G: HasContext> {
+    program: &'a Program<G>,
+    inputs: Vec<(&'static str, Input<G>)>,
+    floats: Vec<(&'a str, f32)>,
+    direction: Option<(f32, f32)>,
+    /// None draws to the screen
+    output: Option<Slot>,
+}
+
+struct Programs<G: HasContext> {
+    copy: Program<G>,
+    blur: Program<G>,
+    threshold: Program<G>,
+    bloom: Program<G>,
+    vignette: Program<G>,
+    lut: Program<G>,
+    crt: Program<G>,
+}
+
+pub struct PostChain<G: HasContext = glow::Context> {
+    pub effects: Vec<PostEffect<G>>,
+    pub w: usize,
+    pub h: usize,
+    scene: RenderTarget<G>,
+    ping: RenderTarget<G>,
+    pong: RenderTarget<G>,
+    /// bloom's thresholded copy
+    scratch: RenderTarget<G>,
+    triangle: FullscreenTriangle<G>,
+    programs: Programs<G>,
+    custom: Vec<Program<G>>,
+}
+
+/// what PostChain::new has made so far, freed on drop unless it was all taken out
+struct Unwind<'a, G: HasContext> {
+    gl: &'a G,
+    targets: Vec<RenderTarget<G>>,
+    triangle: Option<FullscreenTriangle<G>>,
+    programs: Vec<Program<G>>,
+}
+
+impl<G: HasContext> Drop for Unwind<'_, G> {
+    fn drop(&mut self) {
+        for target in &self.targets {
+            target.free(self.gl);
+        }
+        if let Some(triangle) = &self.triangle {
+            triangle.free(self.gl);
+        }
+        for program in &self.programs {
+            program.free(self.gl);
+        }
+    }
+}
+
+/// the ping-pong target that isnt current
+fn other(current: Slot) -> Slot {
+    if current == Slot::Ping { Slot::Pong } else { Slot::Ping }
+}
+
+impl<G: HasContext> PostChain<G> {
+    /// targets are w x h, usually the window size. The scene target has a depth buffer
+    ///
+    /// # Safety
+    /// gl has to be current. Everything the chain makes belongs to that context
+    pub unsafe fn new(gl: &G, w: usize, h: usize) -> Result<Self, GlowMeshError> {
+        // frees everything made so far if one of these fails
+        let mut made = Unwind { gl, targets: vec![], triangle: None, programs: vec![] };
+        for desc in [RenderTargetDesc::default().with_depth(true), RenderTargetDesc::default(), RenderTargetDesc::default(), RenderTargetDesc::default()] {
+            made.targets.push(RenderTarget::new(gl, w, h, &desc)?);
+        }
+        made.triangle = Some(FullscreenTriangle::new(gl)?);
+        // same order as the Programs fields below
+        for frag in [COPY_FS, BLUR_FS, THRESHOLD_FS, BLOOM_FS, VIGNETTE_FS, LUT_FS, CRT_FS] {
+            made.programs.push(Program::new(gl, FULLSCREEN_VS, frag)?);
+        }
+
+        let mut targets = std::mem::take(&mut made.targets).into_iter();
+        let mut programs = std::mem::take(&mut made.programs).into_iter();
+        let triangle = made.triangle.take().unwrap();
+        let mut target = || targets.next().unwrap();
+        let (scene, ping, pong, scratch) = (target(), target(), target(), target());
+        let mut program = || programs.next().unwrap();
+        let programs = Programs {
+            copy: program(),
+            blur: program(),
+            threshold: program(),
+            bloom: program(),
+            vignette: program(),
+            lut: program(),
+            crt: program(),
+        };
+        Ok(PostChain { effects: vec![], w, h, scene, ping, pong, scratch, triangle, programs, custom: vec![] })
+    }
+    /// compile a fragment shader for PostEffect::Custom, see the module docs for what it gets
+    ///
+    /// # Safety
+    /// gl has to be the context the chain was made on, and current
+    pub unsafe fn add_custom(&mut self, gl: &G, frag: &str) -> Result<usize, GlowMeshError> {
+        self.custom.push(Program::new(gl, FULLSCREEN_VS, frag)?);
+        Ok(self.custom.len() - 1)
+    }
+    /// reallocate the targets, eg. when the window resizes
+    ///
+    /// # Safety
+    /// gl has to be the context the chain was made on, and current
+    pub unsafe fn resize(&mut self, gl: &G, w: usize, h: usize) -> Result<(), GlowMeshError> {
+        self.scene.resize(gl, w, h)?;
+        self.ping.resize(gl, w, h)?;
+        self.pong.resize(gl, w, h)?;
+        self.scratch.resize(gl, w, h)?;
+        self.w = w;
+        self.h = h;
+        Ok(())
+    }
+    /// the target the scene gets drawn into, eg. to draw it somewhere else as well
+    pub fn scene(&self) -> &RenderTarget<G> {
+        &self.scene
+    }
+    /// start drawing the scene into the chain
+    ///
+    /// # Safety
+    /// gl has to be the context the chain was made on, and current
+    pub unsafe fn begin(&self, gl: &G) {
+        self.scene.bind(gl);
+    }
+    /// run the effects, the last one drawing to the default framebuffer. Blending and depth testing are off for the passes and restored after
+    ///
+    /// # Safety
+    /// gl has to be the context the chain was made on, and current, and begin has to have been called this frame
+    pub unsafe fn end(&mut self, gl: &G, window_w: usize, window_h: usize) -> Result<(), GlowMeshError> {
+        for effect in &self.effects {
+            if let PostEffect::Custom { shader, .. } = effect {
+                if *shader >= self.custom.len() {
+                    return Err(GlowMeshError::NoCustomEffect { shader: *shader, count: self.custom.len() });
+                }
+            }
+        }
+
+        let blend = gl.is_enabled(glow::BLEND);
+        let depth_test = gl.is_enabled(glow::DEPTH_TEST);
+        gl.disable(glow::BLEND);
+        gl.disable(glow::DEPTH_TEST);
+
+        let steps = self.steps();
+        for step in &steps {
+            self.run(gl, step, window_w, window_h);
+        }
+
+        if blend {
+            gl.enable(glow::BLEND);
+        }
+        if depth_test {
+            gl.enable(glow::DEPTH_TEST);
+        }
+        Ok(())
+    }
+
+    /// expand the effects into passes
+    fn steps(&self) -> Vec<Step<'_, G>> {
+        let p = &self.programs;
+        let mut steps = vec![];
+        let mut current = Slot::Scene;
+        let simple = |program, current, floats, inputs: Vec<(&'static str, Input<G>)>| {
+            let mut all = vec![("tex", Input::Slot(current))];
+            all.extend(inputs);
+            Step { program, inputs: all, floats, direction: None, output: Some(other(current)) }
+        };
+        for effect in &self.effects {
+            match effect {
+                PostEffect::Blur { radius } => {
+                    let mid = other(current);
+                    let out = other(mid);
+                    steps.push(Step { program: &p.blur, inputs: vec![("tex", Input::Slot(current))], floats: vec![("radius", *radius)], direction: Some((1.0, 0.0)), output: Some(mid) });
+                    steps.push(Step { program: &p.blur, inputs: vec![("tex", Input::Slot(mid))], floats: vec![("radius", *radius)], direction: Some((0.0, 1.0)), output: Some(out) });
+                    current = out;
+                },
+                PostEffect::Bloom { threshold, intensity, radius } => {
+                    let base = current;
+                    let spare = other(base);
+                    steps.push(Step { program: &p.threshold, inputs: vec![("tex", Input::Slot(base))], floats: vec![("threshold", *threshold)], direction: None, output: Some(Slot::Scratch) });
+                    steps.push(Step { program: &p.blur, inputs: vec![("tex", Input::Slot(Slot::Scratch))], floats: vec![("radius", *radius)], direction: Some((1.0, 0.0)), output: Some(spare) });
+                    steps.push(Step { program: &p.blur, inputs: vec![("tex", Input::Slot(spare))], floats: vec![("radius", *radius)], direction: Some((0.0, 1.0)), output: Some(Slot::Scratch) });
+                    steps.push(Step { program: &p.bloom, inputs: vec![("tex", Input::Slot(base)), ("bloom", Input::Slot(Slot::Scratch))], floats: vec![("intensity", *intensity)], direction: None, output: Some(spare) });
+                    current = spare;
+                },
+                PostEffect::Vignette { strength, radius } => {
+                    steps.push(simple(&p.vignette, current, vec![("strength", *strength), ("radius", *radius)], vec![]));
+                    current = other(current);
+                },
+                PostEffect::ColourGrade { lut, strength } => {
+                    steps.push(simple(&p.lut, current, vec![("strength", *strength)], vec![("lut", Input::Texture(*lut))]));
+                    current = other(current);
+                },
+                PostEffect::Crt { curvature, scanlines } => {
+                    steps.push(simple(&p.crt, current, vec![("curvature", *curvature), ("scanlines", *scanlines)], vec![]));
+                    current = other(current);
+                },
+                PostEffect::Custom { shader, params } => {
+                    let floats = params.iter().map(|(name, x)| (name.as_str(), *x)).collect();
+                    steps.push(simple(&self.custom[*shader], current, floats, vec![("scene", Input::Slot(Slot::Scene))]));
+                    current = other(current);
+                },
+            }
+        }
+        match steps.last_mut() {
+            Some(last) => last.output = None,
+            None => steps.push(Step { program: &p.copy, inputs: vec![("tex", Input::Slot(Slot::Scene))], floats: vec![], direction: None, output: None }),
+        }
+        steps
+    }
+
+    fn target(&self, slot: Slot) -> &RenderTarget<G> {
+        match slot {
+            Slot::Scene => &self.scene,
+            Slot::Ping => &self.ping,
+            Slot::Pong => &self.pong,
+            Slot::Scratch => &self.scratch,
+        }
+    }
+
+    unsafe fn run(&self, gl: &G, step: &Step<G>, window_w: usize, window_h: usize) {
+        match step.output {
+            Some(slot) => self.target(slot).bind(gl),
+            None => {
+                gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+                gl.viewport(0, 0, window_w as i32, window_h as i32);
+            },
+        }
+        step.program.bind(gl);
+        for (unit, (name, input)) in step.inputs.iter().enumerate() {
+            let texture = match input {
+                Input::Slot(slot) => self.target(*slot).colour.texture,
+                Input::Texture(texture) => *texture,
+            };
+            gl.active_texture(glow::TEXTURE0 + unit as u32);
+            gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+            step.program.set_i32(name, unit as i32, gl);
+        }
+        gl.active_texture(glow::TEXTURE0);
+        let (tx, ty) = (1.0 / self.w as f32, 1.0 / self.h as f32);
+        step.program.set_vec2("texel", tx, ty, gl);
+        step.program.set_vec2("resolution", self.w as f32, self.h as f32, gl);
+        if let Some((dx, dy)) = step.direction {
+            step.program.set_vec2("direction", dx * tx, dy * ty, gl);
+        }
+        for (name, x) in &step.floats {
+            step.program.set_f32(name, *x, gl);
+        }
+        self.triangle.render(gl);
+    }
+
+    pub fn free(&self, gl: &G) {
+        self.scene.free(gl);
+        self.ping.free(gl);
+        self.pong.free(gl);
+        self.scratch.free(gl);
+        self.triangle.free(gl);
+        let p = &self.programs;
+        for program in [&p.copy, &p.blur, &p.threshold, &p.bloom, &p.vignette, &p.lut, &p.crt] {
+            program.free(gl);
+        }
+        for program in &self.custom {
+            program.free(gl);
+        }
+    }
+}
+
+impl<G: HasContext> Free<G> for PostChain<G> {
+    fn free(&self, gl: &G) {
+        PostChain::free(self, gl)
+    }
+}
+
+/// a lut for ColourGrade that changes nothing, n slices of n x n. Edit it in an image editor to grade
+pub fn identity_lut(n: usize) -> ImageBuffer {
+    let mut img = ImageBuffer::new(n * n, n);
+    let max = (n.max(2) - 1) as f32;
+    for b in 0..n {
+        for g in 0..n {
+            for r in 0..n {
+                let i = (g * n * n + b * n + r) * 4;
+                img.data[i] = (r as f32 / max * 255.0).round() as u8;
+                img.data[i + 1] = (g as f32 / max * 255.0).round() as u8;
+                img.data[i + 2] = (b as f32 / max * 255.0).round() as u8;
+                img.data[i + 3] = 255;
+            }
+        }
+    }
+    img
+}
+
+/// uv from gl_VertexID, for drawing with FullscreenTriangle
+pub const FULLSCREEN_VS: &str = r#"#version 330 core
+out vec2 uv;
+
+void main() {
+    vec2 p = vec2((gl_VertexID << 1) & 2, gl_VertexID & 2);
+    uv = p;
+    gl_Position = vec4(p * 2.0 - 1.0, 0.0, 1.0);
+}
+"#;
+
+pub const COPY_FS: &str = r#"#version 330 core
+in vec2 uv;
+out vec4 frag_colour;
+
+uniform sampler2D tex;
+
+void main() {
+    frag_colour = texture(tex, uv);
+}
+"#;
+
+pub const BLUR_FS: &str = r#"#version 330 core
+in vec2 uv;
+out vec4 frag_colour;
+
+uniform sampler2D tex;
+uniform vec2 direction;
+uniform float radius;
+
+void main() {
+    float sigma = max(radius, 0.001);
+    int n = min(int(ceil(sigma * 3.0)), 32);
+    vec4 sum = vec4(0.0);
+    float weights = 0.0;
+    for (int i = -n; i <= n; i++) {
+        float w = exp(-0.5 * float(i * i) / (sigma * sigma));
+        sum += texture(tex, uv + direction * float(i)) * w;
+        weights += w;
+    }
+    frag_colour = sum / weights;
+}
+"#;
+
+pub const THRESHOLD_FS: &str = r#"#version 330 core
+in vec2 uv;
+out vec4 frag_colour;
+
+uniform sampler2D tex;
+uniform float threshold;
+
+void main() {
+    vec3 c = texture(tex, uv).rgb;
+    float brightness = max(c.r, max(c.g, c.b));
+    frag_colour = vec4(c * max(brightness - threshold, 0.0) / max(brightness, 0.0001), 1.0);
+}
+"#;
+
+pub const BLOOM_FS: &str = r#"#version 330 core
+in vec2 uv;
+out vec4 frag_colour;
+
+uniform sampler2D tex;
+uniform sampler2D bloom;
+uniform float intensity;
+
+void main() {
+    vec4 base = texture(tex, uv);
+    frag_colour = vec4(base.rgb + texture(bloom, uv).rgb * intensity, base.a);
+}
+"#;
+
+pub const VIGNETTE_FS: &str = r#"#version 330 core
+in vec2 uv;
+out vec4 frag_colour;
+
+uniform sampler2D tex;
+uniform float strength;
+uniform float radius;
+
+void main() {
+    vec4 c = texture(tex, uv);
+    float d = length(uv - 0.5) * 1.41421356;
+    frag_colour = vec4(c.rgb * (1.0 - strength * smoothstep(radius, 1.0, d)), c.a);
+}
+"#;
+
+pub const LUT_FS: &str = r#"#version 330 core
+in vec2 uv;
+out vec4 frag_colour;
+
+uniform sampler2D tex;
+uniform sampler2D lut;
+uniform float strength;
+
+vec3 grade(vec3 c) {
+    vec2 size = vec2(textureSize(lut, 0));
+    float n = size.y;
+    c = clamp(c, 0.0, 1.0);
+    float b = c.b * (n - 1.0);
+    float s0 = floor(b);
+    float s1 = min(s0 + 1.0, n - 1.0);
+    vec2 rg = c.rg * (n - 1.0) + 0.5;
+    vec3 lo = texture(lut, vec2((s0 * n + rg.x) / size.x, rg.y / size.y)).rgb;
+    vec3 hi = texture(lut, vec2((s1 * n + rg.x) / size.x, rg.y / size.y)).rgb;
+    return mix(lo, hi, b - s0);
+}
+
+void main() {
+    vec4 c = texture(tex, uv);
+    frag_colour = vec4(mix(c.rgb, grade(c.rgb), strength), c.a);
+}
+"#;
+
+pub const CRT_FS: &str = r#"#version 330 core
+in vec2 uv;
+out vec4 frag_colour;
+
+uniform sampler2D tex;
+uniform vec2 resolution;
+uniform float curvature;
+uniform float scanlines;
+
+void main() {
+    vec2 cc = uv * 2.0 - 1.0;
+    cc += cc * (cc.yx * cc.yx) * curvature;
+    vec2 p = cc * 0.5 + 0.5;
+    if (p.x < 0.0 || p.x > 1.0 || p.y < 0.0 || p.y > 1.0) {
+        frag_colour = vec4(0.0, 0.0, 0.0, 1.0);
+        return;
+    }
+    vec4 c = texture(tex, p);
+    float line = 0.5 + 0.5 * sin(p.y * resolution.y * 3.14159265);
+    frag_colour = vec4(c.rgb * (1.0 - scanlines * line), c.a);
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn failed_creates_leak_nothing() {
+        let gl = RecordingContext::new();
+        let chain = unsafe { PostChain::new(&gl, 8, 8) }.unwrap();
+        let creates = gl.calls().iter().filter(|c| c.name.starts_with("create_")).count();
+        // 4 targets with 9 names between them, a vao and 7 programs of 3
+        assert_eq!(creates, 9 + 1 + 7 * 3);
+        chain.free(&gl);
+        assert!(gl.live_objects().is_empty());
+
+        for n in 0..creates {
+            let gl = RecordingContext::new();
+            gl.fail_nth_create(n);
+            assert!(matches!(unsafe { PostChain::new(&gl, 8, 8) }, Err(GlowMeshError::ResourceCreation(_))));
+            assert!(gl.live_objects().is_empty(), "failing create {} leaked {:?}", n, gl.live_objects());
+        }
+    }
+
+    #[test]
+    fn failed_compile_leaks_nothing() {
+        let gl = RecordingContext::new();
+        gl.set_compile_status(true, false, "link failed");
+        assert!(matches!(unsafe { PostChain::new(&gl, 8, 8) }, Err(GlowMeshError::Link { .. })));
+        assert_eq!(gl.calls_named("link_program").len(), 1);
+        assert!(gl.live_objects().is_empty());
+    }
+
+    #[test]
+    fn unknown_custom_effect() {
+        let gl = RecordingContext::new();
+        let mut chain = unsafe { PostChain::new(&gl, 8, 8) }.unwrap();
+        chain.effects.push(PostEffect::Custom { shader: 0, params: vec![] });
+        assert!(matches!(unsafe { chain.end(&gl, 8, 8) }, Err(GlowMeshError::NoCustomEffect { shader: 0, count: 0 })));
+        assert!(gl.calls_named("draw_arrays").is_empty());
+
+        assert_eq!(unsafe { chain.add_custom(&gl, COPY_FS) }.unwrap(), 0);
+        chain.effects.push(PostEffect::Bloom { threshold: 0.8, intensity: 1.0, radius: 2.0 });
+        unsafe { chain.end(&gl, 8, 8) }.unwrap();
+        assert_eq!(gl.calls_named("draw_arrays").len(), 1 + 4);
+        chain.free(&gl);
+        assert!(gl.live_objects().is_empty());
+    }
+}
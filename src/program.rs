@@ -0,0 +1,236 @@
+use glow::HasContext;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use crate::error::*;
+use crate::gc::*;
+
+/// where a program's shader code comes from
+#[derive(Debug, Clone)]
+pub enum ProgramSource {
+    Strings { vert: String, frag: String },
+    /// read from disk, and reloaded by poll_reload when the files change
+    Files { vert: PathBuf, frag: PathBuf },
+}
+
+/// a linked vertex + fragment shader. The per vertex type programs wrap this.
+pub struct Program<G: HasContext = glow::Context> {
+    program: G::Program,
+    watch: Option<WatchedFiles>,
+    leak: LeakCheck,
+}
+
+struct WatchedFiles {
+    vert: PathBuf,
+    frag: PathBuf,
+    vert_mtime: Option<SystemTime>,
+    frag_mtime: Option<SystemTime>,
+}
+
+fn read_file(path: &Path) -> Result<String, GlowMeshError> {
+    std::fs::read_to_string(path).map_err(|error| GlowMeshError::Io { path: path.to_path_buf(), error })
+}
+
+fn mtime(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+unsafe fn compile_shader<G: HasContext>(gl: &G, stage: ShaderStage, src: &str) -> Result<G::Shader, GlowMeshError> {
+    let ty = match stage {
+        ShaderStage::Vertex => glow::VERTEX_SHADER,
+        ShaderStage::Fragment => glow::FRAGMENT_SHADER,
+    };
+    let shader = gl.create_shader(ty).map_err(GlowMeshError::ResourceCreation)?;
+    gl.shader_source(shader, src);
+    gl.compile_shader(shader);
+    if !gl.get_shader_compile_status(shader) {
+        let log = gl.get_shader_info_log(shader);
+        gl.delete_shader(shader);
+        return Err(GlowMeshError::shader_compile(stage, log, src));
+    }
+    Ok(shader)
+}
+
+unsafe fn link_program<G: HasContext>(gl: &G, vert: &str, frag: &str) -> Result<G::Program, GlowMeshError> {
+    let vs = compile_shader(gl, ShaderStage::Vertex, vert)?;
+    let fs = match compile_shader(gl, ShaderStage::Fragment, frag) {
+        Ok(fs) => fs,
+        Err(e) => {
+            gl.delete_shader(vs);
+            return Err(e);
+        },
+    };
+    let program = match gl.create_program() {
+        Ok(program) => program,
+        Err(e) => {
+            gl.delete_shader(vs);
+            gl.delete_shader(fs);
+            return Err(GlowMeshError::ResourceCreation(e));
+        },
+    };
+    gl.attach_shader(program, vs);
+    gl.attach_shader(program, fs);
+
+    gl.link_program(program);
+    let linked = gl.get_program_link_status(program);
+    gl.detach_shader(program, fs);
+    gl.delete_shader(fs);
+    gl.detach_shader(program, vs);
+    gl.delete_shader(vs);
+    if !linked {
+        let log = gl.get_program_info_log(program);
+        gl.delete_program(program);
+        return Err(GlowMeshError::Link { log });
+    }
+
+    Ok(program)
+}
+
+impl<G: HasContext> Program<G> {
+    /// compile and link. Nothing is leaked if this fails.
+    ///
+    /// # Safety
+    /// gl has to be current
+    pub unsafe fn new(gl: &G, vert: &str, frag: &str) -> Result<Self, GlowMeshError> {
+        Ok(Program {
+            program: link_program(gl, vert, frag)?,
+            watch: None,
+            leak: LeakCheck::new("Program"),
+        })
+    }
+    /// # Safety
+    /// gl has to be current
+    pub unsafe fn from_source(gl: &G, source: &ProgramSource) -> Result<Self, GlowMeshError> {
+        match source {
+            ProgramSource::Strings { vert, frag } => Self::new(gl, vert, frag),
+            ProgramSource::Files { vert, frag } => {
+                // take the mtimes before reading so a save during the read still triggers a reload
+                let vert_mtime = mtime(vert);
+                let frag_mtime = mtime(frag);
+                let program = link_program(gl, &read_file(vert)?, &read_file(frag)?)?;
+                Ok(Program {
+                    program,
+                    watch: Some(WatchedFiles { vert: vert.clone(), frag: frag.clone(), vert_mtime, frag_mtime }),
+                    leak: LeakCheck::new("Program"),
+                })
+            },
+        }
+    }
+    /// for ProgramSource::Files: if either file changed since last time, recompile and swap the program in place.
+    /// Ok(true) if it was swapped. On error the old program stays bound and usable, and the same
+    /// files arent retried until they change again.
+    /// Uniforms live in the program so bind it and set them again after a reload, eg. set_proj.
+    ///
+    /// # Safety
+    /// gl has to be the context the program was made on, and current
+    pub unsafe fn poll_reload(&mut self, gl: &G) -> Result<bool, GlowMeshError> {
+        let watch = match &mut self.watch {
+            Some(watch) => watch,
+            None => return Ok(false),
+        };
+        let vert_mtime = mtime(&watch.vert);
+        let frag_mtime = mtime(&watch.frag);
+        if vert_mtime == watch.vert_mtime && frag_mtime == watch.frag_mtime {
+            return Ok(false);
+        }
+        watch.vert_mtime = vert_mtime;
+        watch.frag_mtime = frag_mtime;
+        let program = link_program(gl, &read_file(&watch.vert)?, &read_file(&watch.frag)?)?;
+        gl.delete_program(self.program);
+        self.program = program;
+        Ok(true)
+    }
+    pub fn native(&self) -> G::Program {
+        self.program
+    }
+    /// # Safety
+    /// gl has to be the context the program was made on, and current
+    pub unsafe fn bind(&self, gl: &G) {
+        gl.use_program(Some(self.program))
+    }
+    /// # Safety
+    /// the program has to be bound on gl
+    pub unsafe fn set_proj(&self, proj: &[f32; 16], gl: &G) {
+        gl.uniform_matrix_4_f32_slice(gl.get_uniform_location(self.program, "projection").as_ref(), true, proj);
+    }
+    /// set a float uniform by name, program must be bound. Does nothing if the shader doesnt have it
+    ///
+    /// # Safety
+    /// the program has to be bound on gl
+    pub unsafe fn set_f32(&self, name: &str, x: f32, gl: &G) {
+        gl.uniform_1_f32(gl.get_uniform_location(self.program, name).as_ref(), x);
+    }
+    /// # Safety
+    /// the program has to be bound on gl
+    pub unsafe fn set_vec2(&self, name: &str, x: f32, y: f32, gl: &G) {
+        gl.uniform_2_f32(gl.get_uniform_location(self.program, name).as_ref(), x, y);
+    }
+    /// eg. which texture unit a sampler reads
+    ///
+    /// # Safety
+    /// the program has to be bound on gl
+    pub unsafe fn set_i32(&self, name: &str, x: i32, gl: &G) {
+        gl.uniform_1_i32(gl.get_uniform_location(self.program, name).as_ref(), x);
+    }
+    pub fn free(&self, gl: &G) {
+        unsafe {
+            gl.delete_program(self.program);
+        }
+        self.leak.freed();
+    }
+}
+
+impl<G: HasContext> Free<G> for Program<G> {
+    fn free(&self, gl: &G) {
+        Program::free(self, gl)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    const VERT: &str = "#version 330 core\nvoid main() { gl_Position = vec4(0.0); }";
+    const FRAG: &str = "#version 330 core\nout vec4 c;\nvoid main() { c = vec4(1.0); }";
+
+    fn names(gl: &RecordingContext) -> Vec<&'static str> {
+        gl.calls().iter().map(|c| c.name).filter(|n| n.starts_with("create_") || n.starts_with("delete_")).collect()
+    }
+
+    #[test]
+    fn new_and_free() {
+        let gl = RecordingContext::new();
+        let program = unsafe { Program::new(&gl, VERT, FRAG) }.unwrap();
+        assert_eq!(gl.live_objects(), vec![program.program]);
+        program.free(&gl);
+        assert!(gl.live_objects().is_empty());
+    }
+
+    #[test]
+    fn failed_creates_leak_nothing() {
+        let expected = [
+            vec!["create_shader"],
+            vec!["create_shader", "create_shader", "delete_shader"],
+            vec!["create_shader", "create_shader", "create_program", "delete_shader", "delete_shader"],
+        ];
+        for (n, expected) in expected.iter().enumerate() {
+            let gl = RecordingContext::new();
+            gl.fail_nth_create(n);
+            assert!(matches!(unsafe { Program::new(&gl, VERT, FRAG) }, Err(GlowMeshError::ResourceCreation(_))));
+            assert_eq!(&names(&gl), expected, "failing create {}", n);
+            assert!(gl.live_objects().is_empty(), "failing create {} leaked {:?}", n, gl.live_objects());
+        }
+    }
+
+    #[test]
+    fn failed_compile_and_link_leak_nothing() {
+        let gl = RecordingContext::new();
+        gl.set_compile_status(false, true, "0:1(1): error: nope");
+        assert!(matches!(unsafe { Program::new(&gl, VERT, FRAG) }, Err(GlowMeshError::ShaderCompile { .. })));
+        assert!(gl.live_objects().is_empty());
+
+        gl.set_compile_status(true, false, "link failed");
+        assert!(matches!(unsafe { Program::new(&gl, VERT, FRAG) }, Err(GlowMeshError::Link { .. })));
+        assert!(gl.live_objects().is_empty());
+    }
+}
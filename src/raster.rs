@@ -0,0 +1,451 @@
+//! A software rasterizer doing the same maths as the default shaders, for rendering meshes without a gpu.
+//! Good for reference images in tests and for thumbnails on a server.
+//!
+//! Follows GL conventions: proj is row major like set_proj takes it, triangles are clipped in clip space,
+//! pixel centres are sampled with the top-left fill rule, varyings are perspective correct.
+//! Depth test is LESS and blending is SRC_ALPHA, ONE_MINUS_SRC_ALPHA, each can be turned off.
+//! Row 0 of the output is the top of the screen, like a screenshot.
+
+use minimg::*;
+use minvect::*;
+use crate::texture::*;
+use crate::xyzrgba::*;
+use crate::xyzrgbauv::*;
+
+#[derive(Clone, Copy)]
+struct ClipVert {
+    clip: [f32; 4],
+    col: Vec4,
+    uv: Vec2,
+}
+
+impl ClipVert {
+    fn lerp(&self, other: &ClipVert, t: f32) -> ClipVert {
+        let mut clip = [0.0; 4];
+        for (i, c) in clip.iter_mut().enumerate() {
+            *c = self.clip[i] + (other.clip[i] - self.clip[i]) * t;
+        }
+        ClipVert { clip, col: self.col.lerp(other.col, t), uv: self.uv.lerp(other.uv, t) }
+    }
+}
+
+struct ScreenVert {
+    x: f32,
+    y: f32,
+    z: f32,
+    inv_w: f32,
+    col: Vec4,
+    uv: Vec2,
+}
+
+pub struct Rasterizer {
+    pub w: usize,
+    pub h: usize,
+    pub colour: Vec<Vec4>,
+    pub depth: Vec<f32>,
+    pub depth_test: bool,
+    pub blend: bool,
+    /// used when sampling textures in draw_xyzrgbauv
+    pub sampler: SamplerDesc,
+}
+
+impl Rasterizer {
+    /// cleared to transparent black, depth 1.0
+    pub fn new(w: usize, h: usize) -> Self {
+        Rasterizer {
+            w,
+            h,
+            colour: vec![vec4(0.0, 0.0, 0.0, 0.0); w*h],
+            depth: vec![1.0; w*h],
+            depth_test: true,
+            blend: true,
+            sampler: SamplerDesc::default(),
+        }
+    }
+    pub fn clear(&mut self, colour: Vec4) {
+        self.colour.iter_mut().for_each(|c| *c = colour);
+        self.depth.iter_mut().for_each(|d| *d = 1.0);
+    }
+    /// same as ProgramXYZRGBA's DEFAULT_VS + DEFAULT_FS
+    pub fn draw_xyzrgba(&mut self, mesh: &[XYZRGBA], proj: &[f32; 16]) {
+        for tri in mesh.chunks_exact(3) {
+            let verts = [0, 1, 2].map(|i| {
+                let v = &tri[i];
+                transform(v.xyz, v.rgba, vec2(0.0, 0.0), proj)
+            });
+            self.draw_triangle(verts, None);
+        }
+    }
+    /// same as ProgramXYZRGBAUV's DEFAULT_VS + DEFAULT_FS. No texture samples as white
+    pub fn draw_xyzrgbauv(&mut self, mesh: &[XYZRGBAUV], proj: &[f32; 16], texture: Option<&ImageBuffer>) {
+        for tri in mesh.chunks_exact(3) {
+            let verts = [0, 1, 2].map(|i| {
+                let v = &tri[i];
+                transform(v.xyz, v.rgba, v.uv, proj)
+            });
+            self.draw_triangle(verts, texture);
+        }
+    }
+    pub fn to_image(&self) -> ImageBuffer {
+        let mut img = ImageBuffer::new(self.w, self.h);
+        for (i, c) in self.colour.iter().enumerate() {
+            img.data[i*4] = to_u8(c.x);
+            img.data[i*4 + 1] = to_u8(c.y);
+            img.data[i*4 + 2] = to_u8(c.z);
+            img.data[i*4 + 3] = to_u8(c.w);
+        }
+        img
+    }
+
+    fn draw_triangle(&mut self, verts: [ClipVert; 3], texture: Option<&ImageBuffer>) {
+        let poly = clip_polygon(verts.to_vec());
+        if poly.len() < 3 {
+            return;
+        }
+        let screen: Vec<ScreenVert> = poly.iter().map(|v| self.to_screen(v)).collect();
+        for i in 1..screen.len() - 1 {
+            self.raster_triangle(&screen[0], &screen[i], &screen[i + 1], texture);
+        }
+    }
+
+    fn to_screen(&self, v: &ClipVert) -> ScreenVert {
+        let inv_w = 1.0 / v.clip[3];
+        let ndc = [v.clip[0] * inv_w, v.clip[1] * inv_w, v.clip[2] * inv_w];
+        ScreenVert {
+            x: (ndc[0] * 0.5 + 0.5) * self.w as f32,
+            y: (0.5 - ndc[1] * 0.5) * self.h as f32,
+            z: ndc[2] * 0.5 + 0.5,
+            inv_w,
+            col: v.col,
+            uv: v.uv,
+        }
+    }
+
+    fn raster_triangle(&mut self, a: &ScreenVert, b: &ScreenVert, c: &ScreenVert, texture: Option<&ImageBuffer>) {
+        // orient so the interior is positive for all three edge functions
+        let area = edge(a, b, c.x, c.y);
+        if area == 0.0 || !area.is_finite() {
+            return;
+        }
+        let (b, c) = if area > 0.0 { (b, c) } else { (c, b) };
+        let area = area.abs();
+
+        let x0 = a.x.min(b.x).min(c.x).floor().max(0.0) as usize;
+        let y0 = a.y.min(b.y).min(c.y).floor().max(0.0) as usize;
+        let x1 = (a.x.max(b.x).max(c.x).ceil().max(0.0) as usize).min(self.w);
+        let y1 = (a.y.max(b.y).max(c.y).ceil().max(0.0) as usize).min(self.h);
+
+        let tl_bc = is_top_left(b, c);
+        let tl_ca = is_top_left(c, a);
+        let tl_ab = is_top_left(a, b);
+
+        for py in y0..y1 {
+            for px in x0..x1 {
+                let sx = px as f32 + 0.5;
+                let sy = py as f32 + 0.5;
+                let wa = edge(b, c, sx, sy);
+                let wb = edge(c, a, sx, sy);
+                let wc = edge(a, b, sx, sy);
+                if !covers(wa, tl_bc) || !covers(wb, tl_ca) || !covers(wc, tl_ab) {
+                    continue;
+                }
+                let (la, lb, lc) = (wa / area, wb / area, wc / area);
+                let z = la * a.z + lb * b.z + lc * c.z;
+                let idx = py * self.w + px;
+                if self.depth_test && z >= self.depth[idx] {
+                    continue;
+                }
+
+                // perspective correct: interpolate attr/w and 1/w, divide
+                let pa = la * a.inv_w;
+                let pb = lb * b.inv_w;
+                let pc = lc * c.inv_w;
+                let norm = 1.0 / (pa + pb + pc);
+                let (pa, pb, pc) = (pa * norm, pb * norm, pc * norm);
+                let col = vec4(
+                    pa * a.col.x + pb * b.col.x + pc * c.col.x,
+                    pa * a.col.y + pb * b.col.y + pc * c.col.y,
+                    pa * a.col.z + pb * b.col.z + pc * c.col.z,
+                    pa * a.col.w + pb * b.col.w + pc * c.col.w,
+                );
+                let src = match texture {
+                    Some(tex) => {
+                        let uv = vec2(
+                            pa * a.uv.x + pb * b.uv.x + pc * c.uv.x,
+                            pa * a.uv.y + pb * b.uv.y + pc * c.uv.y,
+                        );
+                        let t = sample(tex, uv, &self.sampler);
+                        vec4(t.x * col.x, t.y * col.y, t.z * col.z, t.w * col.w)
+                    },
+                    None => col,
+                };
+                // framebuffer is 8 bit, clamp like it would
+                let src = vec4(src.x.clamp(0.0, 1.0), src.y.clamp(0.0, 1.0), src.z.clamp(0.0, 1.0), src.w.clamp(0.0, 1.0));
+
+                self.colour[idx] = if self.blend {
+                    let dst = self.colour[idx];
+                    let sa = src.w;
+                    vec4(
+                        src.x * sa + dst.x * (1.0 - sa),
+                        src.y * sa + dst.y * (1.0 - sa),
+                        src.z * sa + dst.z * (1.0 - sa),
+                        src.w * sa + dst.w * (1.0 - sa),
+                    )
+                } else {
+                    src
+                };
+                if self.depth_test {
+                    self.depth[idx] = z;
+                }
+            }
+        }
+    }
+}
+
+/// draw mesh onto a w x h transparent image
+pub fn rasterize_xyzrgba(mesh: &[XYZRGBA], proj: &[f32; 16], w: usize, h: usize) -> ImageBuffer {
+    let mut r = Rasterizer::new(w, h);
+    r.draw_xyzrgba(mesh, proj);
+    r.to_image()
+}
+
+/// draw mesh onto a w x h transparent image, sampling texture nearest + clamped like the default SamplerDesc
+pub fn rasterize_xyzrgbauv(mesh: &[XYZRGBAUV], proj: &[f32; 16], texture: Option<&ImageBuffer>, w: usize, h: usize) -> ImageBuffer {
+    let mut r = Rasterizer::new(w, h);
+    r.draw_xyzrgbauv(mesh, proj, texture);
+    r.to_image()
+}
+
+fn transform(p: Vec3, col: Vec4, uv: Vec2, proj: &[f32; 16]) -> ClipVert {
+    let v = [p.x, p.y, p.z, 1.0];
+    let mut clip = [0.0; 4];
+    for (i, c) in clip.iter_mut().enumerate() {
+        *c = proj[i*4] * v[0] + proj[i*4 + 1] * v[1] + proj[i*4 + 2] * v[2] + proj[i*4 + 3] * v[3];
+    }
+    ClipVert { clip, col, uv }
+}
+
+/// sutherland hodgman against near, far and w > 0. x and y are left to the scissoring in raster_triangle
+fn clip_polygon(mut poly: Vec<ClipVert>) -> Vec<ClipVert> {
+    let planes: [fn(&[f32; 4]) -> f32; 3] = [
+        |c| c[2] + c[3],
+        |c| c[3] - c[2],
+        |c| c[3] - 1e-6,
+    ];
+    for plane in planes {
+        if poly.is_empty() {
+            break;
+        }
+        let mut out = vec![];
+        for i in 0..poly.len() {
+            let cur = &poly[i];
+            let next = &poly[(i + 1) % poly.len()];
+            let dc = plane(&cur.clip);
+            let dn = plane(&next.clip);
+            if dc >= 0.0 {
+                out.push(*cur);
+            }
+            if (dc >= 0.0) != (dn >= 0.0) {
+                out.push(cur.lerp(next, dc / (dc - dn)));
+            }
+        }
+        poly = out;
+    }
+    poly
+}
+
+/// always evaluated from the same end, so the two triangles sharing an edge get exactly opposite
+/// values and the top-left rule gives each pixel on it to one of them
+fn edge(a: &ScreenVert, b: &ScreenVert, px: f32, py: f32) -> f32 {
+    if (a.x, a.y) > (b.x, b.y) {
+        return -edge(b, a, px, py);
+    }
+    (b.x - a.x) * (py - a.y) - (b.y - a.y) * (px - a.x)
+}
+
+/// with y down and the interior on the positive side
+fn is_top_left(a: &ScreenVert, b: &ScreenVert) -> bool {
+    let dx = b.x - a.x;
+    let dy = b.y - a.y;
+    (dy == 0.0 && dx > 0.0) || dy < 0.0
+}
+
+fn covers(w: f32, top_left: bool) -> bool {
+    w > 0.0 || (w == 0.0 && top_left)
+}
+
+fn to_u8(x: f32) -> u8 {
+    (x.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+fn texel(tex: &ImageBuffer, x: i64, y: i64, wrap: Wrap) -> Vec4 {
+    let wrap_coord = |i: i64, n: i64| -> i64 {
+        match wrap {
+            Wrap::Clamp => i.clamp(0, n - 1),
+            Wrap::Repeat => i.rem_euclid(n),
+            Wrap::Mirror => {
+                let m = i.rem_euclid(2 * n);
+                if m < n { m } else { 2 * n - 1 - m }
+            },
+        }
+    };
+    let x = wrap_coord(x, tex.w as i64) as usize;
+    let y = wrap_coord(y, tex.h as i64) as usize;
+    tex.get(x, y)
+}
+
+/// texture() with the sampler's wrapping and nearest or bilinear filtering. Mip levels arent emulated
+fn sample(tex: &ImageBuffer, uv: Vec2, sampler: &SamplerDesc) -> Vec4 {
+    if tex.w == 0 || tex.h == 0 {
+        return vec4(0.0, 0.0, 0.0, 1.0);
+    }
+    let x = uv.x * tex.w as f32;
+    let y = uv.y * tex.h as f32;
+    match sampler.filter {
+        Filter::Nearest => texel(tex, x.floor() as i64, y.floor() as i64, sampler.wrap),
+        Filter::Linear | Filter::Trilinear => {
+            let x = x - 0.5;
+            let y = y - 0.5;
+            let x0 = x.floor();
+            let y0 = y.floor();
+            let fx = x - x0;
+            let fy = y - y0;
+            let (x0, y0) = (x0 as i64, y0 as i64);
+            let t00 = texel(tex, x0, y0, sampler.wrap);
+            let t10 = texel(tex, x0 + 1, y0, sampler.wrap);
+            let t01 = texel(tex, x0, y0 + 1, sampler.wrap);
+            let t11 = texel(tex, x0 + 1, y0 + 1, sampler.wrap);
+            t00.lerp(t10, fx).lerp(t01.lerp(t11, fx), fy)
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// pixel coordinates, y down
+    fn pixel_proj(w: usize, h: usize) -> [f32; 16] {
+        [
+            2.0 / w as f32, 0.0, 0.0, -1.0,
+            0.0, -2.0 / h as f32, 0.0, 1.0,
+            0.0, 0.0, 1.0, 0.0,
+            0.0, 0.0, 0.0, 1.0,
+        ]
+    }
+
+    /// 90 degree fov, square aspect, looking down -z
+    fn perspective(near: f32, far: f32) -> [f32; 16] {
+        [
+            1.0, 0.0, 0.0, 0.0,
+            0.0, 1.0, 0.0, 0.0,
+            0.0, 0.0, (far + near) / (near - far), 2.0 * far * near / (near - far),
+            0.0, 0.0, -1.0, 0.0,
+        ]
+    }
+
+    fn vert(x: f32, y: f32, z: f32) -> XYZRGBA {
+        XYZRGBA { xyz: vec3(x, y, z), rgba: vec4(1.0, 1.0, 1.0, 1.0) }
+    }
+
+    fn coverage(tri: &[XYZRGBA], proj: &[f32; 16], w: usize, h: usize) -> Vec<bool> {
+        let mut r = Rasterizer::new(w, h);
+        r.depth_test = false;
+        r.blend = false;
+        r.draw_xyzrgba(tri, proj);
+        r.colour.iter().map(|c| c.w > 0.0).collect()
+    }
+
+    fn fan_counts(centre: (f32, f32), ring: &[(f32, f32)], w: usize, h: usize) -> Vec<usize> {
+        let proj = pixel_proj(w, h);
+        let mut counts = vec![0; w*h];
+        for i in 0..ring.len() {
+            let (a, b) = (ring[i], ring[(i + 1) % ring.len()]);
+            let tri = [vert(centre.0, centre.1, 0.0), vert(a.0, a.1, 0.0), vert(b.0, b.1, 0.0)];
+            for (count, covered) in counts.iter_mut().zip(coverage(&tri, &proj, w, h)) {
+                *count += covered as usize;
+            }
+        }
+        counts
+    }
+
+    #[test]
+    fn shared_edges_touch_pixels_once() {
+        // every vertex and most edges land exactly on pixel centres. A power of two size so the
+        // projection round trips them exactly
+        let ring = [(0.5, 0.5), (4.5, 0.5), (8.5, 0.5), (8.5, 4.5), (8.5, 8.5), (4.5, 8.5), (0.5, 8.5), (0.5, 4.5)];
+        let counts = fan_counts((4.5, 4.5), &ring, 16, 16);
+        assert!(counts.iter().all(|&c| c <= 1), "{:?}", counts);
+        // top and left edges are in, bottom and right are out: centres 0.5..7.5
+        assert_eq!(counts.iter().sum::<usize>(), 64);
+        assert_eq!(counts[0], 1);
+        assert_eq!(counts[8], 0);
+        assert_eq!(counts[8 * 16], 0);
+
+        // same again where the projection rounds, so the two sides of an edge only agree if
+        // they evaluate it the same way
+        let counts = fan_counts((4.5, 4.5), &ring, 10, 10);
+        assert!(counts.iter().all(|&c| c <= 1), "{:?}", counts);
+        let ring = [(0.3, 1.7), (5.1, 0.2), (9.7, 3.3), (8.1, 9.4), (2.2, 8.8)];
+        let counts = fan_counts((4.9, 5.3), &ring, 10, 10);
+        assert!(counts.iter().all(|&c| c <= 1), "{:?}", counts);
+    }
+
+    #[test]
+    fn near_plane_clips() {
+        let (w, h) = (32, 32);
+        let proj = perspective(0.1, 100.0);
+        // a floor running from in front of the camera to behind it. Unclipped, the vertex behind
+        // the camera would project into the top half
+        let tri = [vert(-1.0, -1.0, -2.0), vert(1.0, -1.0, -2.0), vert(0.0, -1.0, 1.0)];
+        let mut r = Rasterizer::new(w, h);
+        r.draw_xyzrgba(&tri, &proj);
+        let covered: Vec<usize> = (0..w*h).filter(|&i| r.colour[i].w > 0.0).collect();
+        assert!(!covered.is_empty());
+        assert!(covered.iter().all(|&i| i / w >= h / 2), "drew above the horizon");
+        assert!(covered.iter().all(|&i| r.depth[i] >= 0.0 && r.depth[i] <= 1.0));
+        // reaches the bottom of the screen, where the floor meets the near plane
+        assert!(covered.iter().any(|&i| i / w == h - 1));
+
+        let behind = [vert(-1.0, -1.0, 1.0), vert(1.0, -1.0, 1.0), vert(0.0, 1.0, 0.5)];
+        assert!(coverage(&behind, &proj, w, h).iter().all(|&c| !c));
+    }
+
+    #[test]
+    fn uvs_are_perspective_correct() {
+        let (w, h) = (32, 32);
+        let proj = perspective(0.1, 100.0);
+        // u runs along a floor from z = -1 to z = -9, the texture's red is u
+        let mut tex = ImageBuffer::new(256, 1);
+        for x in 0..256 {
+            tex.set(x, 0, vec4(x as f32 / 255.0, 0.0, 0.0, 1.0));
+        }
+        let v = |x: f32, z: f32, u: f32| XYZRGBAUV { xyz: vec3(x, -1.0, z), rgba: vec4(1.0, 1.0, 1.0, 1.0), uv: vec2(u, 0.0) };
+        let quad = [v(-2.0, -1.0, 0.0), v(2.0, -1.0, 0.0), v(2.0, -9.0, 1.0), v(-2.0, -1.0, 0.0), v(2.0, -9.0, 1.0), v(-2.0, -9.0, 1.0)];
+        let mut r = Rasterizer::new(w, h);
+        r.draw_xyzrgbauv(&quad, &proj, Some(&tex));
+
+        let mut checked = 0;
+        for py in 0..h {
+            for px in 0..w {
+                // cast the pixel centre's ray at the floor
+                let nx = (px as f32 + 0.5) / w as f32 * 2.0 - 1.0;
+                let ny = 1.0 - (py as f32 + 0.5) / h as f32 * 2.0;
+                if ny >= 0.0 {
+                    continue;
+                }
+                let t = -1.0 / ny;
+                if !(1.2..8.8).contains(&t) || (t * nx).abs() > 1.8 {
+                    continue;
+                }
+                let u = (t - 1.0) / 8.0;
+                let expected = (u * 256.0).floor().min(255.0) / 255.0;
+                let got = r.colour[py * w + px];
+                assert!(got.w > 0.0);
+                assert!((got.x - expected).abs() <= 2.0 / 255.0, "pixel {},{}: u {} gave {}", px, py, expected, got.x);
+                checked += 1;
+            }
+        }
+        assert!(checked > 50);
+    }
+}
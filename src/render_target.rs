@@ -0,0 +1,168 @@
+//! Offscreen framebuffers to render into, then use as a texture, eg. for post processing, minimaps or compositing UI.
+//! The colour attachment is a normal Texture so it goes straight into ProgramXYZRGBAUV::bind_texture.
+//! Like anything rendered by gl, v = 0 is the bottom of the image.
+
+use glow::HasContext;
+use crate::error::*;
+use crate::gc::*;
+use crate::texture::*;
+
+/// what a RenderTarget gets besides the colour texture
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderTargetDesc {
+    pub depth: bool,
+    pub stencil: bool,
+    /// for sampling the colour texture afterwards
+    pub sampler: SamplerDesc,
+}
+
+impl Default for RenderTargetDesc {
+    fn default() -> Self {
+        RenderTargetDesc { depth: false, stencil: false, sampler: SamplerDesc::linear() }
+    }
+}
+
+impl RenderTargetDesc {
+    pub fn with_depth(self, depth: bool) -> Self {
+        RenderTargetDesc { depth, ..self }
+    }
+    pub fn with_stencil(self, stencil: bool) -> Self {
+        RenderTargetDesc { stencil, ..self }
+    }
+    pub fn with_sampler(self, sampler: SamplerDesc) -> Self {
+        RenderTargetDesc { sampler, ..self }
+    }
+    /// renderbuffer format and attachment point, if any
+    fn depth_stencil_format(&self) -> Option<(u32, u32)> {
+        match (self.depth, self.stencil) {
+            (true, true) => Some((glow::DEPTH24_STENCIL8, glow::DEPTH_STENCIL_ATTACHMENT)),
+            (true, false) => Some((glow::DEPTH_COMPONENT24, glow::DEPTH_ATTACHMENT)),
+            (false, true) => Some((glow::STENCIL_INDEX8, glow::STENCIL_ATTACHMENT)),
+            (false, false) => None,
+        }
+    }
+}
+
+/// an fbo with a colour texture and optionally a depth and/or stencil renderbuffer
+pub struct RenderTarget<G: HasContext = glow::Context> {
+    pub fbo: G::Framebuffer,
+    pub colour: Texture<G>,
+    pub depth_stencil: Option<G::Renderbuffer>,
+    pub w: usize,
+    pub h: usize,
+    pub desc: RenderTargetDesc,
+    leak: LeakCheck,
+}
+
+impl<G: HasContext> RenderTarget<G> {
+    /// # Safety
+    /// gl has to be current. Leaves the default framebuffer bound
+    pub unsafe fn new(gl: &G, w: usize, h: usize, desc: &RenderTargetDesc) -> Result<Self, GlowMeshError> {
+        let colour = Texture::empty(gl, w, h, &desc.sampler)?;
+        let fbo = match gl.create_framebuffer() {
+            Ok(fbo) => fbo,
+            Err(e) => {
+                colour.free(gl);
+                return Err(GlowMeshError::ResourceCreation(e));
+            },
+        };
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(fbo));
+        gl.framebuffer_texture_2d(glow::FRAMEBUFFER, glow::COLOR_ATTACHMENT0, glow::TEXTURE_2D, Some(colour.texture), 0);
+
+        let depth_stencil = match desc.depth_stencil_format() {
+            Some((format, attachment)) => match gl.create_renderbuffer() {
+                Ok(rb) => {
+                    gl.bind_renderbuffer(glow::RENDERBUFFER, Some(rb));
+                    gl.renderbuffer_storage(glow::RENDERBUFFER, format, w as i32, h as i32);
+                    gl.framebuffer_renderbuffer(glow::FRAMEBUFFER, attachment, glow::RENDERBUFFER, Some(rb));
+                    Some(rb)
+                },
+                Err(e) => {
+                    gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+                    gl.delete_framebuffer(fbo);
+                    colour.free(gl);
+                    return Err(GlowMeshError::ResourceCreation(e));
+                },
+            },
+            None => None,
+        };
+
+        let status = gl.check_framebuffer_status(glow::FRAMEBUFFER);
+        gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        let target = RenderTarget { fbo, colour, depth_stencil, w, h, desc: *desc, leak: LeakCheck::new("RenderTarget") };
+        if status != glow::FRAMEBUFFER_COMPLETE {
+            target.free(gl);
+            return Err(GlowMeshError::IncompleteFramebuffer(status));
+        }
+        Ok(target)
+    }
+    /// draw into this from now on, viewport set to cover it
+    ///
+    /// # Safety
+    /// gl has to be the context the target was made on, and current
+    pub unsafe fn bind(&self, gl: &G) {
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.fbo));
+        gl.viewport(0, 0, self.w as i32, self.h as i32);
+    }
+    /// back to the default framebuffer with the viewport set to the window size.
+    /// Regenerates the colour texture's mipmaps if its sampler wants them
+    ///
+    /// # Safety
+    /// gl has to be the context the target was made on, and current
+    pub unsafe fn unbind(&self, gl: &G, window_w: usize, window_h: usize) {
+        gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        gl.viewport(0, 0, window_w as i32, window_h as i32);
+        if self.colour.sampler.generates_mipmaps() {
+            gl.bind_texture(glow::TEXTURE_2D, Some(self.colour.texture));
+            gl.generate_mipmap(glow::TEXTURE_2D);
+        }
+    }
+    /// reallocate everything at the new size, eg. when the window resizes. Contents are lost
+    ///
+    /// # Safety
+    /// gl has to be the context the target was made on, and current
+    pub unsafe fn resize(&mut self, gl: &G, w: usize, h: usize) -> Result<(), GlowMeshError> {
+        let resized = RenderTarget::new(gl, w, h, &self.desc)?;
+        std::mem::replace(self, resized).free(gl);
+        Ok(())
+    }
+    pub fn free(&self, gl: &G) {
+        unsafe {
+            gl.delete_framebuffer(self.fbo);
+            if let Some(rb) = self.depth_stencil {
+                gl.delete_renderbuffer(rb);
+            }
+        }
+        self.colour.free(gl);
+        self.leak.freed();
+    }
+}
+
+impl<G: HasContext> Free<G> for RenderTarget<G> {
+    fn free(&self, gl: &G) {
+        RenderTarget::free(self, gl)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn failed_creates_leak_nothing() {
+        let desc = RenderTargetDesc::default().with_depth(true);
+        for n in 0..3 {
+            let gl = RecordingContext::new();
+            gl.fail_nth_create(n);
+            assert!(matches!(unsafe { RenderTarget::new(&gl, 4, 4, &desc) }, Err(GlowMeshError::ResourceCreation(_))));
+            assert!(gl.live_objects().is_empty(), "failing create {} leaked {:?}", n, gl.live_objects());
+            assert_eq!(gl.last_call("bind_framebuffer").map(|c| c.args), if n < 2 { None } else { Some(vec![Arg::U32(glow::FRAMEBUFFER), Arg::Name(None)]) });
+        }
+        let gl = RecordingContext::new();
+        let target = unsafe { RenderTarget::new(&gl, 4, 4, &desc) }.unwrap();
+        assert_eq!(gl.live_objects().len(), 3);
+        target.free(&gl);
+        assert!(gl.live_objects().is_empty());
+    }
+}
@@ -0,0 +1,95 @@
+//! Reading the framebuffer back and pngs in and out.
+
+use glow::HasContext;
+use minimg::*;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use crate::error::*;
+
+/// read w x h pixels from the bound framebuffer, x, y being the bottom left corner like glReadPixels.
+/// Flipped so row 0 is the top, ie. how it looked on screen. PACK_ALIGNMENT is put back how it was.
+///
+/// # Safety
+/// gl has to be current and the rectangle has to be inside the bound framebuffer
+pub unsafe fn screenshot<G: HasContext>(gl: &G, x: usize, y: usize, w: usize, h: usize) -> ImageBuffer {
+    let mut img = ImageBuffer::new(w, h);
+    let alignment = gl.get_parameter_i32(glow::PACK_ALIGNMENT);
+    gl.pixel_store_i32(glow::PACK_ALIGNMENT, 1);
+    gl.read_pixels(x as i32, y as i32, w as i32, h as i32, glow::RGBA, glow::UNSIGNED_BYTE, glow::PixelPackData::Slice(&mut img.data));
+    gl.pixel_store_i32(glow::PACK_ALIGNMENT, alignment);
+    flip_vertical(&mut img);
+    img
+}
+
+/// swap rows top to bottom, gl's row 0 is the bottom
+pub fn flip_vertical(img: &mut ImageBuffer) {
+    let stride = img.w * 4;
+    for y in 0..img.h / 2 {
+        let (top, bottom) = img.data.split_at_mut((img.h - 1 - y) * stride);
+        top[y*stride..(y + 1)*stride].swap_with_slice(&mut bottom[..stride]);
+    }
+}
+
+/// write img as an 8 bit RGBA png
+pub fn save_png<P: AsRef<Path>>(img: &ImageBuffer, path: P) -> Result<(), GlowMeshError> {
+    let path = path.as_ref();
+    let file = File::create(path).map_err(|error| GlowMeshError::Io { path: path.to_path_buf(), error })?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), img.w as u32, img.h as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let png_err = |e: png::EncodingError| GlowMeshError::Png { path: path.to_path_buf(), error: e.to_string() };
+    let mut writer = encoder.write_header().map_err(png_err)?;
+    writer.write_image_data(&img.data).map_err(png_err)
+}
+
+/// read a png of any colour type into RGBA8. Unlike ImageBuffer::from_bytes this doesnt assume the file is already RGBA
+pub fn load_png<P: AsRef<Path>>(path: P) -> Result<ImageBuffer, GlowMeshError> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|error| GlowMeshError::Io { path: path.to_path_buf(), error })?;
+    let png_err = |e: png::DecodingError| GlowMeshError::Png { path: path.to_path_buf(), error: e.to_string() };
+    let mut decoder = png::Decoder::new(BufReader::new(file));
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let mut reader = decoder.read_info().map_err(png_err)?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).map_err(png_err)?;
+    buf.truncate(info.buffer_size());
+    let channels = match info.color_type {
+        png::ColorType::Grayscale => 1,
+        png::ColorType::GrayscaleAlpha => 2,
+        png::ColorType::Rgb => 3,
+        png::ColorType::Rgba => 4,
+        png::ColorType::Indexed => return Err(GlowMeshError::Png { path: path.to_path_buf(), error: "indexed png wasnt expanded".to_string() }),
+    };
+    let mut img = ImageBuffer::new(info.width as usize, info.height as usize);
+    for (i, px) in buf.chunks_exact(channels).enumerate() {
+        let rgba = match px {
+            [l] => [*l, *l, *l, 255],
+            [l, a] => [*l, *l, *l, *a],
+            [r, g, b] => [*r, *g, *b, 255],
+            [r, g, b, a] => [*r, *g, *b, *a],
+            _ => unreachable!(),
+        };
+        img.data[i*4..i*4 + 4].copy_from_slice(&rgba);
+    }
+    Ok(img)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn screenshot_restores_pack_alignment() {
+        let gl = RecordingContext::new();
+        gl.set_parameter(glow::PACK_ALIGNMENT, 4);
+        let img = unsafe { screenshot(&gl, 0, 0, 3, 2) };
+        assert_eq!((img.w, img.h), (3, 2));
+        let stores: Vec<Vec<Arg>> = gl.calls_named("pixel_store_i32").into_iter().map(|c| c.args).collect();
+        assert_eq!(stores, vec![
+            vec![Arg::U32(glow::PACK_ALIGNMENT), Arg::I32(1)],
+            vec![Arg::U32(glow::PACK_ALIGNMENT), Arg::I32(4)],
+        ]);
+    }
+}
@@ -0,0 +1,248 @@
+//! Parsing driver shader info logs into (line, column, message) and pointing at the source.
+//! Handles the common formats:
+//! * Mesa:              `0:12(5): error: 'foo' undeclared`
+//! * NVIDIA:            `0(12) : error C1008: undefined variable "foo"`
+//! * ANGLE/AMD/Apple:   `ERROR: 0:12: 'foo' : undeclared identifier`
+//!
+//! Anything else is kept as an entry with no line so nothing gets lost.
+
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+    /// 1 based, as the driver reports it
+    pub line: Option<usize>,
+    /// 1 based, only some drivers report it
+    pub column: Option<usize>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+pub fn parse_info_log(log: &str) -> Vec<LogEntry> {
+    log.lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .map(parse_line)
+        .collect()
+}
+
+fn parse_line(l: &str) -> LogEntry {
+    // ANGLE style: severity first
+    if let Some((severity, rest)) = strip_severity(l) {
+        let rest = rest.trim_start_matches(':').trim_start();
+        if let Some((line, column, msg)) = parse_location_colon(rest) {
+            return LogEntry { line: Some(line), column, severity, message: msg.trim().to_string() };
+        }
+        return LogEntry { line: None, column: None, severity, message: rest.trim().to_string() };
+    }
+    // Mesa / NVIDIA style: location first
+    if let Some((line, column, rest)) = parse_location_paren(l).or_else(|| parse_location_colon(l)) {
+        let rest = rest.trim_start().trim_start_matches(':').trim_start();
+        let (severity, msg) = match strip_severity(rest) {
+            Some((severity, msg)) => (severity, msg),
+            None => (Severity::Error, rest),
+        };
+        let msg = strip_error_code(msg.trim_start_matches(':').trim_start());
+        return LogEntry { line: Some(line), column, severity, message: msg.trim().to_string() };
+    }
+    LogEntry { line: None, column: None, severity: guess_severity(l), message: l.to_string() }
+}
+
+/// "error", "ERROR", "warning", "WARNING", "note", "info" at the start
+fn strip_severity(s: &str) -> Option<(Severity, &str)> {
+    let lower = s.to_ascii_lowercase();
+    for (word, severity) in [("error", Severity::Error), ("warning", Severity::Warning), ("note", Severity::Note), ("info", Severity::Note)] {
+        if lower.starts_with(word) {
+            let rest = &s[word.len()..];
+            if rest.starts_with(':') || rest.starts_with(' ') {
+                return Some((severity, rest));
+            }
+        }
+    }
+    None
+}
+
+/// NVIDIA puts an error code before the colon: "C1008: undefined variable"
+fn strip_error_code(s: &str) -> &str {
+    let code_len = s.find(':').unwrap_or(0);
+    let code = &s[..code_len];
+    if code_len > 1 && !code.contains(' ') && code.chars().skip(1).all(|c| c.is_ascii_digit()) {
+        s[code_len + 1..].trim_start()
+    } else {
+        s
+    }
+}
+
+fn guess_severity(s: &str) -> Severity {
+    let lower = s.to_ascii_lowercase();
+    if lower.contains("warning") {
+        Severity::Warning
+    } else {
+        Severity::Error
+    }
+}
+
+fn parse_number(s: &str) -> Option<(usize, &str)> {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    if end == 0 {
+        return None;
+    }
+    Some((s[..end].parse().ok()?, &s[end..]))
+}
+
+/// `0:12(5):` (Mesa) or `0(12) :` (NVIDIA)
+fn parse_location_paren(s: &str) -> Option<(usize, Option<usize>, &str)> {
+    let (_, rest) = parse_number(s)?;
+    if let Some(rest) = rest.strip_prefix(':') {
+        let (line, rest) = parse_number(rest)?;
+        let rest = rest.strip_prefix('(')?;
+        let (column, rest) = parse_number(rest)?;
+        let rest = rest.strip_prefix(')')?;
+        return Some((line, Some(column), rest));
+    }
+    let rest = rest.strip_prefix('(')?;
+    let (line, rest) = parse_number(rest)?;
+    let rest = rest.strip_prefix(')')?;
+    Some((line, None, rest))
+}
+
+/// `0:12:` or `0:12:5:` (ANGLE and friends)
+fn parse_location_colon(s: &str) -> Option<(usize, Option<usize>, &str)> {
+    let (_, rest) = parse_number(s)?;
+    let rest = rest.strip_prefix(':')?;
+    let (line, rest) = parse_number(rest)?;
+    let rest = rest.strip_prefix(':')?;
+    if let Some((column, after)) = parse_number(rest) {
+        if let Some(after) = after.strip_prefix(':') {
+            return Some((line, Some(column), after));
+        }
+    }
+    Some((line, None, rest))
+}
+
+/// render the entries against the source, rustc style:
+/// ```text
+/// error: 'foo' : undeclared identifier
+///   --> 12:5
+///    |
+/// 11 |     vec4 c = col;
+/// 12 |     frag_colour = foo;
+///    |     ^
+/// ```
+pub fn annotate_source(source: &str, entries: &[LogEntry]) -> String {
+    let lines: Vec<&str> = source.lines().collect();
+    let gutter = lines.len().to_string().len();
+    let mut out = String::new();
+    for e in entries {
+        let severity = match e.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+        };
+        let _ = writeln!(out, "{}: {}", severity, e.message);
+        let line = match e.line {
+            Some(line) if line >= 1 && line <= lines.len() => line,
+            _ => continue,
+        };
+        match e.column {
+            Some(column) => { let _ = writeln!(out, "{:w$}--> {}:{}", "", line, column, w = gutter); },
+            None => { let _ = writeln!(out, "{:w$}--> {}", "", line, w = gutter); },
+        }
+        let _ = writeln!(out, "{:w$} |", "", w = gutter);
+        if line >= 2 {
+            let _ = writeln!(out, "{:>w$} | {}", line - 1, lines[line - 2], w = gutter);
+        }
+        let text = lines[line - 1];
+        let _ = writeln!(out, "{:>w$} | {}", line, text, w = gutter);
+        let marker = match e.column {
+            Some(column) => format!("{:c$}^", "", c = column.saturating_sub(1).min(text.len())),
+            None => {
+                let indent = text.len() - text.trim_start().len();
+                format!("{:i$}{}", "", "^".repeat(text.trim().len().max(1)), i = indent)
+            },
+        };
+        let _ = writeln!(out, "{:w$} | {}", "", marker, w = gutter);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(line: Option<usize>, column: Option<usize>, severity: Severity, message: &str) -> LogEntry {
+        LogEntry { line, column, severity, message: message.to_string() }
+    }
+
+    #[test]
+    fn mesa() {
+        let log = "0:12(5): error: `foo' undeclared\n0:3(10): warning: extension `GL_ARB_foo' unsupported in fragment shader\n";
+        assert_eq!(parse_info_log(log), vec![
+            entry(Some(12), Some(5), Severity::Error, "`foo' undeclared"),
+            entry(Some(3), Some(10), Severity::Warning, "extension `GL_ARB_foo' unsupported in fragment shader"),
+        ]);
+    }
+
+    #[test]
+    fn nvidia() {
+        let log = "0(12) : error C1008: undefined variable \"foo\"\n0(7) : warning C7022: unrecognized profile specifier \"bar\"\n";
+        assert_eq!(parse_info_log(log), vec![
+            entry(Some(12), None, Severity::Error, "undefined variable \"foo\""),
+            entry(Some(7), None, Severity::Warning, "unrecognized profile specifier \"bar\""),
+        ]);
+    }
+
+    #[test]
+    fn angle() {
+        let log = "ERROR: 0:12: 'foo' : undeclared identifier\nWARNING: 0:4: 'highp' : precision qualifier ignored\nERROR: 2 compilation errors.  No code generated.\n\n";
+        assert_eq!(parse_info_log(log), vec![
+            entry(Some(12), None, Severity::Error, "'foo' : undeclared identifier"),
+            entry(Some(4), None, Severity::Warning, "'highp' : precision qualifier ignored"),
+            entry(None, None, Severity::Error, "2 compilation errors.  No code generated."),
+        ]);
+    }
+
+    #[test]
+    fn unparseable_lines_are_kept() {
+        let log = "Fragment info\n-------------\nsomething went wrong, warning level 3\n";
+        assert_eq!(parse_info_log(log), vec![
+            entry(None, None, Severity::Error, "Fragment info"),
+            entry(None, None, Severity::Error, "-------------"),
+            entry(None, None, Severity::Warning, "something went wrong, warning level 3"),
+        ]);
+    }
+
+    #[test]
+    fn annotate() {
+        let source = "void main() {\n    vec4 c = col;\n    frag_colour = foo;\n}";
+        let out = annotate_source(source, &parse_info_log("0:3(19): error: `foo' undeclared"));
+        assert_eq!(out, "error: `foo' undeclared\n --> 3:19\n  |\n2 |     vec4 c = col;\n3 |     frag_colour = foo;\n  |                   ^\n");
+    }
+
+    #[test]
+    fn annotate_without_column_underlines_the_line() {
+        let source = "void main() {\n    foo();\n}";
+        let out = annotate_source(source, &parse_info_log("0(2) : error C1008: undefined variable \"foo\""));
+        assert_eq!(out, "error: undefined variable \"foo\"\n --> 2\n  |\n1 | void main() {\n2 |     foo();\n  |     ^^^^^^\n");
+    }
+
+    #[test]
+    fn annotate_out_of_range() {
+        let source = "void main() {\n}";
+        let entries = parse_info_log("0:0(1): error: before the start\n0:99(1): error: past the end\nERROR: 0:3: 'x' : just past the end\nlink failed");
+        assert_eq!(annotate_source(source, &entries), "error: before the start\nerror: past the end\nerror: 'x' : just past the end\nerror: link failed\n");
+    }
+
+    #[test]
+    fn annotate_column_past_the_end_of_the_line() {
+        let out = annotate_source("x;", &[entry(Some(1), Some(40), Severity::Note, "here")]);
+        assert_eq!(out, "note: here\n --> 1:40\n  |\n1 | x;\n  |   ^\n");
+    }
+}
@@ -0,0 +1,295 @@
+//! Golden image tests, behind the `testing` feature. Meshes get drawn with the software rasterizer so no gpu is needed:
+//! ```no_run
+//! use glow_mesh::assert_render_matches;
+//! use glow_mesh::xyzrgba::*;
+//! use minvect::*;
+//!
+//! let mut buf = vec![];
+//! put_poly(&mut buf, vec2(0.0, 0.0), 0.5, 7, 0.0, vec4(1.0, 0.0, 0.0, 1.0), 0.0);
+//! let proj = [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0];
+//! assert_render_matches!(buf, proj, "snapshots/heptagon.png");
+//! ```
+//! Paths are relative to the calling crate's manifest dir. Run with GLOW_MESH_BLESS=1 to write new snapshots.
+//! On a mismatch the render goes next to the snapshot as name.actual.png, along with name.diff.png
+//! which is the expected image greyed out with differing pixels in red.
+
+use minimg::*;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use crate::raster::*;
+use crate::screenshot::*;
+use crate::xyzrgba::*;
+use crate::xyzrgbauv::*;
+
+/// size assert_render_matches! renders at if none is given
+pub const DEFAULT_SNAPSHOT_SIZE: usize = 128;
+
+/// set to anything but "0" to overwrite snapshots instead of comparing
+pub const BLESS_VAR: &str = "GLOW_MESH_BLESS";
+
+/// a mesh the snapshot harness knows how to rasterize
+pub trait SnapshotMesh {
+    fn rasterize(&self, proj: &[f32; 16], w: usize, h: usize) -> ImageBuffer;
+}
+
+impl SnapshotMesh for [XYZRGBA] {
+    fn rasterize(&self, proj: &[f32; 16], w: usize, h: usize) -> ImageBuffer {
+        rasterize_xyzrgba(self, proj, w, h)
+    }
+}
+
+/// untextured, ie. just the vertex colours
+impl SnapshotMesh for [XYZRGBAUV] {
+    fn rasterize(&self, proj: &[f32; 16], w: usize, h: usize) -> ImageBuffer {
+        rasterize_xyzrgbauv(self, proj, None, w, h)
+    }
+}
+
+/// how far off a render was, per channel
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffStats {
+    pub differing_pixels: usize,
+    pub total_pixels: usize,
+    /// max absolute difference of r, g, b, a
+    pub max: [u8; 4],
+    /// mean absolute difference of r, g, b, a over all pixels
+    pub mean: [f64; 4],
+}
+
+impl fmt::Display for DiffStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}/{} pixels differ", self.differing_pixels, self.total_pixels)?;
+        for (i, c) in ["r", "g", "b", "a"].iter().enumerate() {
+            writeln!(f, "  {}: max {} mean {:.3}", c, self.max[i], self.mean[i])?;
+        }
+        Ok(())
+    }
+}
+
+/// both images have to be the same size
+pub fn diff_stats(expected: &ImageBuffer, actual: &ImageBuffer) -> DiffStats {
+    let total_pixels = expected.w * expected.h;
+    let mut differing_pixels = 0;
+    let mut max = [0u8; 4];
+    let mut sum = [0u64; 4];
+    for (e, a) in expected.data.chunks_exact(4).zip(actual.data.chunks_exact(4)) {
+        if e != a {
+            differing_pixels += 1;
+        }
+        for i in 0..4 {
+            let d = e[i].abs_diff(a[i]);
+            max[i] = max[i].max(d);
+            sum[i] += d as u64;
+        }
+    }
+    let mean = sum.map(|s| if total_pixels == 0 { 0.0 } else { s as f64 / total_pixels as f64 });
+    DiffStats { differing_pixels, total_pixels, max, mean }
+}
+
+/// expected faded to grey with differing pixels in red, brighter the bigger the difference
+pub fn diff_image(expected: &ImageBuffer, actual: &ImageBuffer) -> ImageBuffer {
+    let mut img = ImageBuffer::new(expected.w, expected.h);
+    for (i, (e, a)) in expected.data.chunks_exact(4).zip(actual.data.chunks_exact(4)).enumerate() {
+        let d = (0..4).map(|c| e[c].abs_diff(a[c])).max().unwrap();
+        let px = if d > 0 {
+            [128 + d / 2, 0, 0, 255]
+        } else {
+            let luma = (e[0] as u32 * 3 + e[1] as u32 * 6 + e[2] as u32) / 10;
+            let grey = (64 + luma * e[3] as u32 / 255 / 4) as u8;
+            [grey, grey, grey, 255]
+        };
+        img.data[i*4..i*4 + 4].copy_from_slice(&px);
+    }
+    img
+}
+
+fn blessing() -> bool {
+    std::env::var(BLESS_VAR).map(|v| v != "0").unwrap_or(false)
+}
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    path.with_file_name(format!("{}.{}.png", stem, suffix))
+}
+
+/// compare actual against the png at path, writing the .actual and .diff images if they dont match.
+/// With GLOW_MESH_BLESS set, writes actual to path instead. Err is a message for the panic.
+pub fn check_snapshot(actual: &ImageBuffer, path: &Path) -> Result<(), String> {
+    check_or_bless(actual, path, blessing())
+}
+
+fn check_or_bless(actual: &ImageBuffer, path: &Path, bless: bool) -> Result<(), String> {
+    let actual_path = sibling(path, "actual");
+    let diff_path = sibling(path, "diff");
+    if bless {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+        }
+        save_png(actual, path).map_err(|e| e.to_string())?;
+        let _ = std::fs::remove_file(&actual_path);
+        let _ = std::fs::remove_file(&diff_path);
+        return Ok(());
+    }
+    if !path.exists() {
+        save_png(actual, &actual_path).map_err(|e| e.to_string())?;
+        return Err(format!("no snapshot at {}, render written to {}. Run with {}=1 to accept it", path.display(), actual_path.display(), BLESS_VAR));
+    }
+    let expected = load_png(path).map_err(|e| e.to_string())?;
+    if expected.w != actual.w || expected.h != actual.h {
+        save_png(actual, &actual_path).map_err(|e| e.to_string())?;
+        return Err(format!("snapshot {} is {}x{} but the render is {}x{}, render written to {}", path.display(), expected.w, expected.h, actual.w, actual.h, actual_path.display()));
+    }
+    if expected.data == actual.data {
+        let _ = std::fs::remove_file(&actual_path);
+        let _ = std::fs::remove_file(&diff_path);
+        return Ok(());
+    }
+    save_png(actual, &actual_path).map_err(|e| e.to_string())?;
+    save_png(&diff_image(&expected, actual), &diff_path).map_err(|e| e.to_string())?;
+    Err(format!("render doesnt match snapshot {}\n{}render: {}\ndiff: {}\nRun with {}=1 to accept it",
+        path.display(), diff_stats(&expected, actual), actual_path.display(), diff_path.display(), BLESS_VAR))
+}
+
+/// rasterize mesh and check_snapshot it
+pub fn check_render<M: SnapshotMesh + ?Sized>(mesh: &M, proj: &[f32; 16], path: &Path, w: usize, h: usize) -> Result<(), String> {
+    check_snapshot(&mesh.rasterize(proj, w, h), path)
+}
+
+/// `assert_render_matches!(mesh, proj, "snapshots/name.png")` or with a size, `assert_render_matches!(mesh, proj, "snapshots/name.png", w, h)`.
+/// mesh is anything that slices to [XYZRGBA] or [XYZRGBAUV], proj is a row major [f32; 16] like set_proj takes.
+#[macro_export]
+macro_rules! assert_render_matches {
+    ($mesh:expr, $proj:expr, $path:expr) => {
+        $crate::assert_render_matches!($mesh, $proj, $path, $crate::snapshot::DEFAULT_SNAPSHOT_SIZE, $crate::snapshot::DEFAULT_SNAPSHOT_SIZE)
+    };
+    ($mesh:expr, $proj:expr, $path:expr, $w:expr, $h:expr) => {
+        if let Err(e) = $crate::snapshot::check_render(&$mesh[..], &$proj, &::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join($path), $w, $h) {
+            panic!("{}", e);
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// an empty directory of its own for each test
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("glow_mesh_snapshot_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn image(w: usize, h: usize, px: [u8; 4]) -> ImageBuffer {
+        let mut img = ImageBuffer::new(w, h);
+        for p in img.data.chunks_exact_mut(4) {
+            p.copy_from_slice(&px);
+        }
+        img
+    }
+
+    #[test]
+    fn stats_and_diff_image() {
+        let expected = image(2, 1, [100, 100, 100, 255]);
+        let mut actual = image(2, 1, [100, 100, 100, 255]);
+        actual.data[4] = 110;
+        actual.data[7] = 250;
+        let stats = diff_stats(&expected, &actual);
+        assert_eq!(stats, DiffStats { differing_pixels: 1, total_pixels: 2, max: [10, 0, 0, 5], mean: [5.0, 0.0, 0.0, 2.5] });
+        assert!(stats.to_string().starts_with("1/2 pixels differ\n  r: max 10 mean 5.000\n"));
+        assert_eq!(diff_stats(&expected, &expected).differing_pixels, 0);
+
+        let diff = diff_image(&expected, &actual);
+        assert_eq!(diff.data[4..8], [133, 0, 0, 255]);
+        // grey, not red
+        let same = &diff.data[0..4];
+        assert!(same[0] == same[1] && same[1] == same[2] && same[3] == 255);
+    }
+
+    #[test]
+    fn matching_snapshot() {
+        let dir = temp_dir("matching");
+        let path = dir.join("a.png");
+        let img = image(4, 4, [255, 0, 0, 255]);
+        save_png(&img, &path).unwrap();
+        // left over from a failure before
+        save_png(&img, dir.join("a.actual.png")).unwrap();
+        save_png(&img, dir.join("a.diff.png")).unwrap();
+        assert_eq!(check_or_bless(&img, &path, false), Ok(()));
+        assert!(!dir.join("a.actual.png").exists());
+        assert!(!dir.join("a.diff.png").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn mismatch_writes_render_and_diff() {
+        let dir = temp_dir("mismatch");
+        let path = dir.join("a.png");
+        let expected = image(4, 4, [255, 0, 0, 255]);
+        save_png(&expected, &path).unwrap();
+        let mut actual = image(4, 4, [255, 0, 0, 255]);
+        actual.data[1] = 200;
+
+        let message = check_or_bless(&actual, &path, false).unwrap_err();
+        assert!(message.contains("1/16 pixels differ"), "{}", message);
+        assert!(message.contains("g: max 200"), "{}", message);
+        assert_eq!(load_png(dir.join("a.actual.png")).unwrap().data, actual.data);
+        assert_eq!(load_png(dir.join("a.diff.png")).unwrap().data, diff_image(&expected, &actual).data);
+        // the snapshot itself is left alone
+        assert_eq!(load_png(&path).unwrap().data, expected.data);
+
+        // a different size only writes the render
+        let _ = std::fs::remove_file(dir.join("a.diff.png"));
+        let message = check_or_bless(&image(2, 2, [0; 4]), &path, false).unwrap_err();
+        assert!(message.contains("is 4x4 but the render is 2x2"), "{}", message);
+        assert_eq!(load_png(dir.join("a.actual.png")).unwrap().w, 2);
+        assert!(!dir.join("a.diff.png").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn mismatch_panics_with_stats() {
+        if blessing() {
+            // blessing takes whatever is rendered
+            return;
+        }
+        let dir = temp_dir("panics");
+        let path = dir.join("empty.png");
+        save_png(&image(8, 8, [0, 0, 0, 0]), &path).unwrap();
+        let mut buf = vec![];
+        crate::xyzrgba::put_rect(&mut buf, minvect::rect(-1.0, -1.0, 2.0, 2.0), minvect::vec4(1.0, 1.0, 1.0, 1.0), 0.0);
+        let proj = [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0];
+        let panic = std::panic::catch_unwind(|| {
+            crate::assert_render_matches!(buf, proj, &path, 8, 8);
+        }).unwrap_err();
+        let message = panic.downcast_ref::<String>().unwrap();
+        assert!(message.contains("64/64 pixels differ"), "{}", message);
+        assert!(dir.join("empty.actual.png").exists());
+        assert!(dir.join("empty.diff.png").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn bless_writes_missing_snapshot() {
+        let dir = temp_dir("bless");
+        let img = image(3, 2, [1, 2, 3, 4]);
+        let message = check_or_bless(&img, &dir.join("a.png"), false).unwrap_err();
+        assert!(message.contains("no snapshot at"), "{}", message);
+        assert!(!dir.join("a.png").exists());
+        assert!(dir.join("a.actual.png").exists());
+
+        // makes the directory too
+        let path = dir.join("new").join("a.png");
+        assert_eq!(check_or_bless(&img, &path, true), Ok(()));
+        assert_eq!(load_png(&path).unwrap().data, img.data);
+        // and it matches after
+        assert_eq!(check_or_bless(&img, &path, false), Ok(()));
+        // blessing over a failure clears out its images
+        save_png(&img, dir.join("a.diff.png")).unwrap();
+        assert_eq!(check_or_bless(&img, &dir.join("a.png"), true), Ok(()));
+        assert!(!dir.join("a.actual.png").exists());
+        assert!(!dir.join("a.diff.png").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
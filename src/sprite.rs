@@ -0,0 +1,251 @@
+//! Sprites with source rects in pixels instead of normalized uvs. Consecutive sprites from the same texture
+//! go out in one draw call; the batch flushes by itself when the texture changes or it's full.
+//! ```ignore
+//! let mut batch = SpriteBatch::new(&gl, 1000)?;
+//! batch.set_proj(&proj, &gl);
+//! batch.draw(&gl, &texture, &Sprite::new(rect(10.0, 10.0, 32.0, 32.0), rect(0.0, 0.0, 16.0, 16.0)));
+//! batch.flush(&gl);
+//! ```
+//! Source rect y goes down the image, so with a y down projection dest.xy is the top left of the sprite.
+
+use glow::HasContext;
+use minvect::*;
+use crate::error::*;
+use crate::gc::*;
+use crate::mesh::*;
+use crate::texture::*;
+use crate::xyzrgbauv::*;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Sprite {
+    /// where it goes, in whatever units the projection uses
+    pub dest: Rect,
+    /// what part of the texture, in pixels
+    pub src: Rect,
+    pub tint: Vec4,
+    /// radians, around origin
+    pub rotation: f32,
+    /// pivot for rotation, relative to dest.xy
+    pub origin: Vec2,
+    pub depth: f32,
+}
+
+impl Sprite {
+    /// untinted, unrotated, depth 0
+    pub fn new(dest: Rect, src: Rect) -> Self {
+        Sprite { dest, src, tint: vec4(1.0, 1.0, 1.0, 1.0), rotation: 0.0, origin: vec2(0.0, 0.0), depth: 0.0 }
+    }
+    pub fn with_tint(self, tint: Vec4) -> Self {
+        Sprite { tint, ..self }
+    }
+    pub fn with_rotation(self, rotation: f32, origin: Vec2) -> Self {
+        Sprite { rotation, origin, ..self }
+    }
+    pub fn with_depth(self, depth: f32) -> Self {
+        Sprite { depth, ..self }
+    }
+}
+
+/// append the sprite's quad, uvs computed from src and a tex_w x tex_h texture
+pub fn put_sprite(buf: &mut Vec<XYZRGBAUV>, sprite: &Sprite, tex_w: usize, tex_h: usize) {
+    let scale = vec2(1.0 / tex_w as f32, 1.0 / tex_h as f32);
+    let uv = rect(sprite.src.xy.x * scale.x, sprite.src.xy.y * scale.y, sprite.src.wh.x * scale.x, sprite.src.wh.y * scale.y);
+    if sprite.rotation == 0.0 {
+        put_rect(buf, sprite.dest, uv, sprite.tint, sprite.depth);
+        return;
+    }
+    let pivot = sprite.dest.xy + sprite.origin;
+    let (s, c) = sprite.rotation.sin_cos();
+    let rotate = |p: Vec2| {
+        let d = p - pivot;
+        pivot + vec2(d.x * c - d.y * s, d.x * s + d.y * c)
+    };
+    let r = sprite.dest;
+    put_quad(buf, rotate(r.tl()), rotate(r.tr()), rotate(r.br()), rotate(r.bl()), sprite.tint, uv.tl(), uv.br(), sprite.depth);
+}
+
+pub struct SpriteBatch<G: HasContext = glow::Context> {
+    /// ProgramXYZRGBAUV::default unless replaced with one taking the same inputs
+    pub program: ProgramXYZRGBAUV<G>,
+    mesh: DynamicMesh<XYZRGBAUV, G>,
+    buf: Vec<XYZRGBAUV>,
+    /// texture the queued sprites use
+    texture: Option<G::Texture>,
+    max_sprites: usize,
+    /// draw calls made so far, reset it whenever
+    pub draw_calls: usize,
+}
+
+impl<G: HasContext> SpriteBatch<G> {
+    /// flushes every max_sprites sprites at most
+    ///
+    /// # Safety
+    /// gl has to be current
+    pub unsafe fn new(gl: &G, max_sprites: usize) -> Result<Self, GlowMeshError> {
+        let max_sprites = max_sprites.max(1);
+        let program = ProgramXYZRGBAUV::default(gl)?;
+        let mesh = match DynamicMesh::with_capacity(max_sprites * 6, Usage::Stream, gl) {
+            Ok(mesh) => mesh,
+            Err(e) => {
+                program.free(gl);
+                return Err(e);
+            },
+        };
+        Ok(SpriteBatch {
+            program,
+            mesh,
+            buf: Vec::with_capacity(max_sprites * 6),
+            texture: None,
+            max_sprites,
+            draw_calls: 0,
+        })
+    }
+    /// # Safety
+    /// gl has to be the context the batch was made on, and current. Leaves the batch's program bound
+    pub unsafe fn set_proj(&self, proj: &[f32; 16], gl: &G) {
+        self.program.bind(gl);
+        self.program.set_proj(proj, gl);
+    }
+    /// queue a sprite, flushing first if it needs a different texture or the batch is full
+    ///
+    /// # Safety
+    /// gl has to be the context the batch was made on, and current, and texture has to belong to it too. It has to stay alive until the batch is flushed
+    pub unsafe fn draw(&mut self, gl: &G, texture: &Texture<G>, sprite: &Sprite) {
+        if self.texture != Some(texture.texture) || self.buf.len() + 6 > self.max_sprites * 6 {
+            self.flush(gl);
+            self.texture = Some(texture.texture);
+        }
+        put_sprite(&mut self.buf, sprite, texture.w, texture.h);
+    }
+    /// sprites queued but not drawn yet
+    pub fn len(&self) -> usize {
+        self.buf.len() / 6
+    }
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+    /// draw whatever is queued. Call at the end of the frame or before drawing something else on top
+    ///
+    /// # Safety
+    /// gl has to be the context the batch was made on, and current, and the queued sprites' texture still has to exist
+    pub unsafe fn flush(&mut self, gl: &G) {
+        let texture = match self.texture {
+            Some(texture) if !self.buf.is_empty() => texture,
+            _ => return,
+        };
+        self.program.bind(gl);
+        self.program.bind_native_texture(texture, gl);
+        self.mesh.update(&self.buf, gl);
+        self.mesh.render(gl);
+        self.buf.clear();
+        self.draw_calls += 1;
+    }
+    pub fn free(&self, gl: &G) {
+        self.program.free(gl);
+        self.mesh.free(gl);
+    }
+}
+
+impl<G: HasContext> Free<G> for SpriteBatch<G> {
+    fn free(&self, gl: &G) {
+        SpriteBatch::free(self, gl)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn failed_creates_leak_nothing() {
+        // vertex shader, fragment shader, program, vbo, vao
+        for n in 0..5 {
+            let gl = RecordingContext::new();
+            gl.fail_nth_create(n);
+            assert!(matches!(unsafe { SpriteBatch::new(&gl, 16) }, Err(GlowMeshError::ResourceCreation(_))));
+            assert!(gl.live_objects().is_empty(), "failing create {} leaked {:?}", n, gl.live_objects());
+        }
+        let gl = RecordingContext::new();
+        let batch = unsafe { SpriteBatch::new(&gl, 16) }.unwrap();
+        assert_eq!(gl.calls_named("create_buffer").len(), 1);
+        assert_eq!(gl.live_objects().len(), 3);
+        batch.free(&gl);
+        assert!(gl.live_objects().is_empty());
+    }
+
+    #[test]
+    fn zero_max_sprites_is_one() {
+        let gl = RecordingContext::new();
+        let batch = unsafe { SpriteBatch::new(&gl, 0) }.unwrap();
+        assert_eq!(batch.max_sprites, 1);
+        let upload = gl.last_call("buffer_data_size").unwrap();
+        assert_eq!(upload.args[1], Arg::I32(6 * std::mem::size_of::<XYZRGBAUV>() as i32));
+        batch.free(&gl);
+    }
+
+    /// (texture bound, vertex count) for each draw so far
+    fn draws(gl: &RecordingContext) -> Vec<(Option<u32>, i32)> {
+        let mut bound = None;
+        let mut draws = vec![];
+        for call in gl.calls() {
+            match (call.name, &call.args[..]) {
+                ("bind_texture", [_, Arg::Name(texture)]) => bound = *texture,
+                ("draw_arrays", [_, _, Arg::I32(count)]) => draws.push((bound, *count)),
+                _ => {},
+            }
+        }
+        draws
+    }
+
+    #[test]
+    fn texture_changes_flush() {
+        let gl = RecordingContext::new();
+        let mut batch = unsafe { SpriteBatch::new(&gl, 16) }.unwrap();
+        let a = unsafe { Texture::empty(&gl, 16, 16, &SamplerDesc::nearest()) }.unwrap();
+        let b = unsafe { Texture::empty(&gl, 16, 16, &SamplerDesc::nearest()) }.unwrap();
+        let sprite = Sprite::new(rect(0.0, 0.0, 8.0, 8.0), rect(0.0, 0.0, 8.0, 8.0));
+        unsafe {
+            batch.draw(&gl, &a, &sprite);
+            batch.draw(&gl, &a, &sprite);
+            assert!(draws(&gl).is_empty());
+            batch.draw(&gl, &b, &sprite);
+            assert_eq!(draws(&gl), vec![(Some(a.texture), 12)]);
+            batch.draw(&gl, &a, &sprite);
+            assert_eq!(draws(&gl), vec![(Some(a.texture), 12), (Some(b.texture), 6)]);
+            assert_eq!(batch.len(), 1);
+            batch.flush(&gl);
+            batch.flush(&gl);
+        }
+        assert_eq!(draws(&gl), vec![(Some(a.texture), 12), (Some(b.texture), 6), (Some(a.texture), 6)]);
+        assert_eq!(batch.draw_calls, 3);
+        assert!(batch.is_empty());
+        batch.free(&gl);
+        a.free(&gl);
+        b.free(&gl);
+    }
+
+    #[test]
+    fn full_batch_flushes() {
+        let gl = RecordingContext::new();
+        let mut batch = unsafe { SpriteBatch::new(&gl, 3) }.unwrap();
+        let texture = unsafe { Texture::empty(&gl, 16, 16, &SamplerDesc::nearest()) }.unwrap();
+        let sprite = Sprite::new(rect(0.0, 0.0, 8.0, 8.0), rect(0.0, 0.0, 8.0, 8.0));
+        for _ in 0..7 {
+            unsafe { batch.draw(&gl, &texture, &sprite) };
+        }
+        // the 4th and 7th sprites didnt fit
+        assert_eq!(draws(&gl), vec![(Some(texture.texture), 18); 2]);
+        assert_eq!(batch.draw_calls, 2);
+        assert_eq!(batch.len(), 1);
+        unsafe { batch.flush(&gl) };
+        assert_eq!(draws(&gl).last(), Some(&(Some(texture.texture), 6)));
+        assert_eq!(batch.draw_calls, 3);
+        // never more than fits in the buffer
+        let uploads = gl.calls_named("buffer_sub_data_u8_slice");
+        assert_eq!(uploads.len(), 3);
+        assert!(uploads.iter().all(|c| matches!(&c.args[2], Arg::Bytes(b) if b.len() <= 18 * std::mem::size_of::<XYZRGBAUV>())));
+        batch.free(&gl);
+        texture.free(&gl);
+    }
+}
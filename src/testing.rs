@@ -0,0 +1,1327 @@
+//! A fake gl context for testing without a gpu, behind the `testing` feature.
+//! RecordingContext implements glow::HasContext by writing down every call and its arguments
+//! instead of doing anything. Everything in the crate is generic over HasContext so it can be
+//! pointed at one of these and the calls asserted on:
+//! ```
+//! use glow_mesh::testing::*;
+//! use glow_mesh::xyzrgba::*;
+//! use minvect::*;
+//!
+//! let gl = RecordingContext::new();
+//! let mut buf = vec![];
+//! put_rect(&mut buf, rect(0.0, 0.0, 1.0, 1.0), vec4(1.0, 1.0, 1.0, 1.0), 0.0);
+//! let h = unsafe { upload_xyzrgba_mesh(&buf, &gl) }.unwrap();
+//! unsafe { h.render(&gl) };
+//! h.free(&gl);
+//! let draw = gl.last_call("draw_arrays").unwrap();
+//! assert_eq!(draw.args, vec![Arg::U32(glow::TRIANGLES), Arg::I32(0), Arg::I32(6)]);
+//! ```
+//!
+//! Object names are handed out counting up from 1. Uniform locations are just the uniform's name.
+//! live_objects lists what has been created and not deleted, and fail_nth_create makes a create_* call fail
+//! to test the error paths.
+//! Queries return zeroes / empty except for the few things that need to succeed:
+//! shader compile + link status (see set_compile_status), framebuffer completeness, and the parameters set with set_parameter.
+
+use glow::*;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+
+/// one argument of a recorded call
+#[derive(Debug, Clone, PartialEq)]
+pub enum Arg {
+    U32(u32),
+    I32(i32),
+    U64(u64),
+    F32(f32),
+    F64(f64),
+    Bool(bool),
+    Str(String),
+    Strs(Vec<String>),
+    Bytes(Vec<u8>),
+    U32s(Vec<u32>),
+    I32s(Vec<i32>),
+    F32s(Vec<f32>),
+    F64s(Vec<f64>),
+    /// an object name (buffer, texture, program...) or None for unbinding
+    Name(Option<u32>),
+    Uniform(Option<String>),
+    /// length of an output slice, the contents arent recorded
+    Len(usize),
+    /// a buffer offset given instead of pixel data
+    Offset(u32),
+    None,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Call {
+    pub name: &'static str,
+    pub args: Vec<Arg>,
+}
+
+pub struct RecordingContext {
+    calls: RefCell<Vec<Call>>,
+    next_name: Cell<u32>,
+    live: RefCell<HashSet<u32>>,
+    fail_create: Cell<Option<usize>>,
+    compile_status: Cell<bool>,
+    link_status: Cell<bool>,
+    info_log: RefCell<String>,
+    parameters: RefCell<HashMap<u32, i32>>,
+    extensions: HashSet<String>,
+    version: Version,
+}
+
+impl Default for RecordingContext {
+    fn default() -> Self {
+        RecordingContext {
+            calls: RefCell::new(vec![]),
+            next_name: Cell::new(1),
+            live: RefCell::new(HashSet::new()),
+            fail_create: Cell::new(None),
+            compile_status: Cell::new(true),
+            link_status: Cell::new(true),
+            info_log: RefCell::new(String::new()),
+            parameters: RefCell::new(HashMap::new()),
+            extensions: HashSet::new(),
+            version: Version { major: 3, minor: 3, is_embedded: false, revision: None, vendor_info: "glow_mesh recording context".to_string() },
+        }
+    }
+}
+
+impl RecordingContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// every call so far, in order
+    pub fn calls(&self) -> Vec<Call> {
+        self.calls.borrow().clone()
+    }
+    /// every call so far, and forget them
+    pub fn take_calls(&self) -> Vec<Call> {
+        std::mem::take(&mut *self.calls.borrow_mut())
+    }
+    pub fn calls_named(&self, name: &str) -> Vec<Call> {
+        self.calls.borrow().iter().filter(|c| c.name == name).cloned().collect()
+    }
+    pub fn last_call(&self, name: &str) -> Option<Call> {
+        self.calls.borrow().iter().rev().find(|c| c.name == name).cloned()
+    }
+    pub fn clear(&self) {
+        self.calls.borrow_mut().clear();
+    }
+    /// what shader compile and program link status report from now on, and the info log they give.
+    /// Defaults to everything compiling.
+    pub fn set_compile_status(&self, compiles: bool, links: bool, info_log: &str) {
+        self.compile_status.set(compiles);
+        self.link_status.set(links);
+        *self.info_log.borrow_mut() = info_log.to_string();
+    }
+    /// what get_parameter_i32/f32 returns for parameter
+    pub fn set_parameter(&self, parameter: u32, value: i32) {
+        self.parameters.borrow_mut().insert(parameter, value);
+    }
+    pub fn add_extension(&mut self, extension: &str) {
+        self.extensions.insert(extension.to_string());
+    }
+    /// make the nth create_* call from now fail, 0 being the next one. Only fails once.
+    /// The failing call is still recorded.
+    pub fn fail_nth_create(&self, n: usize) {
+        self.fail_create.set(Some(n));
+    }
+    /// names of everything created and not deleted yet, in order. Empty if nothing leaked.
+    pub fn live_objects(&self) -> Vec<u32> {
+        let mut live: Vec<u32> = self.live.borrow().iter().copied().collect();
+        live.sort_unstable();
+        live
+    }
+    fn record(&self, name: &'static str, args: Vec<Arg>) {
+        self.calls.borrow_mut().push(Call { name, args });
+    }
+    fn create(&self) -> Result<u32, String> {
+        match self.fail_create.get() {
+            Some(0) => {
+                self.fail_create.set(None);
+                return Err("create failed (RecordingContext::fail_nth_create)".to_string());
+            },
+            Some(n) => self.fail_create.set(Some(n - 1)),
+            None => {},
+        }
+        let name = self.next_name.get();
+        self.next_name.set(name + 1);
+        self.live.borrow_mut().insert(name);
+        Ok(name)
+    }
+    fn delete(&self, name: u32) {
+        self.live.borrow_mut().remove(&name);
+    }
+}
+
+fn pack_arg(data: &PixelPackData) -> Arg {
+    match data {
+        PixelPackData::BufferOffset(offset) => Arg::Offset(*offset),
+        PixelPackData::Slice(slice) => Arg::Len(slice.len()),
+    }
+}
+
+fn unpack_arg(data: &PixelUnpackData) -> Arg {
+    match data {
+        PixelUnpackData::BufferOffset(offset) => Arg::Offset(*offset),
+        PixelUnpackData::Slice(slice) => Arg::Bytes(slice.to_vec()),
+    }
+}
+
+fn compressed_arg(data: &CompressedPixelUnpackData) -> Arg {
+    match data {
+        CompressedPixelUnpackData::BufferRange(range) => Arg::U32s(vec![range.start, range.end]),
+        CompressedPixelUnpackData::Slice(slice) => Arg::Bytes(slice.to_vec()),
+    }
+}
+
+impl HasContext for RecordingContext {
+    type Shader = u32;
+    type Program = u32;
+    type Buffer = u32;
+    type VertexArray = u32;
+    type Texture = u32;
+    type Sampler = u32;
+    type Fence = u32;
+    type Framebuffer = u32;
+    type Renderbuffer = u32;
+    type Query = u32;
+    type TransformFeedback = u32;
+    type UniformLocation = String;
+
+    fn supported_extensions(&self) -> &HashSet<String> {
+        &self.extensions
+    }
+
+    fn supports_debug(&self) -> bool {
+        false
+    }
+
+    fn version(&self) -> &Version {
+        &self.version
+    }
+
+    unsafe fn create_framebuffer(&self) -> Result<Self::Framebuffer, String> {
+        self.record("create_framebuffer", vec![]);
+        self.create()
+    }
+
+    unsafe fn is_framebuffer(&self, framebuffer: Self::Framebuffer) -> bool {
+        self.record("is_framebuffer", vec![Arg::Name(Some(framebuffer))]);
+        false
+    }
+
+    unsafe fn create_query(&self) -> Result<Self::Query, String> {
+        self.record("create_query", vec![]);
+        self.create()
+    }
+
+    unsafe fn create_renderbuffer(&self) -> Result<Self::Renderbuffer, String> {
+        self.record("create_renderbuffer", vec![]);
+        self.create()
+    }
+
+    unsafe fn is_renderbuffer(&self, renderbuffer: Self::Renderbuffer) -> bool {
+        self.record("is_renderbuffer", vec![Arg::Name(Some(renderbuffer))]);
+        false
+    }
+
+    unsafe fn create_sampler(&self) -> Result<Self::Sampler, String> {
+        self.record("create_sampler", vec![]);
+        self.create()
+    }
+
+    unsafe fn create_shader(&self, shader_type: u32) -> Result<Self::Shader, String> {
+        self.record("create_shader", vec![Arg::U32(shader_type)]);
+        self.create()
+    }
+
+    unsafe fn is_shader(&self, shader: Self::Shader) -> bool {
+        self.record("is_shader", vec![Arg::Name(Some(shader))]);
+        false
+    }
+
+    unsafe fn create_texture(&self) -> Result<Self::Texture, String> {
+        self.record("create_texture", vec![]);
+        self.create()
+    }
+
+    unsafe fn is_texture(&self, texture: Self::Texture) -> bool {
+        self.record("is_texture", vec![Arg::Name(Some(texture))]);
+        false
+    }
+
+    unsafe fn delete_shader(&self, shader: Self::Shader) {
+        self.record("delete_shader", vec![Arg::Name(Some(shader))]);
+        self.delete(shader);
+    }
+
+    unsafe fn shader_source(&self, shader: Self::Shader, source: &str) {
+        self.record("shader_source", vec![Arg::Name(Some(shader)), Arg::Str(source.to_string())]);
+    }
+
+    unsafe fn compile_shader(&self, shader: Self::Shader) {
+        self.record("compile_shader", vec![Arg::Name(Some(shader))]);
+    }
+
+    unsafe fn get_shader_compile_status(&self, shader: Self::Shader) -> bool {
+        self.record("get_shader_compile_status", vec![Arg::Name(Some(shader))]);
+        self.compile_status.get()
+    }
+
+    unsafe fn get_shader_info_log(&self, shader: Self::Shader) -> String {
+        self.record("get_shader_info_log", vec![Arg::Name(Some(shader))]);
+        self.info_log.borrow().clone()
+    }
+
+    unsafe fn get_tex_image(&self, target: u32, level: i32, format: u32, ty: u32, pixels: PixelPackData) {
+        self.record("get_tex_image", vec![Arg::U32(target), Arg::I32(level), Arg::U32(format), Arg::U32(ty), pack_arg(&pixels)]);
+    }
+
+    unsafe fn create_program(&self) -> Result<Self::Program, String> {
+        self.record("create_program", vec![]);
+        self.create()
+    }
+
+    unsafe fn is_program(&self, program: Self::Program) -> bool {
+        self.record("is_program", vec![Arg::Name(Some(program))]);
+        false
+    }
+
+    unsafe fn delete_program(&self, program: Self::Program) {
+        self.record("delete_program", vec![Arg::Name(Some(program))]);
+        self.delete(program);
+    }
+
+    unsafe fn attach_shader(&self, program: Self::Program, shader: Self::Shader) {
+        self.record("attach_shader", vec![Arg::Name(Some(program)), Arg::Name(Some(shader))]);
+    }
+
+    unsafe fn detach_shader(&self, program: Self::Program, shader: Self::Shader) {
+        self.record("detach_shader", vec![Arg::Name(Some(program)), Arg::Name(Some(shader))]);
+    }
+
+    unsafe fn link_program(&self, program: Self::Program) {
+        self.record("link_program", vec![Arg::Name(Some(program))]);
+    }
+
+    unsafe fn get_program_link_status(&self, program: Self::Program) -> bool {
+        self.record("get_program_link_status", vec![Arg::Name(Some(program))]);
+        self.link_status.get()
+    }
+
+    unsafe fn get_program_info_log(&self, program: Self::Program) -> String {
+        self.record("get_program_info_log", vec![Arg::Name(Some(program))]);
+        self.info_log.borrow().clone()
+    }
+
+    unsafe fn get_active_uniforms(&self, program: Self::Program) -> u32 {
+        self.record("get_active_uniforms", vec![Arg::Name(Some(program))]);
+        0
+    }
+
+    unsafe fn get_active_uniform(&self, program: Self::Program, index: u32) -> Option<ActiveUniform> {
+        self.record("get_active_uniform", vec![Arg::Name(Some(program)), Arg::U32(index)]);
+        None
+    }
+
+    unsafe fn use_program(&self, program: Option<Self::Program>) {
+        self.record("use_program", vec![Arg::Name(program)]);
+    }
+
+    unsafe fn create_buffer(&self) -> Result<Self::Buffer, String> {
+        self.record("create_buffer", vec![]);
+        self.create()
+    }
+
+    unsafe fn is_buffer(&self, buffer: Self::Buffer) -> bool {
+        self.record("is_buffer", vec![Arg::Name(Some(buffer))]);
+        false
+    }
+
+    unsafe fn bind_buffer(&self, target: u32, buffer: Option<Self::Buffer>) {
+        self.record("bind_buffer", vec![Arg::U32(target), Arg::Name(buffer)]);
+    }
+
+    unsafe fn bind_buffer_base(&self, target: u32, index: u32, buffer: Option<Self::Buffer>) {
+        self.record("bind_buffer_base", vec![Arg::U32(target), Arg::U32(index), Arg::Name(buffer)]);
+    }
+
+    unsafe fn bind_buffer_range(&self, target: u32, index: u32, buffer: Option<Self::Buffer>, offset: i32, size: i32) {
+        self.record("bind_buffer_range", vec![Arg::U32(target), Arg::U32(index), Arg::Name(buffer), Arg::I32(offset), Arg::I32(size)]);
+    }
+
+    // glow types this one as the native glow::Buffer rather than Self::Buffer, so it cant be recorded usefully
+    unsafe fn bind_vertex_buffer(&self, binding_index: u32, _buffer: Option<Buffer>, offset: i32, stride: i32) {
+        self.record("bind_vertex_buffer", vec![Arg::U32(binding_index), Arg::None, Arg::I32(offset), Arg::I32(stride)]);
+    }
+
+    unsafe fn bind_framebuffer(&self, target: u32, framebuffer: Option<Self::Framebuffer>) {
+        self.record("bind_framebuffer", vec![Arg::U32(target), Arg::Name(framebuffer)]);
+    }
+
+    unsafe fn bind_renderbuffer(&self, target: u32, renderbuffer: Option<Self::Renderbuffer>) {
+        self.record("bind_renderbuffer", vec![Arg::U32(target), Arg::Name(renderbuffer)]);
+    }
+
+    unsafe fn blit_framebuffer(&self, src_x0: i32, src_y0: i32, src_x1: i32, src_y1: i32, dst_x0: i32, dst_y0: i32, dst_x1: i32, dst_y1: i32, mask: u32, filter: u32) {
+        self.record("blit_framebuffer", vec![Arg::I32(src_x0), Arg::I32(src_y0), Arg::I32(src_x1), Arg::I32(src_y1), Arg::I32(dst_x0), Arg::I32(dst_y0), Arg::I32(dst_x1), Arg::I32(dst_y1), Arg::U32(mask), Arg::U32(filter)]);
+    }
+
+    unsafe fn create_vertex_array(&self) -> Result<Self::VertexArray, String> {
+        self.record("create_vertex_array", vec![]);
+        self.create()
+    }
+
+    unsafe fn delete_vertex_array(&self, vertex_array: Self::VertexArray) {
+        self.record("delete_vertex_array", vec![Arg::Name(Some(vertex_array))]);
+        self.delete(vertex_array);
+    }
+
+    unsafe fn bind_vertex_array(&self, vertex_array: Option<Self::VertexArray>) {
+        self.record("bind_vertex_array", vec![Arg::Name(vertex_array)]);
+    }
+
+    unsafe fn clear_color(&self, red: f32, green: f32, blue: f32, alpha: f32) {
+        self.record("clear_color", vec![Arg::F32(red), Arg::F32(green), Arg::F32(blue), Arg::F32(alpha)]);
+    }
+
+    unsafe fn supports_f64_precision() -> bool {
+        false
+    }
+
+    unsafe fn clear_depth_f64(&self, depth: f64) {
+        self.record("clear_depth_f64", vec![Arg::F64(depth)]);
+    }
+
+    unsafe fn clear_depth_f32(&self, depth: f32) {
+        self.record("clear_depth_f32", vec![Arg::F32(depth)]);
+    }
+
+    unsafe fn clear_stencil(&self, stencil: i32) {
+        self.record("clear_stencil", vec![Arg::I32(stencil)]);
+    }
+
+    unsafe fn clear(&self, mask: u32) {
+        self.record("clear", vec![Arg::U32(mask)]);
+    }
+
+    unsafe fn patch_parameter_i32(&self, parameter: u32, value: i32) {
+        self.record("patch_parameter_i32", vec![Arg::U32(parameter), Arg::I32(value)]);
+    }
+
+    unsafe fn pixel_store_i32(&self, parameter: u32, value: i32) {
+        self.record("pixel_store_i32", vec![Arg::U32(parameter), Arg::I32(value)]);
+    }
+
+    unsafe fn pixel_store_bool(&self, parameter: u32, value: bool) {
+        self.record("pixel_store_bool", vec![Arg::U32(parameter), Arg::Bool(value)]);
+    }
+
+    unsafe fn bind_frag_data_location(&self, program: Self::Program, color_number: u32, name: &str) {
+        self.record("bind_frag_data_location", vec![Arg::Name(Some(program)), Arg::U32(color_number), Arg::Str(name.to_string())]);
+    }
+
+    unsafe fn buffer_data_size(&self, target: u32, size: i32, usage: u32) {
+        self.record("buffer_data_size", vec![Arg::U32(target), Arg::I32(size), Arg::U32(usage)]);
+    }
+
+    unsafe fn buffer_data_u8_slice(&self, target: u32, data: &[u8], usage: u32) {
+        self.record("buffer_data_u8_slice", vec![Arg::U32(target), Arg::Bytes(data.to_vec()), Arg::U32(usage)]);
+    }
+
+    unsafe fn buffer_sub_data_u8_slice(&self, target: u32, offset: i32, src_data: &[u8]) {
+        self.record("buffer_sub_data_u8_slice", vec![Arg::U32(target), Arg::I32(offset), Arg::Bytes(src_data.to_vec())]);
+    }
+
+    unsafe fn get_buffer_sub_data(&self, target: u32, offset: i32, dst_data: &mut [u8]) {
+        self.record("get_buffer_sub_data", vec![Arg::U32(target), Arg::I32(offset), Arg::Len(dst_data.len())]);
+    }
+
+    unsafe fn buffer_storage(&self, target: u32, size: i32, data: Option<&[u8]>, flags: u32) {
+        self.record("buffer_storage", vec![Arg::U32(target), Arg::I32(size), data.map_or(Arg::None, |d| Arg::Bytes(d.to_vec())), Arg::U32(flags)]);
+    }
+
+    unsafe fn check_framebuffer_status(&self, target: u32) -> u32 {
+        self.record("check_framebuffer_status", vec![Arg::U32(target)]);
+        glow::FRAMEBUFFER_COMPLETE
+    }
+
+    unsafe fn clear_buffer_i32_slice(&self, target: u32, draw_buffer: u32, values: &[i32]) {
+        self.record("clear_buffer_i32_slice", vec![Arg::U32(target), Arg::U32(draw_buffer), Arg::I32s(values.to_vec())]);
+    }
+
+    unsafe fn clear_buffer_u32_slice(&self, target: u32, draw_buffer: u32, values: &[u32]) {
+        self.record("clear_buffer_u32_slice", vec![Arg::U32(target), Arg::U32(draw_buffer), Arg::U32s(values.to_vec())]);
+    }
+
+    unsafe fn clear_buffer_f32_slice(&self, target: u32, draw_buffer: u32, values: &[f32]) {
+        self.record("clear_buffer_f32_slice", vec![Arg::U32(target), Arg::U32(draw_buffer), Arg::F32s(values.to_vec())]);
+    }
+
+    unsafe fn clear_buffer_depth_stencil(&self, target: u32, draw_buffer: u32, depth: f32, stencil: i32) {
+        self.record("clear_buffer_depth_stencil", vec![Arg::U32(target), Arg::U32(draw_buffer), Arg::F32(depth), Arg::I32(stencil)]);
+    }
+
+    unsafe fn client_wait_sync(&self, fence: Self::Fence, flags: u32, timeout: i32) -> u32 {
+        self.record("client_wait_sync", vec![Arg::Name(Some(fence)), Arg::U32(flags), Arg::I32(timeout)]);
+        0
+    }
+
+    unsafe fn wait_sync(&self, fence: Self::Fence, flags: u32, timeout: u64) {
+        self.record("wait_sync", vec![Arg::Name(Some(fence)), Arg::U32(flags), Arg::U64(timeout)]);
+    }
+
+    unsafe fn copy_buffer_sub_data(&self, src_target: u32, dst_target: u32, src_offset: i32, dst_offset: i32, size: i32) {
+        self.record("copy_buffer_sub_data", vec![Arg::U32(src_target), Arg::U32(dst_target), Arg::I32(src_offset), Arg::I32(dst_offset), Arg::I32(size)]);
+    }
+
+    unsafe fn copy_tex_image_2d(&self, target: u32, level: i32, internal_format: u32, x: i32, y: i32, width: i32, height: i32, border: i32) {
+        self.record("copy_tex_image_2d", vec![Arg::U32(target), Arg::I32(level), Arg::U32(internal_format), Arg::I32(x), Arg::I32(y), Arg::I32(width), Arg::I32(height), Arg::I32(border)]);
+    }
+
+    unsafe fn copy_tex_sub_image_2d(&self, target: u32, level: i32, x_offset: i32, y_offset: i32, x: i32, y: i32, width: i32, height: i32) {
+        self.record("copy_tex_sub_image_2d", vec![Arg::U32(target), Arg::I32(level), Arg::I32(x_offset), Arg::I32(y_offset), Arg::I32(x), Arg::I32(y), Arg::I32(width), Arg::I32(height)]);
+    }
+
+    unsafe fn copy_tex_sub_image_3d(&self, target: u32, level: i32, x_offset: i32, y_offset: i32, z_offset: i32, x: i32, y: i32, width: i32, height: i32) {
+        self.record("copy_tex_sub_image_3d", vec![Arg::U32(target), Arg::I32(level), Arg::I32(x_offset), Arg::I32(y_offset), Arg::I32(z_offset), Arg::I32(x), Arg::I32(y), Arg::I32(width), Arg::I32(height)]);
+    }
+
+    unsafe fn delete_buffer(&self, buffer: Self::Buffer) {
+        self.record("delete_buffer", vec![Arg::Name(Some(buffer))]);
+        self.delete(buffer);
+    }
+
+    unsafe fn delete_framebuffer(&self, framebuffer: Self::Framebuffer) {
+        self.record("delete_framebuffer", vec![Arg::Name(Some(framebuffer))]);
+        self.delete(framebuffer);
+    }
+
+    unsafe fn delete_query(&self, query: Self::Query) {
+        self.record("delete_query", vec![Arg::Name(Some(query))]);
+        self.delete(query);
+    }
+
+    unsafe fn delete_renderbuffer(&self, renderbuffer: Self::Renderbuffer) {
+        self.record("delete_renderbuffer", vec![Arg::Name(Some(renderbuffer))]);
+        self.delete(renderbuffer);
+    }
+
+    unsafe fn delete_sampler(&self, texture: Self::Sampler) {
+        self.record("delete_sampler", vec![Arg::Name(Some(texture))]);
+        self.delete(texture);
+    }
+
+    unsafe fn delete_sync(&self, fence: Self::Fence) {
+        self.record("delete_sync", vec![Arg::Name(Some(fence))]);
+        self.delete(fence);
+    }
+
+    unsafe fn delete_texture(&self, texture: Self::Texture) {
+        self.record("delete_texture", vec![Arg::Name(Some(texture))]);
+        self.delete(texture);
+    }
+
+    unsafe fn disable(&self, parameter: u32) {
+        self.record("disable", vec![Arg::U32(parameter)]);
+    }
+
+    unsafe fn disable_draw_buffer(&self, parameter: u32, draw_buffer: u32) {
+        self.record("disable_draw_buffer", vec![Arg::U32(parameter), Arg::U32(draw_buffer)]);
+    }
+
+    unsafe fn disable_vertex_attrib_array(&self, index: u32) {
+        self.record("disable_vertex_attrib_array", vec![Arg::U32(index)]);
+    }
+
+    unsafe fn dispatch_compute(&self, groups_x: u32, groups_y: u32, groups_z: u32) {
+        self.record("dispatch_compute", vec![Arg::U32(groups_x), Arg::U32(groups_y), Arg::U32(groups_z)]);
+    }
+
+    unsafe fn dispatch_compute_indirect(&self, offset: i32) {
+        self.record("dispatch_compute_indirect", vec![Arg::I32(offset)]);
+    }
+
+    unsafe fn draw_arrays(&self, mode: u32, first: i32, count: i32) {
+        self.record("draw_arrays", vec![Arg::U32(mode), Arg::I32(first), Arg::I32(count)]);
+    }
+
+    unsafe fn draw_arrays_instanced(&self, mode: u32, first: i32, count: i32, instance_count: i32) {
+        self.record("draw_arrays_instanced", vec![Arg::U32(mode), Arg::I32(first), Arg::I32(count), Arg::I32(instance_count)]);
+    }
+
+    unsafe fn draw_arrays_instanced_base_instance(&self, mode: u32, first: i32, count: i32, instance_count: i32, base_instance: u32) {
+        self.record("draw_arrays_instanced_base_instance", vec![Arg::U32(mode), Arg::I32(first), Arg::I32(count), Arg::I32(instance_count), Arg::U32(base_instance)]);
+    }
+
+    unsafe fn draw_arrays_indirect_offset(&self, mode: u32, offset: i32) {
+        self.record("draw_arrays_indirect_offset", vec![Arg::U32(mode), Arg::I32(offset)]);
+    }
+
+    unsafe fn draw_buffer(&self, buffer: u32) {
+        self.record("draw_buffer", vec![Arg::U32(buffer)]);
+    }
+
+    unsafe fn draw_buffers(&self, buffers: &[u32]) {
+        self.record("draw_buffers", vec![Arg::U32s(buffers.to_vec())]);
+    }
+
+    unsafe fn draw_elements(&self, mode: u32, count: i32, element_type: u32, offset: i32) {
+        self.record("draw_elements", vec![Arg::U32(mode), Arg::I32(count), Arg::U32(element_type), Arg::I32(offset)]);
+    }
+
+    unsafe fn draw_elements_base_vertex(&self, mode: u32, count: i32, element_type: u32, offset: i32, base_vertex: i32) {
+        self.record("draw_elements_base_vertex", vec![Arg::U32(mode), Arg::I32(count), Arg::U32(element_type), Arg::I32(offset), Arg::I32(base_vertex)]);
+    }
+
+    unsafe fn draw_elements_instanced(&self, mode: u32, count: i32, element_type: u32, offset: i32, instance_count: i32) {
+        self.record("draw_elements_instanced", vec![Arg::U32(mode), Arg::I32(count), Arg::U32(element_type), Arg::I32(offset), Arg::I32(instance_count)]);
+    }
+
+    unsafe fn draw_elements_instanced_base_vertex(&self, mode: u32, count: i32, element_type: u32, offset: i32, instance_count: i32, base_vertex: i32) {
+        self.record("draw_elements_instanced_base_vertex", vec![Arg::U32(mode), Arg::I32(count), Arg::U32(element_type), Arg::I32(offset), Arg::I32(instance_count), Arg::I32(base_vertex)]);
+    }
+
+    unsafe fn draw_elements_instanced_base_vertex_base_instance(&self, mode: u32, count: i32, element_type: u32, offset: i32, instance_count: i32, base_vertex: i32, base_instance: u32) {
+        self.record("draw_elements_instanced_base_vertex_base_instance", vec![Arg::U32(mode), Arg::I32(count), Arg::U32(element_type), Arg::I32(offset), Arg::I32(instance_count), Arg::I32(base_vertex), Arg::U32(base_instance)]);
+    }
+
+    unsafe fn draw_elements_indirect_offset(&self, mode: u32, element_type: u32, offset: i32) {
+        self.record("draw_elements_indirect_offset", vec![Arg::U32(mode), Arg::U32(element_type), Arg::I32(offset)]);
+    }
+
+    unsafe fn enable(&self, parameter: u32) {
+        self.record("enable", vec![Arg::U32(parameter)]);
+    }
+
+    unsafe fn is_enabled(&self, parameter: u32) -> bool {
+        self.record("is_enabled", vec![Arg::U32(parameter)]);
+        false
+    }
+
+    unsafe fn enable_draw_buffer(&self, parameter: u32, draw_buffer: u32) {
+        self.record("enable_draw_buffer", vec![Arg::U32(parameter), Arg::U32(draw_buffer)]);
+    }
+
+    unsafe fn enable_vertex_attrib_array(&self, index: u32) {
+        self.record("enable_vertex_attrib_array", vec![Arg::U32(index)]);
+    }
+
+    unsafe fn flush(&self) {
+        self.record("flush", vec![]);
+    }
+
+    unsafe fn framebuffer_renderbuffer(&self, target: u32, attachment: u32, renderbuffer_target: u32, renderbuffer: Option<Self::Renderbuffer>) {
+        self.record("framebuffer_renderbuffer", vec![Arg::U32(target), Arg::U32(attachment), Arg::U32(renderbuffer_target), Arg::Name(renderbuffer)]);
+    }
+
+    unsafe fn framebuffer_texture(&self, target: u32, attachment: u32, texture: Option<Self::Texture>, level: i32) {
+        self.record("framebuffer_texture", vec![Arg::U32(target), Arg::U32(attachment), Arg::Name(texture), Arg::I32(level)]);
+    }
+
+    unsafe fn framebuffer_texture_2d(&self, target: u32, attachment: u32, texture_target: u32, texture: Option<Self::Texture>, level: i32) {
+        self.record("framebuffer_texture_2d", vec![Arg::U32(target), Arg::U32(attachment), Arg::U32(texture_target), Arg::Name(texture), Arg::I32(level)]);
+    }
+
+    unsafe fn framebuffer_texture_3d(&self, target: u32, attachment: u32, texture_target: u32, texture: Option<Self::Texture>, level: i32, layer: i32) {
+        self.record("framebuffer_texture_3d", vec![Arg::U32(target), Arg::U32(attachment), Arg::U32(texture_target), Arg::Name(texture), Arg::I32(level), Arg::I32(layer)]);
+    }
+
+    unsafe fn framebuffer_texture_layer(&self, target: u32, attachment: u32, texture: Option<Self::Texture>, level: i32, layer: i32) {
+        self.record("framebuffer_texture_layer", vec![Arg::U32(target), Arg::U32(attachment), Arg::Name(texture), Arg::I32(level), Arg::I32(layer)]);
+    }
+
+    unsafe fn front_face(&self, value: u32) {
+        self.record("front_face", vec![Arg::U32(value)]);
+    }
+
+    unsafe fn get_error(&self) -> u32 {
+        self.record("get_error", vec![]);
+        glow::NO_ERROR
+    }
+
+    unsafe fn get_tex_parameter_i32(&self, target: u32, parameter: u32) -> i32 {
+        self.record("get_tex_parameter_i32", vec![Arg::U32(target), Arg::U32(parameter)]);
+        0
+    }
+
+    unsafe fn get_buffer_parameter_i32(&self, target: u32, parameter: u32) -> i32 {
+        self.record("get_buffer_parameter_i32", vec![Arg::U32(target), Arg::U32(parameter)]);
+        0
+    }
+
+    unsafe fn get_parameter_i32(&self, parameter: u32) -> i32 {
+        self.record("get_parameter_i32", vec![Arg::U32(parameter)]);
+        self.parameters.borrow().get(&parameter).copied().unwrap_or(0)
+    }
+
+    unsafe fn get_parameter_i32_slice(&self, parameter: u32, out: &mut [i32]) {
+        self.record("get_parameter_i32_slice", vec![Arg::U32(parameter), Arg::Len(out.len())]);
+    }
+
+    unsafe fn get_parameter_f32(&self, parameter: u32) -> f32 {
+        self.record("get_parameter_f32", vec![Arg::U32(parameter)]);
+        self.parameters.borrow().get(&parameter).copied().unwrap_or(0) as f32
+    }
+
+    unsafe fn get_parameter_f32_slice(&self, parameter: u32, out: &mut [f32]) {
+        self.record("get_parameter_f32_slice", vec![Arg::U32(parameter), Arg::Len(out.len())]);
+    }
+
+    unsafe fn get_parameter_indexed_i32(&self, parameter: u32, index: u32) -> i32 {
+        self.record("get_parameter_indexed_i32", vec![Arg::U32(parameter), Arg::U32(index)]);
+        0
+    }
+
+    unsafe fn get_parameter_indexed_string(&self, parameter: u32, index: u32) -> String {
+        self.record("get_parameter_indexed_string", vec![Arg::U32(parameter), Arg::U32(index)]);
+        String::new()
+    }
+
+    unsafe fn get_parameter_string(&self, parameter: u32) -> String {
+        self.record("get_parameter_string", vec![Arg::U32(parameter)]);
+        String::new()
+    }
+
+    unsafe fn get_active_uniform_block_parameter_i32(&self, program: Self::Program, uniform_block_index: u32, parameter: u32) -> i32 {
+        self.record("get_active_uniform_block_parameter_i32", vec![Arg::Name(Some(program)), Arg::U32(uniform_block_index), Arg::U32(parameter)]);
+        0
+    }
+
+    unsafe fn get_active_uniform_block_parameter_i32_slice(&self, program: Self::Program, uniform_block_index: u32, parameter: u32, out: &mut [i32]) {
+        self.record("get_active_uniform_block_parameter_i32_slice", vec![Arg::Name(Some(program)), Arg::U32(uniform_block_index), Arg::U32(parameter), Arg::Len(out.len())]);
+    }
+
+    unsafe fn get_active_uniform_block_name(&self, program: Self::Program, uniform_block_index: u32) -> String {
+        self.record("get_active_uniform_block_name", vec![Arg::Name(Some(program)), Arg::U32(uniform_block_index)]);
+        String::new()
+    }
+
+    unsafe fn get_uniform_location(&self, program: Self::Program, name: &str) -> Option<Self::UniformLocation> {
+        self.record("get_uniform_location", vec![Arg::Name(Some(program)), Arg::Str(name.to_string())]);
+        Some(name.to_string())
+    }
+
+    unsafe fn get_attrib_location(&self, program: Self::Program, name: &str) -> Option<u32> {
+        self.record("get_attrib_location", vec![Arg::Name(Some(program)), Arg::Str(name.to_string())]);
+        None
+    }
+
+    unsafe fn bind_attrib_location(&self, program: Self::Program, index: u32, name: &str) {
+        self.record("bind_attrib_location", vec![Arg::Name(Some(program)), Arg::U32(index), Arg::Str(name.to_string())]);
+    }
+
+    unsafe fn get_active_attributes(&self, program: Self::Program) -> u32 {
+        self.record("get_active_attributes", vec![Arg::Name(Some(program))]);
+        0
+    }
+
+    unsafe fn get_active_attribute(&self, program: Self::Program, index: u32) -> Option<ActiveAttribute> {
+        self.record("get_active_attribute", vec![Arg::Name(Some(program)), Arg::U32(index)]);
+        None
+    }
+
+    unsafe fn get_sync_status(&self, fence: Self::Fence) -> u32 {
+        self.record("get_sync_status", vec![Arg::Name(Some(fence))]);
+        0
+    }
+
+    unsafe fn is_sync(&self, fence: Self::Fence) -> bool {
+        self.record("is_sync", vec![Arg::Name(Some(fence))]);
+        false
+    }
+
+    unsafe fn renderbuffer_storage(&self, target: u32, internal_format: u32, width: i32, height: i32) {
+        self.record("renderbuffer_storage", vec![Arg::U32(target), Arg::U32(internal_format), Arg::I32(width), Arg::I32(height)]);
+    }
+
+    unsafe fn renderbuffer_storage_multisample(&self, target: u32, samples: i32, internal_format: u32, width: i32, height: i32) {
+        self.record("renderbuffer_storage_multisample", vec![Arg::U32(target), Arg::I32(samples), Arg::U32(internal_format), Arg::I32(width), Arg::I32(height)]);
+    }
+
+    unsafe fn sampler_parameter_f32(&self, sampler: Self::Sampler, name: u32, value: f32) {
+        self.record("sampler_parameter_f32", vec![Arg::Name(Some(sampler)), Arg::U32(name), Arg::F32(value)]);
+    }
+
+    unsafe fn sampler_parameter_f32_slice(&self, sampler: Self::Sampler, name: u32, value: &[f32]) {
+        self.record("sampler_parameter_f32_slice", vec![Arg::Name(Some(sampler)), Arg::U32(name), Arg::F32s(value.to_vec())]);
+    }
+
+    unsafe fn sampler_parameter_i32(&self, sampler: Self::Sampler, name: u32, value: i32) {
+        self.record("sampler_parameter_i32", vec![Arg::Name(Some(sampler)), Arg::U32(name), Arg::I32(value)]);
+    }
+
+    unsafe fn generate_mipmap(&self, target: u32) {
+        self.record("generate_mipmap", vec![Arg::U32(target)]);
+    }
+
+    unsafe fn tex_image_1d(&self, target: u32, level: i32, internal_format: i32, width: i32, border: i32, format: u32, ty: u32, pixels: Option<&[u8]>) {
+        self.record("tex_image_1d", vec![Arg::U32(target), Arg::I32(level), Arg::I32(internal_format), Arg::I32(width), Arg::I32(border), Arg::U32(format), Arg::U32(ty), pixels.map_or(Arg::None, |d| Arg::Bytes(d.to_vec()))]);
+    }
+
+    unsafe fn compressed_tex_image_1d(&self, target: u32, level: i32, internal_format: i32, width: i32, border: i32, image_size: i32, pixels: &[u8]) {
+        self.record("compressed_tex_image_1d", vec![Arg::U32(target), Arg::I32(level), Arg::I32(internal_format), Arg::I32(width), Arg::I32(border), Arg::I32(image_size), Arg::Bytes(pixels.to_vec())]);
+    }
+
+    unsafe fn tex_image_2d(&self, target: u32, level: i32, internal_format: i32, width: i32, height: i32, border: i32, format: u32, ty: u32, pixels: Option<&[u8]>) {
+        self.record("tex_image_2d", vec![Arg::U32(target), Arg::I32(level), Arg::I32(internal_format), Arg::I32(width), Arg::I32(height), Arg::I32(border), Arg::U32(format), Arg::U32(ty), pixels.map_or(Arg::None, |d| Arg::Bytes(d.to_vec()))]);
+    }
+
+    unsafe fn tex_image_2d_multisample(&self, target: u32, samples: i32, internal_format: i32, width: i32, height: i32, fixed_sample_locations: bool) {
+        self.record("tex_image_2d_multisample", vec![Arg::U32(target), Arg::I32(samples), Arg::I32(internal_format), Arg::I32(width), Arg::I32(height), Arg::Bool(fixed_sample_locations)]);
+    }
+
+    unsafe fn compressed_tex_image_2d(&self, target: u32, level: i32, internal_format: i32, width: i32, height: i32, border: i32, image_size: i32, pixels: &[u8]) {
+        self.record("compressed_tex_image_2d", vec![Arg::U32(target), Arg::I32(level), Arg::I32(internal_format), Arg::I32(width), Arg::I32(height), Arg::I32(border), Arg::I32(image_size), Arg::Bytes(pixels.to_vec())]);
+    }
+
+    unsafe fn tex_image_3d(&self, target: u32, level: i32, internal_format: i32, width: i32, height: i32, depth: i32, border: i32, format: u32, ty: u32, pixels: Option<&[u8]>) {
+        self.record("tex_image_3d", vec![Arg::U32(target), Arg::I32(level), Arg::I32(internal_format), Arg::I32(width), Arg::I32(height), Arg::I32(depth), Arg::I32(border), Arg::U32(format), Arg::U32(ty), pixels.map_or(Arg::None, |d| Arg::Bytes(d.to_vec()))]);
+    }
+
+    unsafe fn compressed_tex_image_3d(&self, target: u32, level: i32, internal_format: i32, width: i32, height: i32, depth: i32, border: i32, image_size: i32, pixels: &[u8]) {
+        self.record("compressed_tex_image_3d", vec![Arg::U32(target), Arg::I32(level), Arg::I32(internal_format), Arg::I32(width), Arg::I32(height), Arg::I32(depth), Arg::I32(border), Arg::I32(image_size), Arg::Bytes(pixels.to_vec())]);
+    }
+
+    unsafe fn tex_storage_1d(&self, target: u32, levels: i32, internal_format: u32, width: i32) {
+        self.record("tex_storage_1d", vec![Arg::U32(target), Arg::I32(levels), Arg::U32(internal_format), Arg::I32(width)]);
+    }
+
+    unsafe fn tex_storage_2d(&self, target: u32, levels: i32, internal_format: u32, width: i32, height: i32) {
+        self.record("tex_storage_2d", vec![Arg::U32(target), Arg::I32(levels), Arg::U32(internal_format), Arg::I32(width), Arg::I32(height)]);
+    }
+
+    unsafe fn tex_storage_2d_multisample(&self, target: u32, samples: i32, internal_format: u32, width: i32, height: i32, fixed_sample_locations: bool) {
+        self.record("tex_storage_2d_multisample", vec![Arg::U32(target), Arg::I32(samples), Arg::U32(internal_format), Arg::I32(width), Arg::I32(height), Arg::Bool(fixed_sample_locations)]);
+    }
+
+    unsafe fn tex_storage_3d(&self, target: u32, levels: i32, internal_format: u32, width: i32, height: i32, depth: i32) {
+        self.record("tex_storage_3d", vec![Arg::U32(target), Arg::I32(levels), Arg::U32(internal_format), Arg::I32(width), Arg::I32(height), Arg::I32(depth)]);
+    }
+
+    unsafe fn get_uniform_i32(&self, program: Self::Program, location: &Self::UniformLocation, v: &mut [i32]) {
+        self.record("get_uniform_i32", vec![Arg::Name(Some(program)), Arg::Uniform(Some(location.clone())), Arg::Len(v.len())]);
+    }
+
+    unsafe fn get_uniform_f32(&self, program: Self::Program, location: &Self::UniformLocation, v: &mut [f32]) {
+        self.record("get_uniform_f32", vec![Arg::Name(Some(program)), Arg::Uniform(Some(location.clone())), Arg::Len(v.len())]);
+    }
+
+    unsafe fn uniform_1_i32(&self, location: Option<&Self::UniformLocation>, x: i32) {
+        self.record("uniform_1_i32", vec![Arg::Uniform(location.cloned()), Arg::I32(x)]);
+    }
+
+    unsafe fn uniform_2_i32(&self, location: Option<&Self::UniformLocation>, x: i32, y: i32) {
+        self.record("uniform_2_i32", vec![Arg::Uniform(location.cloned()), Arg::I32(x), Arg::I32(y)]);
+    }
+
+    unsafe fn uniform_3_i32(&self, location: Option<&Self::UniformLocation>, x: i32, y: i32, z: i32) {
+        self.record("uniform_3_i32", vec![Arg::Uniform(location.cloned()), Arg::I32(x), Arg::I32(y), Arg::I32(z)]);
+    }
+
+    unsafe fn uniform_4_i32(&self, location: Option<&Self::UniformLocation>, x: i32, y: i32, z: i32, w: i32) {
+        self.record("uniform_4_i32", vec![Arg::Uniform(location.cloned()), Arg::I32(x), Arg::I32(y), Arg::I32(z), Arg::I32(w)]);
+    }
+
+    unsafe fn uniform_1_i32_slice(&self, location: Option<&Self::UniformLocation>, v: &[i32]) {
+        self.record("uniform_1_i32_slice", vec![Arg::Uniform(location.cloned()), Arg::I32s(v.to_vec())]);
+    }
+
+    unsafe fn uniform_2_i32_slice(&self, location: Option<&Self::UniformLocation>, v: &[i32]) {
+        self.record("uniform_2_i32_slice", vec![Arg::Uniform(location.cloned()), Arg::I32s(v.to_vec())]);
+    }
+
+    unsafe fn uniform_3_i32_slice(&self, location: Option<&Self::UniformLocation>, v: &[i32]) {
+        self.record("uniform_3_i32_slice", vec![Arg::Uniform(location.cloned()), Arg::I32s(v.to_vec())]);
+    }
+
+    unsafe fn uniform_4_i32_slice(&self, location: Option<&Self::UniformLocation>, v: &[i32]) {
+        self.record("uniform_4_i32_slice", vec![Arg::Uniform(location.cloned()), Arg::I32s(v.to_vec())]);
+    }
+
+    unsafe fn uniform_1_u32(&self, location: Option<&Self::UniformLocation>, x: u32) {
+        self.record("uniform_1_u32", vec![Arg::Uniform(location.cloned()), Arg::U32(x)]);
+    }
+
+    unsafe fn uniform_2_u32(&self, location: Option<&Self::UniformLocation>, x: u32, y: u32) {
+        self.record("uniform_2_u32", vec![Arg::Uniform(location.cloned()), Arg::U32(x), Arg::U32(y)]);
+    }
+
+    unsafe fn uniform_3_u32(&self, location: Option<&Self::UniformLocation>, x: u32, y: u32, z: u32) {
+        self.record("uniform_3_u32", vec![Arg::Uniform(location.cloned()), Arg::U32(x), Arg::U32(y), Arg::U32(z)]);
+    }
+
+    unsafe fn uniform_4_u32(&self, location: Option<&Self::UniformLocation>, x: u32, y: u32, z: u32, w: u32) {
+        self.record("uniform_4_u32", vec![Arg::Uniform(location.cloned()), Arg::U32(x), Arg::U32(y), Arg::U32(z), Arg::U32(w)]);
+    }
+
+    unsafe fn uniform_1_u32_slice(&self, location: Option<&Self::UniformLocation>, v: &[u32]) {
+        self.record("uniform_1_u32_slice", vec![Arg::Uniform(location.cloned()), Arg::U32s(v.to_vec())]);
+    }
+
+    unsafe fn uniform_2_u32_slice(&self, location: Option<&Self::UniformLocation>, v: &[u32]) {
+        self.record("uniform_2_u32_slice", vec![Arg::Uniform(location.cloned()), Arg::U32s(v.to_vec())]);
+    }
+
+    unsafe fn uniform_3_u32_slice(&self, location: Option<&Self::UniformLocation>, v: &[u32]) {
+        self.record("uniform_3_u32_slice", vec![Arg::Uniform(location.cloned()), Arg::U32s(v.to_vec())]);
+    }
+
+    unsafe fn uniform_4_u32_slice(&self, location: Option<&Self::UniformLocation>, v: &[u32]) {
+        self.record("uniform_4_u32_slice", vec![Arg::Uniform(location.cloned()), Arg::U32s(v.to_vec())]);
+    }
+
+    unsafe fn uniform_1_f32(&self, location: Option<&Self::UniformLocation>, x: f32) {
+        self.record("uniform_1_f32", vec![Arg::Uniform(location.cloned()), Arg::F32(x)]);
+    }
+
+    unsafe fn uniform_2_f32(&self, location: Option<&Self::UniformLocation>, x: f32, y: f32) {
+        self.record("uniform_2_f32", vec![Arg::Uniform(location.cloned()), Arg::F32(x), Arg::F32(y)]);
+    }
+
+    unsafe fn uniform_3_f32(&self, location: Option<&Self::UniformLocation>, x: f32, y: f32, z: f32) {
+        self.record("uniform_3_f32", vec![Arg::Uniform(location.cloned()), Arg::F32(x), Arg::F32(y), Arg::F32(z)]);
+    }
+
+    unsafe fn uniform_4_f32(&self, location: Option<&Self::UniformLocation>, x: f32, y: f32, z: f32, w: f32) {
+        self.record("uniform_4_f32", vec![Arg::Uniform(location.cloned()), Arg::F32(x), Arg::F32(y), Arg::F32(z), Arg::F32(w)]);
+    }
+
+    unsafe fn uniform_1_f32_slice(&self, location: Option<&Self::UniformLocation>, v: &[f32]) {
+        self.record("uniform_1_f32_slice", vec![Arg::Uniform(location.cloned()), Arg::F32s(v.to_vec())]);
+    }
+
+    unsafe fn uniform_2_f32_slice(&self, location: Option<&Self::UniformLocation>, v: &[f32]) {
+        self.record("uniform_2_f32_slice", vec![Arg::Uniform(location.cloned()), Arg::F32s(v.to_vec())]);
+    }
+
+    unsafe fn uniform_3_f32_slice(&self, location: Option<&Self::UniformLocation>, v: &[f32]) {
+        self.record("uniform_3_f32_slice", vec![Arg::Uniform(location.cloned()), Arg::F32s(v.to_vec())]);
+    }
+
+    unsafe fn uniform_4_f32_slice(&self, location: Option<&Self::UniformLocation>, v: &[f32]) {
+        self.record("uniform_4_f32_slice", vec![Arg::Uniform(location.cloned()), Arg::F32s(v.to_vec())]);
+    }
+
+    unsafe fn uniform_matrix_2_f32_slice(&self, location: Option<&Self::UniformLocation>, transpose: bool, v: &[f32]) {
+        self.record("uniform_matrix_2_f32_slice", vec![Arg::Uniform(location.cloned()), Arg::Bool(transpose), Arg::F32s(v.to_vec())]);
+    }
+
+    unsafe fn uniform_matrix_3_f32_slice(&self, location: Option<&Self::UniformLocation>, transpose: bool, v: &[f32]) {
+        self.record("uniform_matrix_3_f32_slice", vec![Arg::Uniform(location.cloned()), Arg::Bool(transpose), Arg::F32s(v.to_vec())]);
+    }
+
+    unsafe fn uniform_matrix_4_f32_slice(&self, location: Option<&Self::UniformLocation>, transpose: bool, v: &[f32]) {
+        self.record("uniform_matrix_4_f32_slice", vec![Arg::Uniform(location.cloned()), Arg::Bool(transpose), Arg::F32s(v.to_vec())]);
+    }
+
+    unsafe fn unmap_buffer(&self, target: u32) {
+        self.record("unmap_buffer", vec![Arg::U32(target)]);
+    }
+
+    unsafe fn cull_face(&self, value: u32) {
+        self.record("cull_face", vec![Arg::U32(value)]);
+    }
+
+    unsafe fn color_mask(&self, red: bool, green: bool, blue: bool, alpha: bool) {
+        self.record("color_mask", vec![Arg::Bool(red), Arg::Bool(green), Arg::Bool(blue), Arg::Bool(alpha)]);
+    }
+
+    unsafe fn color_mask_draw_buffer(&self, buffer: u32, red: bool, green: bool, blue: bool, alpha: bool) {
+        self.record("color_mask_draw_buffer", vec![Arg::U32(buffer), Arg::Bool(red), Arg::Bool(green), Arg::Bool(blue), Arg::Bool(alpha)]);
+    }
+
+    unsafe fn depth_mask(&self, value: bool) {
+        self.record("depth_mask", vec![Arg::Bool(value)]);
+    }
+
+    unsafe fn blend_color(&self, red: f32, green: f32, blue: f32, alpha: f32) {
+        self.record("blend_color", vec![Arg::F32(red), Arg::F32(green), Arg::F32(blue), Arg::F32(alpha)]);
+    }
+
+    unsafe fn line_width(&self, width: f32) {
+        self.record("line_width", vec![Arg::F32(width)]);
+    }
+
+    unsafe fn map_buffer_range(&self, target: u32, offset: i32, length: i32, access: u32) -> *mut u8 {
+        self.record("map_buffer_range", vec![Arg::U32(target), Arg::I32(offset), Arg::I32(length), Arg::U32(access)]);
+        std::ptr::null_mut()
+    }
+
+    unsafe fn flush_mapped_buffer_range(&self, target: u32, offset: i32, length: i32) {
+        self.record("flush_mapped_buffer_range", vec![Arg::U32(target), Arg::I32(offset), Arg::I32(length)]);
+    }
+
+    unsafe fn invalidate_buffer_sub_data(&self, target: u32, offset: i32, length: i32) {
+        self.record("invalidate_buffer_sub_data", vec![Arg::U32(target), Arg::I32(offset), Arg::I32(length)]);
+    }
+
+    unsafe fn invalidate_framebuffer(&self, target: u32, attachments: &[u32]) {
+        self.record("invalidate_framebuffer", vec![Arg::U32(target), Arg::U32s(attachments.to_vec())]);
+    }
+
+    unsafe fn polygon_offset(&self, factor: f32, units: f32) {
+        self.record("polygon_offset", vec![Arg::F32(factor), Arg::F32(units)]);
+    }
+
+    unsafe fn polygon_mode(&self, face: u32, mode: u32) {
+        self.record("polygon_mode", vec![Arg::U32(face), Arg::U32(mode)]);
+    }
+
+    unsafe fn finish(&self) {
+        self.record("finish", vec![]);
+    }
+
+    unsafe fn bind_texture(&self, target: u32, texture: Option<Self::Texture>) {
+        self.record("bind_texture", vec![Arg::U32(target), Arg::Name(texture)]);
+    }
+
+    unsafe fn bind_sampler(&self, unit: u32, sampler: Option<Self::Sampler>) {
+        self.record("bind_sampler", vec![Arg::U32(unit), Arg::Name(sampler)]);
+    }
+
+    unsafe fn active_texture(&self, unit: u32) {
+        self.record("active_texture", vec![Arg::U32(unit)]);
+    }
+
+    unsafe fn fence_sync(&self, condition: u32, flags: u32) -> Result<Self::Fence, String> {
+        self.record("fence_sync", vec![Arg::U32(condition), Arg::U32(flags)]);
+        self.create()
+    }
+
+    unsafe fn tex_parameter_f32(&self, target: u32, parameter: u32, value: f32) {
+        self.record("tex_parameter_f32", vec![Arg::U32(target), Arg::U32(parameter), Arg::F32(value)]);
+    }
+
+    unsafe fn tex_parameter_i32(&self, target: u32, parameter: u32, value: i32) {
+        self.record("tex_parameter_i32", vec![Arg::U32(target), Arg::U32(parameter), Arg::I32(value)]);
+    }
+
+    unsafe fn tex_parameter_f32_slice(&self, target: u32, parameter: u32, values: &[f32]) {
+        self.record("tex_parameter_f32_slice", vec![Arg::U32(target), Arg::U32(parameter), Arg::F32s(values.to_vec())]);
+    }
+
+    unsafe fn tex_parameter_i32_slice(&self, target: u32, parameter: u32, values: &[i32]) {
+        self.record("tex_parameter_i32_slice", vec![Arg::U32(target), Arg::U32(parameter), Arg::I32s(values.to_vec())]);
+    }
+
+    unsafe fn tex_sub_image_2d(&self, target: u32, level: i32, x_offset: i32, y_offset: i32, width: i32, height: i32, format: u32, ty: u32, pixels: PixelUnpackData) {
+        self.record("tex_sub_image_2d", vec![Arg::U32(target), Arg::I32(level), Arg::I32(x_offset), Arg::I32(y_offset), Arg::I32(width), Arg::I32(height), Arg::U32(format), Arg::U32(ty), unpack_arg(&pixels)]);
+    }
+
+    unsafe fn compressed_tex_sub_image_2d(&self, target: u32, level: i32, x_offset: i32, y_offset: i32, width: i32, height: i32, format: u32, pixels: CompressedPixelUnpackData) {
+        self.record("compressed_tex_sub_image_2d", vec![Arg::U32(target), Arg::I32(level), Arg::I32(x_offset), Arg::I32(y_offset), Arg::I32(width), Arg::I32(height), Arg::U32(format), compressed_arg(&pixels)]);
+    }
+
+    unsafe fn tex_sub_image_3d(&self, target: u32, level: i32, x_offset: i32, y_offset: i32, z_offset: i32, width: i32, height: i32, depth: i32, format: u32, ty: u32, pixels: PixelUnpackData) {
+        self.record("tex_sub_image_3d", vec![Arg::U32(target), Arg::I32(level), Arg::I32(x_offset), Arg::I32(y_offset), Arg::I32(z_offset), Arg::I32(width), Arg::I32(height), Arg::I32(depth), Arg::U32(format), Arg::U32(ty), unpack_arg(&pixels)]);
+    }
+
+    unsafe fn compressed_tex_sub_image_3d(&self, target: u32, level: i32, x_offset: i32, y_offset: i32, z_offset: i32, width: i32, height: i32, depth: i32, format: u32, pixels: CompressedPixelUnpackData) {
+        self.record("compressed_tex_sub_image_3d", vec![Arg::U32(target), Arg::I32(level), Arg::I32(x_offset), Arg::I32(y_offset), Arg::I32(z_offset), Arg::I32(width), Arg::I32(height), Arg::I32(depth), Arg::U32(format), compressed_arg(&pixels)]);
+    }
+
+    unsafe fn depth_func(&self, func: u32) {
+        self.record("depth_func", vec![Arg::U32(func)]);
+    }
+
+    unsafe fn depth_range_f32(&self, near: f32, far: f32) {
+        self.record("depth_range_f32", vec![Arg::F32(near), Arg::F32(far)]);
+    }
+
+    unsafe fn depth_range_f64(&self, near: f64, far: f64) {
+        self.record("depth_range_f64", vec![Arg::F64(near), Arg::F64(far)]);
+    }
+
+    unsafe fn depth_range_f64_slice(&self, first: u32, count: i32, values: &[[f64; 2]]) {
+        self.record("depth_range_f64_slice", vec![Arg::U32(first), Arg::I32(count), Arg::F64s(values.iter().flatten().copied().collect())]);
+    }
+
+    unsafe fn scissor(&self, x: i32, y: i32, width: i32, height: i32) {
+        self.record("scissor", vec![Arg::I32(x), Arg::I32(y), Arg::I32(width), Arg::I32(height)]);
+    }
+
+    unsafe fn scissor_slice(&self, first: u32, count: i32, scissors: &[[i32; 4]]) {
+        self.record("scissor_slice", vec![Arg::U32(first), Arg::I32(count), Arg::I32s(scissors.iter().flatten().copied().collect())]);
+    }
+
+    unsafe fn vertex_attrib_divisor(&self, index: u32, divisor: u32) {
+        self.record("vertex_attrib_divisor", vec![Arg::U32(index), Arg::U32(divisor)]);
+    }
+
+    unsafe fn vertex_attrib_pointer_f32(&self, index: u32, size: i32, data_type: u32, normalized: bool, stride: i32, offset: i32) {
+        self.record("vertex_attrib_pointer_f32", vec![Arg::U32(index), Arg::I32(size), Arg::U32(data_type), Arg::Bool(normalized), Arg::I32(stride), Arg::I32(offset)]);
+    }
+
+    unsafe fn vertex_attrib_pointer_i32(&self, index: u32, size: i32, data_type: u32, stride: i32, offset: i32) {
+        self.record("vertex_attrib_pointer_i32", vec![Arg::U32(index), Arg::I32(size), Arg::U32(data_type), Arg::I32(stride), Arg::I32(offset)]);
+    }
+
+    unsafe fn vertex_attrib_pointer_f64(&self, index: u32, size: i32, data_type: u32, stride: i32, offset: i32) {
+        self.record("vertex_attrib_pointer_f64", vec![Arg::U32(index), Arg::I32(size), Arg::U32(data_type), Arg::I32(stride), Arg::I32(offset)]);
+    }
+
+    unsafe fn vertex_attrib_format_f32(&self, index: u32, size: i32, data_type: u32, normalized: bool, relative_offset: u32) {
+        self.record("vertex_attrib_format_f32", vec![Arg::U32(index), Arg::I32(size), Arg::U32(data_type), Arg::Bool(normalized), Arg::U32(relative_offset)]);
+    }
+
+    unsafe fn vertex_attrib_format_i32(&self, index: u32, size: i32, data_type: u32, relative_offset: u32) {
+        self.record("vertex_attrib_format_i32", vec![Arg::U32(index), Arg::I32(size), Arg::U32(data_type), Arg::U32(relative_offset)]);
+    }
+
+    unsafe fn vertex_attrib_1_f32(&self, index: u32, x: f32) {
+        self.record("vertex_attrib_1_f32", vec![Arg::U32(index), Arg::F32(x)]);
+    }
+
+    unsafe fn vertex_attrib_2_f32(&self, index: u32, x: f32, y: f32) {
+        self.record("vertex_attrib_2_f32", vec![Arg::U32(index), Arg::F32(x), Arg::F32(y)]);
+    }
+
+    unsafe fn vertex_attrib_3_f32(&self, index: u32, x: f32, y: f32, z: f32) {
+        self.record("vertex_attrib_3_f32", vec![Arg::U32(index), Arg::F32(x), Arg::F32(y), Arg::F32(z)]);
+    }
+
+    unsafe fn vertex_attrib_4_f32(&self, index: u32, x: f32, y: f32, z: f32, w: f32) {
+        self.record("vertex_attrib_4_f32", vec![Arg::U32(index), Arg::F32(x), Arg::F32(y), Arg::F32(z), Arg::F32(w)]);
+    }
+
+    unsafe fn vertex_attrib_1_f32_slice(&self, index: u32, v: &[f32]) {
+        self.record("vertex_attrib_1_f32_slice", vec![Arg::U32(index), Arg::F32s(v.to_vec())]);
+    }
+
+    unsafe fn vertex_attrib_2_f32_slice(&self, index: u32, v: &[f32]) {
+        self.record("vertex_attrib_2_f32_slice", vec![Arg::U32(index), Arg::F32s(v.to_vec())]);
+    }
+
+    unsafe fn vertex_attrib_3_f32_slice(&self, index: u32, v: &[f32]) {
+        self.record("vertex_attrib_3_f32_slice", vec![Arg::U32(index), Arg::F32s(v.to_vec())]);
+    }
+
+    unsafe fn vertex_attrib_4_f32_slice(&self, index: u32, v: &[f32]) {
+        self.record("vertex_attrib_4_f32_slice", vec![Arg::U32(index), Arg::F32s(v.to_vec())]);
+    }
+
+    unsafe fn vertex_attrib_binding(&self, attrib_index: u32, binding_index: u32) {
+        self.record("vertex_attrib_binding", vec![Arg::U32(attrib_index), Arg::U32(binding_index)]);
+    }
+
+    unsafe fn vertex_binding_divisor(&self, binding_index: u32, divisor: u32) {
+        self.record("vertex_binding_divisor", vec![Arg::U32(binding_index), Arg::U32(divisor)]);
+    }
+
+    unsafe fn viewport(&self, x: i32, y: i32, width: i32, height: i32) {
+        self.record("viewport", vec![Arg::I32(x), Arg::I32(y), Arg::I32(width), Arg::I32(height)]);
+    }
+
+    unsafe fn viewport_f32_slice(&self, first: u32, count: i32, values: &[[f32; 4]]) {
+        self.record("viewport_f32_slice", vec![Arg::U32(first), Arg::I32(count), Arg::F32s(values.iter().flatten().copied().collect())]);
+    }
+
+    unsafe fn blend_equation(&self, mode: u32) {
+        self.record("blend_equation", vec![Arg::U32(mode)]);
+    }
+
+    unsafe fn blend_equation_draw_buffer(&self, draw_buffer: u32, mode: u32) {
+        self.record("blend_equation_draw_buffer", vec![Arg::U32(draw_buffer), Arg::U32(mode)]);
+    }
+
+    unsafe fn blend_equation_separate(&self, mode_rgb: u32, mode_alpha: u32) {
+        self.record("blend_equation_separate", vec![Arg::U32(mode_rgb), Arg::U32(mode_alpha)]);
+    }
+
+    unsafe fn blend_equation_separate_draw_buffer(&self, buffer: u32, mode_rgb: u32, mode_alpha: u32) {
+        self.record("blend_equation_separate_draw_buffer", vec![Arg::U32(buffer), Arg::U32(mode_rgb), Arg::U32(mode_alpha)]);
+    }
+
+    unsafe fn blend_func(&self, src: u32, dst: u32) {
+        self.record("blend_func", vec![Arg::U32(src), Arg::U32(dst)]);
+    }
+
+    unsafe fn blend_func_draw_buffer(&self, draw_buffer: u32, src: u32, dst: u32) {
+        self.record("blend_func_draw_buffer", vec![Arg::U32(draw_buffer), Arg::U32(src), Arg::U32(dst)]);
+    }
+
+    unsafe fn blend_func_separate(&self, src_rgb: u32, dst_rgb: u32, src_alpha: u32, dst_alpha: u32) {
+        self.record("blend_func_separate", vec![Arg::U32(src_rgb), Arg::U32(dst_rgb), Arg::U32(src_alpha), Arg::U32(dst_alpha)]);
+    }
+
+    unsafe fn blend_func_separate_draw_buffer(&self, draw_buffer: u32, src_rgb: u32, dst_rgb: u32, src_alpha: u32, dst_alpha: u32) {
+        self.record("blend_func_separate_draw_buffer", vec![Arg::U32(draw_buffer), Arg::U32(src_rgb), Arg::U32(dst_rgb), Arg::U32(src_alpha), Arg::U32(dst_alpha)]);
+    }
+
+    unsafe fn stencil_func(&self, func: u32, reference: i32, mask: u32) {
+        self.record("stencil_func", vec![Arg::U32(func), Arg::I32(reference), Arg::U32(mask)]);
+    }
+
+    unsafe fn stencil_func_separate(&self, face: u32, func: u32, reference: i32, mask: u32) {
+        self.record("stencil_func_separate", vec![Arg::U32(face), Arg::U32(func), Arg::I32(reference), Arg::U32(mask)]);
+    }
+
+    unsafe fn stencil_mask(&self, mask: u32) {
+        self.record("stencil_mask", vec![Arg::U32(mask)]);
+    }
+
+    unsafe fn stencil_mask_separate(&self, face: u32, mask: u32) {
+        self.record("stencil_mask_separate", vec![Arg::U32(face), Arg::U32(mask)]);
+    }
+
+    unsafe fn stencil_op(&self, stencil_fail: u32, depth_fail: u32, pass: u32) {
+        self.record("stencil_op", vec![Arg::U32(stencil_fail), Arg::U32(depth_fail), Arg::U32(pass)]);
+    }
+
+    unsafe fn stencil_op_separate(&self, face: u32, stencil_fail: u32, depth_fail: u32, pass: u32) {
+        self.record("stencil_op_separate", vec![Arg::U32(face), Arg::U32(stencil_fail), Arg::U32(depth_fail), Arg::U32(pass)]);
+    }
+
+    unsafe fn debug_message_control(&self, source: u32, msg_type: u32, severity: u32, ids: &[u32], enabled: bool) {
+        self.record("debug_message_control", vec![Arg::U32(source), Arg::U32(msg_type), Arg::U32(severity), Arg::U32s(ids.to_vec()), Arg::Bool(enabled)]);
+    }
+
+    unsafe fn debug_message_insert<S>(&self, source: u32, msg_type: u32, id: u32, severity: u32, msg: S)
+    where
+        S: AsRef<str>,
+    {
+        self.record("debug_message_insert", vec![Arg::U32(source), Arg::U32(msg_type), Arg::U32(id), Arg::U32(severity), Arg::Str(msg.as_ref().to_string())]);
+    }
+
+    unsafe fn debug_message_callback<F>(&self, _callback: F)
+    where
+        F: FnMut(u32, u32, u32, u32, &str),
+    {
+        self.record("debug_message_callback", vec![Arg::None]);
+    }
+
+    unsafe fn get_debug_message_log(&self, count: u32) -> Vec<DebugMessageLogEntry> {
+        self.record("get_debug_message_log", vec![Arg::U32(count)]);
+        Vec::new()
+    }
+
+    unsafe fn push_debug_group<S>(&self, source: u32, id: u32, message: S)
+    where
+        S: AsRef<str>,
+    {
+        self.record("push_debug_group", vec![Arg::U32(source), Arg::U32(id), Arg::Str(message.as_ref().to_string())]);
+    }
+
+    unsafe fn pop_debug_group(&self) {
+        self.record("pop_debug_group", vec![]);
+    }
+
+    unsafe fn object_label<S>(&self, identifier: u32, name: u32, label: Option<S>)
+    where
+        S: AsRef<str>,
+    {
+        self.record("object_label", vec![Arg::U32(identifier), Arg::U32(name), label.map_or(Arg::None, |s| Arg::Str(s.as_ref().to_string()))]);
+    }
+
+    unsafe fn get_object_label(&self, identifier: u32, name: u32) -> String {
+        self.record("get_object_label", vec![Arg::U32(identifier), Arg::U32(name)]);
+        String::new()
+    }
+
+    unsafe fn object_ptr_label<S>(&self, sync: Self::Fence, label: Option<S>)
+    where
+        S: AsRef<str>,
+    {
+        self.record("object_ptr_label", vec![Arg::Name(Some(sync)), label.map_or(Arg::None, |s| Arg::Str(s.as_ref().to_string()))]);
+    }
+
+    unsafe fn get_object_ptr_label(&self, sync: Self::Fence) -> String {
+        self.record("get_object_ptr_label", vec![Arg::Name(Some(sync))]);
+        String::new()
+    }
+
+    unsafe fn get_uniform_block_index(&self, program: Self::Program, name: &str) -> Option<u32> {
+        self.record("get_uniform_block_index", vec![Arg::Name(Some(program)), Arg::Str(name.to_string())]);
+        None
+    }
+
+    unsafe fn uniform_block_binding(&self, program: Self::Program, index: u32, binding: u32) {
+        self.record("uniform_block_binding", vec![Arg::Name(Some(program)), Arg::U32(index), Arg::U32(binding)]);
+    }
+
+    unsafe fn get_shader_storage_block_index(&self, program: Self::Program, name: &str) -> Option<u32> {
+        self.record("get_shader_storage_block_index", vec![Arg::Name(Some(program)), Arg::Str(name.to_string())]);
+        None
+    }
+
+    unsafe fn shader_storage_block_binding(&self, program: Self::Program, index: u32, binding: u32) {
+        self.record("shader_storage_block_binding", vec![Arg::Name(Some(program)), Arg::U32(index), Arg::U32(binding)]);
+    }
+
+    unsafe fn read_buffer(&self, src: u32) {
+        self.record("read_buffer", vec![Arg::U32(src)]);
+    }
+
+    unsafe fn read_pixels(&self, x: i32, y: i32, width: i32, height: i32, format: u32, gltype: u32, pixels: PixelPackData) {
+        self.record("read_pixels", vec![Arg::I32(x), Arg::I32(y), Arg::I32(width), Arg::I32(height), Arg::U32(format), Arg::U32(gltype), pack_arg(&pixels)]);
+    }
+
+    unsafe fn begin_query(&self, target: u32, query: Self::Query) {
+        self.record("begin_query", vec![Arg::U32(target), Arg::Name(Some(query))]);
+    }
+
+    unsafe fn end_query(&self, target: u32) {
+        self.record("end_query", vec![Arg::U32(target)]);
+    }
+
+    unsafe fn get_query_parameter_u32(&self, query: Self::Query, parameter: u32) -> u32 {
+        self.record("get_query_parameter_u32", vec![Arg::Name(Some(query)), Arg::U32(parameter)]);
+        0
+    }
+
+    unsafe fn delete_transform_feedback(&self, transform_feedback: Self::TransformFeedback) {
+        self.record("delete_transform_feedback", vec![Arg::Name(Some(transform_feedback))]);
+        self.delete(transform_feedback);
+    }
+
+    unsafe fn create_transform_feedback(&self) -> Result<Self::TransformFeedback, String> {
+        self.record("create_transform_feedback", vec![]);
+        self.create()
+    }
+
+    unsafe fn bind_transform_feedback(&self, target: u32, transform_feedback: Option<Self::TransformFeedback>) {
+        self.record("bind_transform_feedback", vec![Arg::U32(target), Arg::Name(transform_feedback)]);
+    }
+
+    unsafe fn begin_transform_feedback(&self, primitive_mode: u32) {
+        self.record("begin_transform_feedback", vec![Arg::U32(primitive_mode)]);
+    }
+
+    unsafe fn end_transform_feedback(&self) {
+        self.record("end_transform_feedback", vec![]);
+    }
+
+    unsafe fn pause_transform_feedback(&self) {
+        self.record("pause_transform_feedback", vec![]);
+    }
+
+    unsafe fn resume_transform_feedback(&self) {
+        self.record("resume_transform_feedback", vec![]);
+    }
+
+    unsafe fn transform_feedback_varyings(&self, program: Self::Program, varyings: &[&str], buffer_mode: u32) {
+        self.record("transform_feedback_varyings", vec![Arg::Name(Some(program)), Arg::Strs(varyings.iter().map(|s| s.to_string()).collect()), Arg::U32(buffer_mode)]);
+    }
+
+    unsafe fn get_transform_feedback_varying(&self, program: Self::Program, index: u32) -> Option<ActiveTransformFeedback> {
+        self.record("get_transform_feedback_varying", vec![Arg::Name(Some(program)), Arg::U32(index)]);
+        None
+    }
+
+    unsafe fn memory_barrier(&self, barriers: u32) {
+        self.record("memory_barrier", vec![Arg::U32(barriers)]);
+    }
+
+    unsafe fn memory_barrier_by_region(&self, barriers: u32) {
+        self.record("memory_barrier_by_region", vec![Arg::U32(barriers)]);
+    }
+
+    unsafe fn bind_image_texture(&self, unit: u32, texture: Self::Texture, level: i32, layered: bool, layer: i32, access: u32, format: u32) {
+        self.record("bind_image_texture", vec![Arg::U32(unit), Arg::Name(Some(texture)), Arg::I32(level), Arg::Bool(layered), Arg::I32(layer), Arg::U32(access), Arg::U32(format)]);
+    }
+}
@@ -0,0 +1,190 @@
+use glow::HasContext;
+use minimg::*;
+use crate::error::*;
+use crate::gc::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Filter {
+    /// blocky, for pixel art
+    Nearest,
+    Linear,
+    /// linear between pixels and between mip levels. Always generates mipmaps
+    Trilinear,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wrap {
+    Clamp,
+    Repeat,
+    Mirror,
+}
+
+/// how a texture gets sampled. Default is what textures always used to get: nearest, clamped, no mipmaps
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SamplerDesc {
+    pub filter: Filter,
+    pub wrap: Wrap,
+    /// max anisotropy eg. 16.0, clamped to what the driver supports. Ignored if the driver doesnt support it
+    pub anisotropy: Option<f32>,
+    pub mipmaps: bool,
+}
+
+impl Default for SamplerDesc {
+    fn default() -> Self {
+        SamplerDesc {
+            filter: Filter::Nearest,
+            wrap: Wrap::Clamp,
+            anisotropy: None,
+            mipmaps: false,
+        }
+    }
+}
+
+impl SamplerDesc {
+    pub fn nearest() -> Self {
+        Self::default()
+    }
+    pub fn linear() -> Self {
+        SamplerDesc { filter: Filter::Linear, ..Self::default() }
+    }
+    pub fn trilinear() -> Self {
+        SamplerDesc { filter: Filter::Trilinear, mipmaps: true, ..Self::default() }
+    }
+    pub fn with_wrap(self, wrap: Wrap) -> Self {
+        SamplerDesc { wrap, ..self }
+    }
+    pub fn with_anisotropy(self, anisotropy: f32) -> Self {
+        SamplerDesc { anisotropy: Some(anisotropy), ..self }
+    }
+    pub fn with_mipmaps(self, mipmaps: bool) -> Self {
+        SamplerDesc { mipmaps, ..self }
+    }
+    pub fn generates_mipmaps(&self) -> bool {
+        self.mipmaps || self.filter == Filter::Trilinear
+    }
+    fn min_filter(&self) -> u32 {
+        match (self.filter, self.generates_mipmaps()) {
+            (Filter::Nearest, false) => glow::NEAREST,
+            (Filter::Nearest, true) => glow::NEAREST_MIPMAP_NEAREST,
+            (Filter::Linear, false) => glow::LINEAR,
+            (Filter::Linear, true) => glow::LINEAR_MIPMAP_NEAREST,
+            (Filter::Trilinear, _) => glow::LINEAR_MIPMAP_LINEAR,
+        }
+    }
+    fn mag_filter(&self) -> u32 {
+        match self.filter {
+            Filter::Nearest => glow::NEAREST,
+            Filter::Linear | Filter::Trilinear => glow::LINEAR,
+        }
+    }
+    fn wrap_mode(&self) -> u32 {
+        match self.wrap {
+            Wrap::Clamp => glow::CLAMP_TO_EDGE,
+            Wrap::Repeat => glow::REPEAT,
+            Wrap::Mirror => glow::MIRRORED_REPEAT,
+        }
+    }
+    /// set the parameters on the bound TEXTURE_2D
+    unsafe fn apply<G: HasContext>(&self, gl: &G) {
+        gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MIN_FILTER, self.min_filter() as i32);
+        gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MAG_FILTER, self.mag_filter() as i32);
+        gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_S, self.wrap_mode() as i32);
+        gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_T, self.wrap_mode() as i32);
+        let exts = gl.supported_extensions();
+        if exts.contains("GL_EXT_texture_filter_anisotropic") || exts.contains("GL_ARB_texture_filter_anisotropic") {
+            // 1.0 is off, set it anyway so set_sampler can turn it back off
+            let max = gl.get_parameter_f32(glow::MAX_TEXTURE_MAX_ANISOTROPY_EXT);
+            gl.tex_parameter_f32(glow::TEXTURE_2D, glow::TEXTURE_MAX_ANISOTROPY_EXT, self.anisotropy.unwrap_or(1.0).clamp(1.0, max.max(1.0)));
+        }
+        if self.generates_mipmaps() {
+            gl.generate_mipmap(glow::TEXTURE_2D);
+        }
+    }
+}
+
+/// an RGBA8 texture, independent of any program. Bind it with ProgramXYZRGBAUV::bind_texture.
+pub struct Texture<G: HasContext = glow::Context> {
+    pub texture: G::Texture,
+    pub w: usize,
+    pub h: usize,
+    pub sampler: SamplerDesc,
+    leak: LeakCheck,
+}
+
+impl<G: HasContext> Texture<G> {
+    /// # Safety
+    /// gl has to be current. Changes the TEXTURE_2D binding on the active unit
+    pub unsafe fn new(gl: &G, image: &ImageBuffer, sampler: &SamplerDesc) -> Result<Self, GlowMeshError> {
+        let texture = gl.create_texture().map_err(GlowMeshError::ResourceCreation)?;
+        gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+        gl.tex_image_2d(glow::TEXTURE_2D, 0, glow::RGBA as i32, image.w as i32, image.h as i32, 0, glow::RGBA, glow::UNSIGNED_BYTE, Some(&image.data));
+        sampler.apply(gl);
+
+        Ok(Texture {
+            texture,
+            w: image.w,
+            h: image.h,
+            sampler: *sampler,
+            leak: LeakCheck::new("Texture"),
+        })
+    }
+    /// uninitialized contents, eg. to render into
+    ///
+    /// # Safety
+    /// gl has to be current. Changes the TEXTURE_2D binding on the active unit
+    pub unsafe fn empty(gl: &G, w: usize, h: usize, sampler: &SamplerDesc) -> Result<Self, GlowMeshError> {
+        let texture = gl.create_texture().map_err(GlowMeshError::ResourceCreation)?;
+        gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+        gl.tex_image_2d(glow::TEXTURE_2D, 0, glow::RGBA as i32, w as i32, h as i32, 0, glow::RGBA, glow::UNSIGNED_BYTE, None);
+        sampler.apply(gl);
+
+        Ok(Texture {
+            texture,
+            w,
+            h,
+            sampler: *sampler,
+            leak: LeakCheck::new("Texture"),
+        })
+    }
+    /// change filtering / wrapping after creation
+    ///
+    /// # Safety
+    /// gl has to be the context the texture was made on, and current
+    pub unsafe fn set_sampler(&mut self, gl: &G, sampler: &SamplerDesc) {
+        gl.bind_texture(glow::TEXTURE_2D, Some(self.texture));
+        sampler.apply(gl);
+        self.sampler = *sampler;
+    }
+    /// overwrite the rectangle at x, y with image. Has to fit inside the texture. Mipmaps are regenerated if there are any.
+    ///
+    /// # Safety
+    /// gl has to be the context the texture was made on, and current. x + image.w and y + image.h have to be within the texture
+    pub unsafe fn update(&self, gl: &G, x: usize, y: usize, image: &ImageBuffer) {
+        assert!(x + image.w <= self.w && y + image.h <= self.h, "Texture::update: {}x{} at {},{} doesnt fit in {}x{}", image.w, image.h, x, y, self.w, self.h);
+        gl.bind_texture(glow::TEXTURE_2D, Some(self.texture));
+        gl.tex_sub_image_2d(glow::TEXTURE_2D, 0, x as i32, y as i32, image.w as i32, image.h as i32, glow::RGBA, glow::UNSIGNED_BYTE, glow::PixelUnpackData::Slice(&image.data));
+        if self.sampler.generates_mipmaps() {
+            gl.generate_mipmap(glow::TEXTURE_2D);
+        }
+    }
+    /// bind to texture unit `unit`, ie. glow::TEXTURE0 + unit
+    ///
+    /// # Safety
+    /// gl has to be the context the texture was made on, and current. unit has to be below MAX_COMBINED_TEXTURE_IMAGE_UNITS
+    pub unsafe fn bind(&self, gl: &G, unit: u32) {
+        gl.active_texture(glow::TEXTURE0 + unit);
+        gl.bind_texture(glow::TEXTURE_2D, Some(self.texture));
+    }
+    pub fn free(&self, gl: &G) {
+        unsafe {
+            gl.delete_texture(self.texture);
+        }
+        self.leak.freed();
+    }
+}
+
+impl<G: HasContext> Free<G> for Texture<G> {
+    fn free(&self, gl: &G) {
+        Texture::free(self, gl)
+    }
+}
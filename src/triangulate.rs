@@ -0,0 +1,555 @@
+//! Triangulating arbitrary simple polygons, concave and with holes, by ear clipping.
+//! Holes get bridged into the outer ring first so it's all one ring, then ears are cut off one at a time.
+//! When no ear can be found (self touching or slightly self intersecting input) it falls back to
+//! cutting out local self intersections, then to splitting the ring along a diagonal and starting again on
+//! both halves, so it always finishes with something sensible. This follows mapbox's earcut.
+//!
+//! Rings can go either way round, points are in whatever units, and the first point doesnt need repeating at the end.
+
+// Ported from earcut, https://github.com/mapbox/earcut, which is under the ISC licence:
+//
+// ISC License
+//
+// Copyright (c) 2016, Mapbox
+//
+// Permission to use, copy, modify, and/or distribute this software for any purpose
+// with or without fee is hereby granted, provided that the above copyright notice
+// and this permission notice appear in all copies.
+//
+// THE SOFTWARE IS PROVIDED "AS IS" AND ISC DISCLAIMS ALL WARRANTIES WITH REGARD TO
+// THIS SOFTWARE INCLUDING ALL IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS.
+// IN NO EVENT SHALL ISC BE LIABLE FOR ANY SPECIAL, DIRECT, INDIRECT, OR CONSEQUENTIAL
+// DAMAGES OR ANY DAMAGES WHATSOEVER RESULTING FROM LOSS OF USE, DATA OR PROFITS,
+// WHETHER IN AN ACTION OF CONTRACT, NEGLIGENCE OR OTHER TORTIOUS ACTION, ARISING OUT OF
+// OR IN CONNECTION WITH THE USE OR PERFORMANCE OF THIS SOFTWARE.
+
+use minvect::*;
+use crate::error::*;
+
+/// triangles covering outer minus the holes. A ring with its points all in a line is an error,
+/// GlowMeshError::DegenerateRing, with ring 0 being outer and 1.. the holes
+pub fn triangulate(outer: &[Vec2], holes: &[&[Vec2]]) -> Result<Vec<[Vec2; 3]>, GlowMeshError> {
+    for (ring, points) in std::iter::once(outer).chain(holes.iter().copied()).enumerate() {
+        if is_degenerate(points) {
+            return Err(GlowMeshError::DegenerateRing { ring });
+        }
+    }
+    let mut e = Earcut { nodes: vec![], triangles: vec![] };
+    let mut start = 0;
+    let outer_node = match e.linked_list(outer, start, true) {
+        Some(node) => node,
+        None => return Ok(vec![]),
+    };
+    start += outer.len();
+    let mut hole_nodes = vec![];
+    for hole in holes {
+        if let Some(node) = e.linked_list(hole, start, false) {
+            hole_nodes.push(e.leftmost(node));
+        }
+        start += hole.len();
+    }
+    hole_nodes.sort_by(|&a, &b| e.nodes[a].p.x.total_cmp(&e.nodes[b].p.x));
+    let mut outer_node = outer_node;
+    for hole in hole_nodes {
+        outer_node = e.eliminate_hole(hole, outer_node);
+    }
+    e.earcut_linked(outer_node, 0);
+    Ok(e.triangles)
+}
+
+/// fewer than 3 points, something not finite, or all the points in a line
+pub(crate) fn is_degenerate(points: &[Vec2]) -> bool {
+    if points.len() < 3 || points.iter().any(|p| !p.x.is_finite() || !p.y.is_finite()) {
+        return true;
+    }
+    let a = points[0];
+    let far = points.iter().map(|&p| p - a).fold(vec2(0.0, 0.0), |far, d| if d.dot(d) > far.dot(far) { d } else { far });
+    let len2 = far.dot(far);
+    len2 == 0.0 || points.iter().all(|&p| far.cross(p - a).abs() <= 4.0 * f32::EPSILON * len2)
+}
+
+/// twice the area, positive for clockwise with y up
+pub(crate) fn signed_area(points: &[Vec2]) -> f32 {
+    let mut sum = 0.0;
+    let mut j = points.len() - 1;
+    for i in 0..points.len() {
+        sum += (points[j].x - points[i].x) * (points[i].y + points[j].y);
+        j = i;
+    }
+    sum
+}
+
+struct Node {
+    /// which input point, counting through outer then the holes
+    i: usize,
+    p: Vec2,
+    prev: usize,
+    next: usize,
+}
+
+/// the rings as circular linked lists of nodes. Splitting duplicates nodes, removing just unlinks them
+struct Earcut {
+    nodes: Vec<Node>,
+    triangles: Vec<[Vec2; 3]>,
+}
+
+/// positive if p, q, r turns one way, negative the other, 0 if they're in a line
+fn area(p: Vec2, q: Vec2, r: Vec2) -> f32 {
+    (q.y - p.y) * (r.x - q.x) - (q.x - p.x) * (r.y - q.y)
+}
+
+fn point_in_triangle(a: Vec2, b: Vec2, c: Vec2, p: Vec2) -> bool {
+    (c.x - p.x) * (a.y - p.y) >= (a.x - p.x) * (c.y - p.y)
+        && (a.x - p.x) * (b.y - p.y) >= (b.x - p.x) * (a.y - p.y)
+        && (b.x - p.x) * (c.y - p.y) >= (c.x - p.x) * (b.y - p.y)
+}
+
+fn sign(x: f32) -> i32 {
+    if x > 0.0 { 1 } else if x < 0.0 { -1 } else { 0 }
+}
+
+/// q is within p..r's bounding box, for when they're in a line
+fn on_segment(p: Vec2, q: Vec2, r: Vec2) -> bool {
+    q.x <= p.x.max(r.x) && q.x >= p.x.min(r.x) && q.y <= p.y.max(r.y) && q.y >= p.y.min(r.y)
+}
+
+/// segments p1 q1 and p2 q2 cross or touch
+fn intersects(p1: Vec2, q1: Vec2, p2: Vec2, q2: Vec2) -> bool {
+    let o1 = sign(area(p1, q1, p2));
+    let o2 = sign(area(p1, q1, q2));
+    let o3 = sign(area(p2, q2, p1));
+    let o4 = sign(area(p2, q2, q1));
+    (o1 != o2 && o3 != o4)
+        || (o1 == 0 && on_segment(p1, p2, q1))
+        || (o2 == 0 && on_segment(p1, q2, q1))
+        || (o3 == 0 && on_segment(p2, p1, q2))
+        || (o4 == 0 && on_segment(p2, q1, q2))
+}
+
+impl Earcut {
+    fn p(&self, n: usize) -> Vec2 {
+        self.nodes[n].p
+    }
+    fn prev(&self, n: usize) -> usize {
+        self.nodes[n].prev
+    }
+    fn next(&self, n: usize) -> usize {
+        self.nodes[n].next
+    }
+    fn area3(&self, a: usize, b: usize, c: usize) -> f32 {
+        area(self.p(a), self.p(b), self.p(c))
+    }
+
+    fn insert(&mut self, i: usize, p: Vec2, last: Option<usize>) -> usize {
+        let n = self.nodes.len();
+        match last {
+            None => self.nodes.push(Node { i, p, prev: n, next: n }),
+            Some(last) => {
+                let next = self.next(last);
+                self.nodes.push(Node { i, p, prev: last, next });
+                self.nodes[next].prev = n;
+                self.nodes[last].next = n;
+            },
+        }
+        n
+    }
+    fn remove(&mut self, n: usize) {
+        let (prev, next) = (self.prev(n), self.next(n));
+        self.nodes[next].prev = prev;
+        self.nodes[prev].next = next;
+    }
+
+    /// ring as a linked list going the requested way round, first index being start
+    fn linked_list(&mut self, points: &[Vec2], start: usize, clockwise: bool) -> Option<usize> {
+        let mut last = None;
+        if clockwise == (signed_area(points) > 0.0) {
+            for (i, &p) in points.iter().enumerate() {
+                last = Some(self.insert(start + i, p, last));
+            }
+        } else {
+            for (i, &p) in points.iter().enumerate().rev() {
+                last = Some(self.insert(start + i, p, last));
+            }
+        }
+        let last = last?;
+        if self.p(last) == self.p(self.next(last)) {
+            let next = self.next(last);
+            self.remove(last);
+            return Some(next);
+        }
+        Some(last)
+    }
+
+    /// unlink repeated and collinear points from start round to end
+    fn filter_points(&mut self, start: usize, end: Option<usize>) -> usize {
+        let mut end = end.unwrap_or(start);
+        let mut p = start;
+        loop {
+            let mut again = false;
+            let (prev, next) = (self.prev(p), self.next(p));
+            if self.p(p) == self.p(next) || self.area3(prev, p, next) == 0.0 {
+                self.remove(p);
+                p = prev;
+                end = prev;
+                if p == self.next(p) {
+                    break;
+                }
+                again = true;
+            } else {
+                p = next;
+            }
+            if !again && p == end {
+                break;
+            }
+        }
+        end
+    }
+
+    /// pass 0 cuts plain ears, 1 also cures local self intersections, 2 splits the ring in two
+    fn earcut_linked(&mut self, ear: usize, pass: u32) {
+        let mut ear = ear;
+        let mut stop = ear;
+        while self.prev(ear) != self.next(ear) {
+            let (prev, next) = (self.prev(ear), self.next(ear));
+            if self.is_ear(ear) {
+                self.triangles.push([self.p(prev), self.p(ear), self.p(next)]);
+                self.remove(ear);
+                ear = self.next(next);
+                stop = ear;
+                continue;
+            }
+            ear = next;
+            if ear == stop {
+                match pass {
+                    0 => {
+                        let ear = self.filter_points(ear, None);
+                        self.earcut_linked(ear, 1);
+                    },
+                    1 => {
+                        let ear = self.filter_points(ear, None);
+                        let ear = self.cure_local_intersections(ear);
+                        self.earcut_linked(ear, 2);
+                    },
+                    _ => self.split_earcut(ear),
+                }
+                break;
+            }
+        }
+    }
+
+    fn is_ear(&self, ear: usize) -> bool {
+        let (a, c) = (self.prev(ear), self.next(ear));
+        let (pa, pb, pc) = (self.p(a), self.p(ear), self.p(c));
+        if area(pa, pb, pc) >= 0.0 {
+            // reflex
+            return false;
+        }
+        let lo = vec2(pa.x.min(pb.x).min(pc.x), pa.y.min(pb.y).min(pc.y));
+        let hi = vec2(pa.x.max(pb.x).max(pc.x), pa.y.max(pb.y).max(pc.y));
+        let mut p = self.next(c);
+        while p != a {
+            let pp = self.p(p);
+            if pp.x >= lo.x && pp.x <= hi.x && pp.y >= lo.y && pp.y <= hi.y
+                && point_in_triangle(pa, pb, pc, pp)
+                && self.area3(self.prev(p), p, self.next(p)) >= 0.0 {
+                return false;
+            }
+            p = self.next(p);
+        }
+        true
+    }
+
+    /// where a, p, p.next, b has a and b crossing over, cut off the triangle a p b
+    fn cure_local_intersections(&mut self, start: usize) -> usize {
+        let mut start = start;
+        let mut p = start;
+        loop {
+            let a = self.prev(p);
+            let b = self.next(self.next(p));
+            if self.p(a) != self.p(b)
+                && intersects(self.p(a), self.p(p), self.p(self.next(p)), self.p(b))
+                && self.locally_inside(a, b)
+                && self.locally_inside(b, a) {
+                self.triangles.push([self.p(a), self.p(p), self.p(b)]);
+                let next = self.next(p);
+                self.remove(p);
+                self.remove(next);
+                p = b;
+                start = b;
+            }
+            p = self.next(p);
+            if p == start {
+                break;
+            }
+        }
+        self.filter_points(p, None)
+    }
+
+    /// find any valid diagonal, split along it and earcut each half
+    fn split_earcut(&mut self, start: usize) {
+        let mut a = start;
+        loop {
+            let mut b = self.next(self.next(a));
+            while b != self.prev(a) {
+                if self.nodes[a].i != self.nodes[b].i && self.is_valid_diagonal(a, b) {
+                    let c = self.split_polygon(a, b);
+                    let a = self.filter_points(a, Some(self.next(a)));
+                    let c = self.filter_points(c, Some(self.next(c)));
+                    self.earcut_linked(a, 0);
+                    self.earcut_linked(c, 0);
+                    return;
+                }
+                b = self.next(b);
+            }
+            a = self.next(a);
+            if a == start {
+                break;
+            }
+        }
+    }
+
+    fn leftmost(&self, start: usize) -> usize {
+        let mut p = start;
+        let mut leftmost = start;
+        loop {
+            let (pp, lp) = (self.p(p), self.p(leftmost));
+            if pp.x < lp.x || (pp.x == lp.x && pp.y < lp.y) {
+                leftmost = p;
+            }
+            p = self.next(p);
+            if p == start {
+                break;
+            }
+        }
+        leftmost
+    }
+
+    /// join the hole to the outer ring with a pair of coincident edges
+    fn eliminate_hole(&mut self, hole: usize, outer: usize) -> usize {
+        let bridge = match self.find_hole_bridge(hole, outer) {
+            Some(bridge) => bridge,
+            None => return outer,
+        };
+        let bridge_reverse = self.split_polygon(bridge, hole);
+        self.filter_points(bridge_reverse, Some(self.next(bridge_reverse)));
+        self.filter_points(bridge, Some(self.next(bridge)))
+    }
+
+    /// an outer ring node the hole's leftmost point can see, by shooting a ray left from it
+    fn find_hole_bridge(&self, hole: usize, outer: usize) -> Option<usize> {
+        let h = self.p(hole);
+        let mut qx = f32::NEG_INFINITY;
+        let mut m = None;
+        let mut p = outer;
+        loop {
+            let (pp, np) = (self.p(p), self.p(self.next(p)));
+            if h.y <= pp.y && h.y >= np.y && np.y != pp.y {
+                let x = pp.x + (h.y - pp.y) * (np.x - pp.x) / (np.y - pp.y);
+                if x <= h.x && x > qx {
+                    qx = x;
+                    let candidate = if pp.x < np.x { p } else { self.next(p) };
+                    if x == h.x {
+                        return Some(candidate);
+                    }
+                    m = Some(candidate);
+                }
+            }
+            p = self.next(p);
+            if p == outer {
+                break;
+            }
+        }
+        let mut m = m?;
+
+        // something in the triangle between the hole, the ray hit and m might block the view, so take
+        // the point in there with the smallest angle to the ray
+        let stop = m;
+        let mp = self.p(m);
+        let mut tan_min = f32::INFINITY;
+        let mut p = m;
+        loop {
+            let pp = self.p(p);
+            let (a, c) = if h.y < mp.y { (vec2(h.x, h.y), vec2(qx, h.y)) } else { (vec2(qx, h.y), vec2(h.x, h.y)) };
+            if h.x >= pp.x && pp.x >= mp.x && h.x != pp.x && point_in_triangle(a, mp, c, pp) {
+                let tan = (h.y - pp.y).abs() / (h.x - pp.x);
+                let best = self.p(m);
+                if self.locally_inside(p, hole)
+                    && (tan < tan_min || (tan == tan_min && (pp.x > best.x || (pp.x == best.x && self.sector_contains_sector(m, p))))) {
+                    m = p;
+                    tan_min = tan;
+                }
+            }
+            p = self.next(p);
+            if p == stop {
+                break;
+            }
+        }
+        Some(m)
+    }
+
+    /// m's corner contains p's, so p is the better bridge of the two when they're in the same place
+    fn sector_contains_sector(&self, m: usize, p: usize) -> bool {
+        self.area3(self.prev(m), m, self.prev(p)) < 0.0 && self.area3(self.next(p), m, self.next(m)) < 0.0
+    }
+
+    fn is_valid_diagonal(&self, a: usize, b: usize) -> bool {
+        let bi = self.nodes[b].i;
+        self.nodes[self.next(a)].i != bi && self.nodes[self.prev(a)].i != bi && !self.intersects_polygon(a, b)
+            && ((self.locally_inside(a, b) && self.locally_inside(b, a) && self.middle_inside(a, b)
+                // doesnt make corners facing opposite ways
+                && (self.area3(self.prev(a), a, self.prev(b)) != 0.0 || self.area3(a, self.prev(b), b) != 0.0))
+                // zero length diagonal between two convex corners in the same place
+                || (self.p(a) == self.p(b)
+                    && self.area3(self.prev(a), a, self.next(a)) > 0.0
+                    && self.area3(self.prev(b), b, self.next(b)) > 0.0))
+    }
+
+    fn intersects_polygon(&self, a: usize, b: usize) -> bool {
+        let (ai, bi) = (self.nodes[a].i, self.nodes[b].i);
+        let mut p = a;
+        loop {
+            let next = self.next(p);
+            let (pi, ni) = (self.nodes[p].i, self.nodes[next].i);
+            if pi != ai && ni != ai && pi != bi && ni != bi && intersects(self.p(p), self.p(next), self.p(a), self.p(b)) {
+                return true;
+            }
+            p = next;
+            if p == a {
+                return false;
+            }
+        }
+    }
+
+    /// the diagonal a b starts off going into the inside of the ring at a
+    fn locally_inside(&self, a: usize, b: usize) -> bool {
+        let (prev, next) = (self.prev(a), self.next(a));
+        if self.area3(prev, a, next) < 0.0 {
+            self.area3(a, b, next) >= 0.0 && self.area3(a, prev, b) >= 0.0
+        } else {
+            self.area3(a, b, prev) < 0.0 || self.area3(a, next, b) < 0.0
+        }
+    }
+
+    /// the middle of a b is inside the ring, by counting crossings
+    fn middle_inside(&self, a: usize, b: usize) -> bool {
+        let m = (self.p(a) + self.p(b)) / 2.0;
+        let mut inside = false;
+        let mut p = a;
+        loop {
+            let (pp, np) = (self.p(p), self.p(self.next(p)));
+            if (pp.y > m.y) != (np.y > m.y) && np.y != pp.y && m.x < (np.x - pp.x) * (m.y - pp.y) / (np.y - pp.y) + pp.x {
+                inside = !inside;
+            }
+            p = self.next(p);
+            if p == a {
+                return inside;
+            }
+        }
+    }
+
+    /// link a straight to b, and a copy of each the other way round so the ring becomes two. Returns b's copy
+    fn split_polygon(&mut self, a: usize, b: usize) -> usize {
+        let (an, bp) = (self.next(a), self.prev(b));
+        let a2 = self.nodes.len();
+        let b2 = a2 + 1;
+        let (ai, ap, bi, bpos) = (self.nodes[a].i, self.p(a), self.nodes[b].i, self.p(b));
+        self.nodes.push(Node { i: ai, p: ap, prev: b2, next: an});
+        self.nodes.push(Node { i: bi, p: bpos, prev: bp, next: a2});
+        self.nodes[a].next = b;
+        self.nodes[b].prev = a;
+        self.nodes[an].prev = a2;
+        self.nodes[bp].next = b2;
+        b2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring(points: &[(f32, f32)]) -> Vec<Vec2> {
+        points.iter().map(|&(x, y)| vec2(x, y)).collect()
+    }
+
+    fn triangles_area(tris: &[[Vec2; 3]]) -> f32 {
+        tris.iter().map(|t| area(t[0], t[1], t[2]).abs() / 2.0).sum()
+    }
+
+    fn ring_area(points: &[Vec2]) -> f32 {
+        signed_area(points).abs() / 2.0
+    }
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() <= 1e-4 * b.abs().max(1.0), "{} != {}", a, b);
+    }
+
+    #[test]
+    fn concave() {
+        // a comb, ears only at the teeth
+        let comb = ring(&[(0.0, 0.0), (7.0, 0.0), (7.0, 3.0), (6.0, 3.0), (6.0, 1.0), (5.0, 1.0), (5.0, 3.0), (4.0, 3.0), (4.0, 1.0), (3.0, 1.0), (3.0, 3.0), (2.0, 3.0), (2.0, 1.0), (1.0, 1.0), (1.0, 3.0), (0.0, 3.0)]);
+        let tris = triangulate(&comb, &[]).unwrap();
+        assert_eq!(tris.len(), comb.len() - 2);
+        assert_close(triangles_area(&tris), ring_area(&comb));
+
+        // same the other way round
+        let reversed: Vec<Vec2> = comb.iter().rev().copied().collect();
+        assert_close(triangles_area(&triangulate(&reversed, &[]).unwrap()), ring_area(&comb));
+
+        let star: Vec<Vec2> = (0..10).map(|i| {
+            let r = if i % 2 == 0 { 5.0 } else { 2.0 };
+            let a = i as f32 * std::f32::consts::PI / 5.0;
+            vec2(r * a.cos(), r * a.sin())
+        }).collect();
+        assert_close(triangles_area(&triangulate(&star, &[]).unwrap()), ring_area(&star));
+    }
+
+    #[test]
+    fn holes() {
+        let outer = ring(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]);
+        let a = ring(&[(1.0, 1.0), (4.0, 1.0), (4.0, 4.0), (1.0, 4.0)]);
+        // holes can go either way round too
+        let b = ring(&[(6.0, 6.0), (6.0, 9.0), (9.0, 9.0), (9.0, 6.0)]);
+        let c = ring(&[(6.0, 1.0), (9.0, 2.0), (7.0, 4.0)]);
+        let tris = triangulate(&outer, &[&a, &b, &c]).unwrap();
+        assert_close(triangles_area(&tris), 100.0 - 9.0 - 9.0 - ring_area(&c));
+        // nothing inside a hole
+        let centroid = |t: &[Vec2; 3]| (t[0] + t[1] + t[2]) / 3.0;
+        assert!(tris.iter().all(|t| {
+            let m = centroid(t);
+            !(m.x > 1.0 && m.x < 4.0 && m.y > 1.0 && m.y < 4.0) && !(m.x > 6.0 && m.x < 9.0 && m.y > 6.0 && m.y < 9.0)
+        }));
+    }
+
+    #[test]
+    fn degenerate_rings() {
+        let square = ring(&[(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)]);
+        let line = ring(&[(0.0, 0.0), (1.0, 1.0), (3.0, 3.0), (2.0, 2.0)]);
+        assert!(matches!(triangulate(&line, &[]), Err(GlowMeshError::DegenerateRing { ring: 0 })));
+        assert!(matches!(triangulate(&square, &[&square, &line]), Err(GlowMeshError::DegenerateRing { ring: 2 })));
+        assert!(matches!(triangulate(&square[..2], &[]), Err(GlowMeshError::DegenerateRing { ring: 0 })));
+        let nan = ring(&[(0.0, 0.0), (4.0, f32::NAN), (4.0, 4.0)]);
+        assert!(matches!(triangulate(&nan, &[]), Err(GlowMeshError::DegenerateRing { ring: 0 })));
+
+        // collinear runs and repeated points in an otherwise fine ring are just dropped
+        let runs = ring(&[(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (2.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0), (0.0, 2.0)]);
+        assert_close(triangles_area(&triangulate(&runs, &[]).unwrap()), 16.0);
+    }
+
+    #[test]
+    fn self_touching() {
+        // a notch in the top coming back to (2, 2) to go round a pocket below it, touching itself there.
+        // 16 less 4 for the notch and 1 for the pocket
+        let pocket = ring(&[(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (2.0, 2.0), (3.0, 1.0), (1.0, 1.0), (2.0, 2.0), (0.0, 4.0)]);
+        assert_close(ring_area(&pocket), 11.0);
+        assert_close(triangles_area(&triangulate(&pocket, &[]).unwrap()), 11.0);
+
+        // two squares joined at a corner
+        let bowtie = ring(&[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (4.0, 2.0), (4.0, 4.0), (2.0, 4.0), (2.0, 2.0), (0.0, 2.0)]);
+        assert_close(triangles_area(&triangulate(&bowtie, &[]).unwrap()), 8.0);
+
+        // a hole touching the outside at a vertex
+        let outer = ring(&[(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0)]);
+        let hole = ring(&[(0.0, 2.0), (2.0, 1.0), (2.0, 3.0)]);
+        assert_close(triangles_area(&triangulate(&outer, &[&hole]).unwrap()), 14.0);
+    }
+}
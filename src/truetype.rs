@@ -0,0 +1,395 @@
+//! TrueType / OpenType text behind the `truetype` feature. Glyphs are rasterized with fontdue when they're first
+//! drawn and kept in a cache texture split into a grid of equal slots, least recently used glyphs getting
+//! evicted when it fills up. Glyphs drawn this frame are never evicted, so call next_frame once a frame.
+//!
+//! With sdf on, glyphs are rasterized once at a base size as signed distance fields and scaled to whatever size
+//! they're drawn at. They need SDF_FS instead of the default fragment shader:
+//! `ProgramXYZRGBAUV::new(gl, xyzrgbauv::DEFAULT_VS, truetype::SDF_FS)`.
+//!
+//! Like font, text goes in y down with pos at the top left of the first line. size is the font size in pixels.
+
+use glow::HasContext;
+use minimg::*;
+use minvect::*;
+use std::collections::HashMap;
+use crate::error::*;
+use crate::gc::*;
+use crate::texture::*;
+use crate::xyzrgbauv::*;
+
+/// rasterize glyphs as distance fields at base_px, spread pixels of distance either side of the edge
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SdfSettings {
+    pub base_px: f32,
+    pub spread: usize,
+}
+
+impl Default for SdfSettings {
+    fn default() -> Self {
+        SdfSettings { base_px: 48.0, spread: 6 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GlyphKey {
+    pub index: u16,
+    /// px * 64, so sizes can be hashed
+    pub px_64: u32,
+}
+
+/// where a glyph sits in the cache and how to place it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CachedGlyph {
+    pub slot: usize,
+    pub w: usize,
+    pub h: usize,
+    /// from the pen position on the baseline to the bitmap's top left, y down
+    pub offset_x: f32,
+    pub offset_y: f32,
+    last_used: u64,
+}
+
+/// the slot bookkeeping of a glyph cache without the texture
+pub struct GlyphCache {
+    pub w: usize,
+    pub h: usize,
+    pub slot_size: usize,
+    slots: Vec<Option<GlyphKey>>,
+    glyphs: HashMap<GlyphKey, CachedGlyph>,
+    frame: u64,
+}
+
+impl GlyphCache {
+    /// errors if not even one slot fits, slots being at least 3 pixels so theres room for the gutter
+    pub fn new(w: usize, h: usize, slot_size: usize) -> Result<Self, GlowMeshError> {
+        if slot_size < 3 || slot_size > w || slot_size > h {
+            return Err(GlowMeshError::GlyphCacheSize { w, h, slot_size });
+        }
+        let n = (w / slot_size) * (h / slot_size);
+        Ok(GlyphCache { w, h, slot_size, slots: vec![None; n], glyphs: HashMap::new(), frame: 0 })
+    }
+    pub fn next_frame(&mut self) {
+        self.frame += 1;
+    }
+    /// top left pixel of a slot
+    pub fn slot_xy(&self, slot: usize) -> (usize, usize) {
+        let cols = self.w / self.slot_size;
+        ((slot % cols) * self.slot_size, (slot / cols) * self.slot_size)
+    }
+    /// the cached glyph, marking it used this frame
+    pub fn get(&mut self, key: GlyphKey) -> Option<CachedGlyph> {
+        let frame = self.frame;
+        self.glyphs.get_mut(&key).map(|g| {
+            g.last_used = frame;
+            *g
+        })
+    }
+    /// find a slot for a w x h glyph, evicting the least recently used one if its full.
+    /// None if its too big for a slot or every slot was used this frame
+    pub fn insert(&mut self, key: GlyphKey, w: usize, h: usize, offset_x: f32, offset_y: f32) -> Option<CachedGlyph> {
+        // 1 pixel gutter so filtering doesnt pick up the next slot
+        if w + 2 > self.slot_size || h + 2 > self.slot_size {
+            return None;
+        }
+        let slot = match self.slots.iter().position(|s| s.is_none()) {
+            Some(free) => free,
+            None => {
+                let (slot, oldest) = self.slots.iter().enumerate()
+                    .filter_map(|(i, k)| k.map(|k| (i, self.glyphs[&k].last_used)))
+                    .min_by_key(|&(_, used)| used)?;
+                if oldest == self.frame {
+                    return None;
+                }
+                let evicted = self.slots[slot].take().unwrap();
+                self.glyphs.remove(&evicted);
+                slot
+            },
+        };
+        let glyph = CachedGlyph { slot, w, h, offset_x, offset_y, last_used: self.frame };
+        self.slots[slot] = Some(key);
+        self.glyphs.insert(key, glyph);
+        Some(glyph)
+    }
+    /// uvs of a cached glyph's bitmap
+    pub fn uv(&self, glyph: &CachedGlyph) -> Rect {
+        let (x, y) = self.slot_xy(glyph.slot);
+        rect(
+            (x + 1) as f32 / self.w as f32,
+            (y + 1) as f32 / self.h as f32,
+            glyph.w as f32 / self.w as f32,
+            glyph.h as f32 / self.h as f32,
+        )
+    }
+    pub fn len(&self) -> usize {
+        self.glyphs.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.glyphs.is_empty()
+    }
+}
+
+/// coverage (0-255, w x h) to a distance field padded by spread on every side: 0.5 on the edge, up to 1 inside
+pub fn sdf_from_coverage(coverage: &[u8], w: usize, h: usize, spread: usize) -> (Vec<u8>, usize, usize) {
+    let ow = w + 2 * spread;
+    let oh = h + 2 * spread;
+    let s = spread as isize;
+    let inside = |x: isize, y: isize| -> bool {
+        x >= s && y >= s && x < s + w as isize && y < s + h as isize && coverage[(y - s) as usize * w + (x - s) as usize] >= 128
+    };
+    let mut out = vec![0; ow * oh];
+    for y in 0..oh as isize {
+        for x in 0..ow as isize {
+            let here = inside(x, y);
+            // nearest pixel on the other side of the edge, within spread
+            let mut best = (spread * spread + 1) as isize;
+            for dy in -s..=s {
+                for dx in -s..=s {
+                    let d2 = dx * dx + dy * dy;
+                    if d2 < best && inside(x + dx, y + dy) != here {
+                        best = d2;
+                    }
+                }
+            }
+            // the edge is half way between the pixel centres
+            let d = ((best as f32).sqrt() - 0.5).min(spread as f32);
+            let signed = if here { d } else { -d };
+            out[y as usize * ow + x as usize] = ((0.5 + signed / (2.0 * spread as f32)).clamp(0.0, 1.0) * 255.0).round() as u8;
+        }
+    }
+    (out, ow, oh)
+}
+
+pub struct TrueTypeFont<G: HasContext = glow::Context> {
+    pub font: fontdue::Font,
+    pub texture: Texture<G>,
+    pub cache: GlyphCache,
+    pub sdf: Option<SdfSettings>,
+}
+
+impl<G: HasContext> TrueTypeFont<G> {
+    /// cache_size square texture split into slot_size slots, eg. 1024 and 64
+    ///
+    /// # Safety
+    /// gl has to be current
+    pub unsafe fn new(gl: &G, ttf: &[u8], cache_size: usize, slot_size: usize, sdf: Option<SdfSettings>) -> Result<Self, GlowMeshError> {
+        let font = fontdue::Font::from_bytes(ttf, fontdue::FontSettings::default())
+            .map_err(|e| GlowMeshError::FontLoad { what: "ttf".to_string(), message: e.to_string() })?;
+        let cache = GlyphCache::new(cache_size, cache_size, slot_size)?;
+        let mut blank = ImageBuffer::new(cache_size, cache_size);
+        // white with coverage in alpha, so the default shader's texture * col works
+        for px in blank.data.chunks_exact_mut(4) {
+            px.copy_from_slice(&[255, 255, 255, 0]);
+        }
+        let texture = Texture::new(gl, &blank, &SamplerDesc::linear())?;
+        Ok(TrueTypeFont { font, texture, cache, sdf })
+    }
+    pub fn next_frame(&mut self) {
+        self.cache.next_frame();
+    }
+    /// line height at size
+    pub fn line_height(&self, size: f32) -> f32 {
+        self.font.horizontal_line_metrics(size).map(|m| m.new_line_size).unwrap_or(size)
+    }
+    fn ascent(&self, size: f32) -> f32 {
+        self.font.horizontal_line_metrics(size).map(|m| m.ascent).unwrap_or(size)
+    }
+    /// px the glyph actually gets rasterized at
+    fn raster_px(&self, size: f32) -> f32 {
+        self.sdf.map(|s| s.base_px).unwrap_or(size)
+    }
+
+    /// rasterize and upload if it isnt cached
+    unsafe fn glyph(&mut self, gl: &G, index: u16, px: f32) -> Option<CachedGlyph> {
+        let key = GlyphKey { index, px_64: (px * 64.0).round() as u32 };
+        if let Some(g) = self.cache.get(key) {
+            return Some(g);
+        }
+        let (metrics, coverage) = self.font.rasterize_indexed(index, px);
+        let (bitmap, w, h, pad) = match self.sdf {
+            Some(sdf) => {
+                let (field, w, h) = sdf_from_coverage(&coverage, metrics.width, metrics.height, sdf.spread);
+                (field, w, h, sdf.spread as f32)
+            },
+            None => (coverage, metrics.width, metrics.height, 0.0),
+        };
+        let offset_x = metrics.xmin as f32 - pad;
+        let offset_y = -(metrics.ymin as f32 + metrics.height as f32) - pad;
+        let glyph = self.cache.insert(key, w, h, offset_x, offset_y)?;
+
+        // whole slot so whatever was evicted from it is gone
+        let slot = self.cache.slot_size;
+        let mut img = ImageBuffer::new(slot, slot);
+        for px in img.data.chunks_exact_mut(4) {
+            px.copy_from_slice(&[255, 255, 255, 0]);
+        }
+        for y in 0..h {
+            for x in 0..w {
+                img.data[((y + 1) * slot + x + 1) * 4 + 3] = bitmap[y * w + x];
+            }
+        }
+        let (sx, sy) = self.cache.slot_xy(glyph.slot);
+        self.texture.update(gl, sx, sy, &img);
+        Some(glyph)
+    }
+
+    /// append quads for s, rasterizing any glyphs that arent cached yet. Glyphs that dont fit in the cache are skipped
+    ///
+    /// # Safety
+    /// gl has to be the context the font was made on, and current. Changes the TEXTURE_2D binding when glyphs get rasterized
+    #[allow(clippy::too_many_arguments)]
+    pub unsafe fn put_text(&mut self, gl: &G, buf: &mut Vec<XYZRGBAUV>, pos: Vec2, size: f32, s: &str, col: Vec4, depth: f32) {
+        let px = self.raster_px(size);
+        let scale = size / px;
+        let line_height = self.line_height(size);
+        let ascent = self.ascent(size);
+        for (i, line) in s.split('\n').enumerate() {
+            let baseline = pos.y + ascent + i as f32 * line_height;
+            let mut x = pos.x;
+            let mut prev: Option<char> = None;
+            for c in line.chars() {
+                if let Some(p) = prev {
+                    x += self.font.horizontal_kern(p, c, size).unwrap_or(0.0);
+                }
+                prev = Some(c);
+                let index = self.font.lookup_glyph_index(c);
+                let metrics = self.font.metrics_indexed(index, size);
+                // blank ones like space dont need a slot
+                if metrics.width > 0 && metrics.height > 0 {
+                    if let Some(g) = self.glyph(gl, index, px) {
+                        // snap plain glyphs to pixels so they stay sharp
+                        let gx = if self.sdf.is_some() { x } else { x.round() };
+                        let dest = rect(gx + g.offset_x * scale, baseline + g.offset_y * scale, g.w as f32 * scale, g.h as f32 * scale);
+                        put_rect(buf, dest, self.cache.uv(&g), col, depth);
+                    }
+                }
+                x += metrics.advance_width;
+            }
+        }
+    }
+
+    /// width of the widest line and total height
+    pub fn measure_text(&self, size: f32, s: &str) -> Vec2 {
+        let mut w: f32 = 0.0;
+        let mut lines = 0;
+        for line in s.split('\n') {
+            let mut x = 0.0;
+            let mut prev: Option<char> = None;
+            for c in line.chars() {
+                if let Some(p) = prev {
+                    x += self.font.horizontal_kern(p, c, size).unwrap_or(0.0);
+                }
+                prev = Some(c);
+                x += self.font.metrics(c, size).advance_width;
+            }
+            w = w.max(x);
+            lines += 1;
+        }
+        vec2(w, lines as f32 * self.line_height(size))
+    }
+
+    pub fn free(&self, gl: &G) {
+        self.texture.free(gl);
+    }
+}
+
+impl<G: HasContext> Free<G> for TrueTypeFont<G> {
+    fn free(&self, gl: &G) {
+        TrueTypeFont::free(self, gl)
+    }
+}
+
+/// fragment shader for sdf glyphs: alpha is the field thresholded at the edge, smoothed over about a pixel
+pub const SDF_FS: &str = r#"#version 330 core
+in vec4 col;
+in vec2 uv;
+out vec4 frag_colour;
+
+uniform sampler2D tex;
+
+void main() {
+    float d = texture(tex, uv).a;
+    float w = max(fwidth(d), 0.0001);
+    float a = smoothstep(0.5 - w, 0.5 + w, d);
+    frag_colour = vec4(col.rgb, col.a * a);
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(index: u16) -> GlyphKey {
+        GlyphKey { index, px_64: 16 * 64 }
+    }
+
+    #[test]
+    fn cache_size_is_checked() {
+        assert!(matches!(GlyphCache::new(64, 64, 0), Err(GlowMeshError::GlyphCacheSize { slot_size: 0, .. })));
+        assert!(matches!(GlyphCache::new(64, 64, 2), Err(GlowMeshError::GlyphCacheSize { .. })));
+        assert!(matches!(GlyphCache::new(64, 32, 48), Err(GlowMeshError::GlyphCacheSize { .. })));
+        assert!(GlyphCache::new(64, 64, 64).is_ok());
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        // 4 slots, glyphs up to 2 x 2
+        let mut cache = GlyphCache::new(8, 8, 4).unwrap();
+        assert!(cache.insert(key(99), 3, 1, 0.0, 0.0).is_none());
+        for i in 0..4 {
+            assert_eq!(cache.insert(key(i), 2, 2, 0.0, 0.0).unwrap().slot, i as usize);
+            cache.next_frame();
+        }
+        // 1 is the oldest once 0 gets used again
+        assert!(cache.get(key(0)).is_some());
+        assert_eq!(cache.insert(key(4), 2, 2, 0.0, 0.0).unwrap().slot, 1);
+        assert!(cache.get(key(1)).is_none());
+        assert_eq!(cache.insert(key(5), 1, 1, 0.0, 0.0).unwrap().slot, 2);
+        assert_eq!(cache.insert(key(6), 1, 1, 0.0, 0.0).unwrap().slot, 3);
+        // everything was used this frame, nothing can go
+        assert!(cache.insert(key(7), 1, 1, 0.0, 0.0).is_none());
+        assert_eq!(cache.len(), 4);
+        assert!([0, 4, 5, 6].iter().all(|&i| cache.get(key(i)).is_some()));
+
+        cache.next_frame();
+        assert!(cache.get(key(5)).is_some());
+        assert_eq!(cache.insert(key(7), 1, 1, 0.0, 0.0).unwrap().slot, 0);
+        assert!(cache.get(key(0)).is_none());
+        let glyph = cache.get(key(7)).unwrap();
+        assert_eq!(cache.uv(&glyph), rect(1.0 / 8.0, 1.0 / 8.0, 1.0 / 8.0, 1.0 / 8.0));
+        assert_eq!(cache.slot_xy(3), (4, 4));
+    }
+
+    #[test]
+    fn sdf_of_a_square() {
+        // a 4 x 4 square in the middle of 8 x 8
+        let mut coverage = vec![0u8; 64];
+        for y in 2..6 {
+            for x in 2..6 {
+                coverage[y * 8 + x] = 255;
+            }
+        }
+        let spread = 3;
+        let (field, w, h) = sdf_from_coverage(&coverage, 8, 8, spread);
+        assert_eq!((w, h), (14, 14));
+        let at = |x: usize, y: usize| field[y * w + x];
+        // the square is now at 5..9, distances are in pixels from the edge half way between pixel centres
+        let expected = |d: f32| ((0.5 + d / (2.0 * spread as f32)) * 255.0).round() as u8;
+        assert_eq!(at(5, 7), expected(0.5));
+        assert_eq!(at(6, 7), expected(1.5));
+        assert_eq!(at(4, 7), expected(-0.5));
+        assert_eq!(at(2, 7), expected(-2.5));
+        // symmetric about the edge
+        assert_eq!(at(5, 7) as u32 + at(4, 7) as u32, 255);
+        for y in 0..h {
+            for x in 0..w {
+                let inside = (5..9).contains(&x) && (5..9).contains(&y);
+                assert_eq!(at(x, y) > 128, inside, "{},{}", x, y);
+            }
+        }
+        // further out is further, until spread runs out
+        for x in 2..5 {
+            assert!(at(x, 7) > at(x - 1, 7));
+        }
+        assert_eq!(at(0, 7), at(1, 7));
+    }
+}
@@ -0,0 +1,84 @@
+use glow::HasContext;
+
+/// one entry in a vertex layout, ie. one `layout (location = n) in ...` in the shader
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VertexAttrib {
+    pub location: u32,
+    /// number of components, 1-4
+    pub size: i32,
+    /// glow::FLOAT, glow::UNSIGNED_BYTE etc.
+    pub data_type: u32,
+    /// integer types get mapped to 0..1 floats in the shader
+    pub normalized: bool,
+    /// byte offset of the field within the vertex
+    pub offset: i32,
+}
+
+/// a vertex type that can be uploaded. Should be #[repr(C)] or #[repr(C, packed)] so the offsets mean something.
+/// Use #[derive(Vertex)] rather than implementing it by hand, it checks the layout.
+///
+/// # Safety
+/// the type has to have no padding, every byte of it is read when uploading
+///
+/// ```
+/// use glow_mesh::vertex::Vertex;
+/// use minvect::*;
+///
+/// #[derive(glow_mesh::Vertex)]
+/// #[repr(C)]
+/// struct V { pos: Vec2, col: [u8; 4], id: u32 }
+///
+/// assert_eq!(V::ATTRIBS.len(), 3);
+/// assert_eq!(V::ATTRIBS[1].offset, 8);
+/// assert!(V::ATTRIBS[1].normalized);
+/// ```
+///
+/// derive(Vertex) needs #[repr(C)]:
+/// ```compile_fail
+/// #[derive(glow_mesh::Vertex)]
+/// struct V { pos: [f32; 2] }
+/// ```
+///
+/// or a struct with padding:
+/// ```compile_fail
+/// #[derive(glow_mesh::Vertex)]
+/// #[repr(C)]
+/// struct V { pos: [f32; 3], col: [u8; 4], id: u16 }
+/// ```
+///
+/// and doesn't take generic structs:
+/// ```compile_fail
+/// #[derive(glow_mesh::Vertex)]
+/// #[repr(C)]
+/// struct V<T> { pos: [f32; 2], t: std::marker::PhantomData<T> }
+/// ```
+pub unsafe trait Vertex: Sized {
+    const ATTRIBS: &'static [VertexAttrib];
+}
+
+/// sets up attrib pointers for V on the currently bound vao + vbo
+///
+/// # Safety
+/// a vao and a vbo holding V have to be bound on gl
+pub unsafe fn bind_attribs<V: Vertex, G: HasContext>(gl: &G) {
+    let stride = std::mem::size_of::<V>() as i32;
+    for a in V::ATTRIBS {
+        if a.data_type == glow::FLOAT || a.normalized {
+            gl.vertex_attrib_pointer_f32(a.location, a.size, a.data_type, a.normalized, stride, a.offset);
+        } else {
+            gl.vertex_attrib_pointer_i32(a.location, a.size, a.data_type, stride, a.offset);
+        }
+        gl.enable_vertex_attrib_array(a.location);
+    }
+}
+
+/// view a vertex slice as bytes for uploading
+pub fn as_bytes<V: Vertex>(mesh: &[V]) -> &[u8] {
+    // fine to read every byte, Vertex types promise not to have padding
+    unsafe {
+        std::slice::from_raw_parts(
+            mesh.as_ptr() as *const u8,
+            std::mem::size_of_val(mesh),
+        )
+    }
+}
@@ -1,101 +1,79 @@
 use glow::HasContext;
 use minvect::*;
+use crate::program::*;
+use crate::mesh::*;
+use crate::error::*;
+use crate::gc::*;
+use crate::triangulate::*;
+use crate::path::*;
+use crate::Vertex;
 use std::f32::consts::PI;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Vertex)]
 #[repr(C, packed)]
 pub struct XYZRGBA {
     pub xyz: Vec3,
     pub rgba: Vec4,
 }
 
-pub struct ProgramXYZRGBA {
-    program: glow::NativeProgram,
+pub struct ProgramXYZRGBA<G: HasContext = glow::Context> {
+    program: Program<G>,
 }
 
-impl ProgramXYZRGBA {
-    pub unsafe fn new(gl: &glow::Context, vert: &str, frag: &str) -> Self {
-        let program = gl.create_program().expect("Cannot create program");
-    
-        let vs = gl.create_shader(glow::VERTEX_SHADER).expect("cannot create vertex shader");
-        gl.shader_source(vs, vert);
-        gl.compile_shader(vs);
-        if !gl.get_shader_compile_status(vs) {
-            panic!("{}", gl.get_shader_info_log(vs));
-        }
-        gl.attach_shader(program, vs);
-
-        let fs = gl.create_shader(glow::FRAGMENT_SHADER).expect("cannot create fragment shader");
-        gl.shader_source(fs, frag);
-        gl.compile_shader(fs);
-        if !gl.get_shader_compile_status(fs) {
-            panic!("{}", gl.get_shader_info_log(fs));
-        }
-        gl.attach_shader(program, fs);
-
-        gl.link_program(program);
-        if !gl.get_program_link_status(program) {
-            panic!("{}", gl.get_program_info_log(program));
-        }
-        gl.detach_shader(program, fs);
-        gl.delete_shader(fs);
-        gl.detach_shader(program, vs);
-        gl.delete_shader(vs);
-
-        ProgramXYZRGBA {
-            program
-        }
+impl<G: HasContext> ProgramXYZRGBA<G> {
+    /// # Safety
+    /// see Program::new
+    pub unsafe fn new(gl: &G, vert: &str, frag: &str) -> Result<Self, GlowMeshError> {
+        Ok(ProgramXYZRGBA {
+            program: Program::new(gl, vert, frag)?,
+        })
+    }
+    /// # Safety
+    /// see Program::from_source
+    pub unsafe fn from_source(gl: &G, source: &ProgramSource) -> Result<Self, GlowMeshError> {
+        Ok(ProgramXYZRGBA {
+            program: Program::from_source(gl, source)?,
+        })
     }
-    pub unsafe fn default(gl: &glow::Context) -> Self {
+    /// # Safety
+    /// see Program::new
+    pub unsafe fn default(gl: &G) -> Result<Self, GlowMeshError> {
         Self::new(gl, DEFAULT_VS, DEFAULT_FS)
     }
-    pub unsafe fn bind(&self, gl: &glow::Context) {
-        gl.use_program(Some(self.program))
+    /// see Program::poll_reload
+    ///
+    /// # Safety
+    /// see Program::poll_reload
+    pub unsafe fn poll_reload(&mut self, gl: &G) -> Result<bool, GlowMeshError> {
+        self.program.poll_reload(gl)
     }
-    pub unsafe fn set_proj(&self, proj: &[f32; 16], gl: &glow::Context) {
-        gl.uniform_matrix_4_f32_slice(gl.get_uniform_location(self.program, "projection").as_ref(), true, proj);
+    /// # Safety
+    /// see Program::bind
+    pub unsafe fn bind(&self, gl: &G) {
+        self.program.bind(gl)
+    }
+    /// # Safety
+    /// see Program::set_proj
+    pub unsafe fn set_proj(&self, proj: &[f32; 16], gl: &G) {
+        self.program.set_proj(proj, gl)
+    }
+    pub fn free(&self, gl: &G) {
+        self.program.free(gl)
     }
 }
 
-pub unsafe fn upload_xyzrgba_mesh(mesh: &[XYZRGBA], gl: &glow::Context) -> HandleXYZRGBA {
-    let vbo = gl.create_buffer().unwrap();
-    gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
-    let vao = gl.create_vertex_array().unwrap();
-    gl.bind_vertex_array(Some(vao));
-    let vert_size = std::mem::size_of::<XYZRGBA>();
-
-    gl.vertex_attrib_pointer_f32(0, 3, glow::FLOAT, false, vert_size as i32, 0);
-    gl.enable_vertex_attrib_array(0);
-    gl.vertex_attrib_pointer_f32(1, 4, glow::FLOAT, false, vert_size as i32, 3*4);
-    gl.enable_vertex_attrib_array(1);
-    
-    let vert_bytes: &[u8] = std::slice::from_raw_parts(
-        mesh.as_ptr() as *const u8,
-        mesh.len() * vert_size,
-    );
-    gl.buffer_data_u8_slice(glow::ARRAY_BUFFER, vert_bytes, glow::STATIC_DRAW);
-    HandleXYZRGBA {vao, vbo, num_verts: mesh.len()}
-}
-
-pub struct HandleXYZRGBA {
-    pub vao: glow::NativeVertexArray,
-    pub vbo: glow::NativeBuffer,
-    pub num_verts: usize,
+impl<G: HasContext> Free<G> for ProgramXYZRGBA<G> {
+    fn free(&self, gl: &G) {
+        ProgramXYZRGBA::free(self, gl)
+    }
 }
 
-impl HandleXYZRGBA {
-    pub unsafe fn render(&self, gl: &glow::Context) {
-        gl.bind_vertex_array(Some(self.vao));
-        gl.draw_arrays(glow::TRIANGLES, 0, self.num_verts as i32);
-    }
+pub type HandleXYZRGBA<G = glow::Context> = Mesh<XYZRGBA, G>;
 
-    /// definitely want to call this when dropping the buffer. not impling drop because how to reference opengl context hey?
-    pub fn free(&self, gl: &glow::Context) {
-        unsafe {
-            gl.delete_vertex_array(self.vao);
-            gl.delete_buffer(self.vbo);
-        }
-    }
+/// # Safety
+/// see upload_mesh
+pub unsafe fn upload_xyzrgba_mesh<G: HasContext>(mesh: &[XYZRGBA], gl: &G) -> Result<HandleXYZRGBA<G>, GlowMeshError> {
+    upload_mesh(mesh, gl)
 }
 
 pub const DEFAULT_FS: &str = r#"#version 330 core
@@ -148,8 +126,13 @@ pub fn put_rect(buf: &mut Vec<XYZRGBA>, r: Rect, col: Vec4, depth: f32) {
     put_quad(buf, a, b, c, d, col, depth);
 }
 
+/// thickness is how far each edge is from the centre line, so the line ends up 2 * thickness wide.
+/// Does nothing if p1 == p2. Use put_polyline for more than one segment so the corners join up
 pub fn put_line(buf: &mut Vec<XYZRGBA>, p1: Vec2, p2: Vec2, thickness: f32, col: Vec4, depth: f32) {
-    let v = (p2-p1).normalize();
+    let v = match unit(p2 - p1) {
+        Some(v) => v,
+        None => return,
+    };
     let u = thickness*vec2(-v.y, v.x);
     let a = p1 + u;
     let b = p2 + u;
@@ -169,8 +152,704 @@ pub fn put_poly(buf: &mut Vec<XYZRGBA>, c: Vec2, r: f32, n: usize, phase: f32, c
     }
 }
 
-pub fn transform_mesh(v: &mut Vec<XYZRGBA>, mat: &[f32; 16]) {
-    for i in 0..v.len() {
-        v[i].xyz = mat4_trans_homog(v[i].xyz, mat);
+/// fill any simple polygon, concave or with holes, eg. a region drawn by hand. Rings go either way round
+/// and can touch themselves. Nothing is added if a ring is degenerate, see triangulate
+pub fn put_polygon(buf: &mut Vec<XYZRGBA>, outer: &[Vec2], holes: &[&[Vec2]], col: Vec4, depth: f32) -> Result<(), GlowMeshError> {
+    for [a, b, c] in triangulate(outer, holes)? {
+        put_triangle(buf, a, b, c, col, depth);
+    }
+    Ok(())
+}
+
+pub fn put_triangle_indexed<I: Index>(buf: &mut IndexedMesh<XYZRGBA, I>, a: Vec2, b: Vec2, c: Vec2, col: Vec4, depth: f32) {
+    let ia = buf.push_vertex(XYZRGBA { xyz: vec3(a.x, a.y, depth), rgba: col });
+    let ib = buf.push_vertex(XYZRGBA { xyz: vec3(b.x, b.y, depth), rgba: col });
+    let ic = buf.push_vertex(XYZRGBA { xyz: vec3(c.x, c.y, depth), rgba: col });
+    buf.push_triangle(ia, ib, ic);
+}
+
+/// 4 vertices instead of put_quad's 6
+pub fn put_quad_indexed<I: Index>(buf: &mut IndexedMesh<XYZRGBA, I>, a: Vec2, b: Vec2, c: Vec2, d: Vec2, col: Vec4, depth: f32) {
+    let ia = buf.push_vertex(XYZRGBA { xyz: vec3(a.x, a.y, depth), rgba: col });
+    let ib = buf.push_vertex(XYZRGBA { xyz: vec3(b.x, b.y, depth), rgba: col });
+    let ic = buf.push_vertex(XYZRGBA { xyz: vec3(c.x, c.y, depth), rgba: col });
+    let id = buf.push_vertex(XYZRGBA { xyz: vec3(d.x, d.y, depth), rgba: col });
+    buf.push_triangle(ia, ib, ic);
+    buf.push_triangle(ia, ic, id);
+}
+
+pub fn put_rect_indexed<I: Index>(buf: &mut IndexedMesh<XYZRGBA, I>, r: Rect, col: Vec4, depth: f32) {
+    put_quad_indexed(buf, r.tl(), r.tr(), r.br(), r.bl(), col, depth);
+}
+
+/// n + 1 vertices instead of put_poly's 3n
+pub fn put_poly_indexed<I: Index>(buf: &mut IndexedMesh<XYZRGBA, I>, c: Vec2, r: f32, n: usize, phase: f32, col: Vec4, depth: f32) {
+    let dtheta = (2.0 * PI) / n as f32;
+    let centre = buf.push_vertex(XYZRGBA { xyz: vec3(c.x, c.y, depth), rgba: col });
+    let rim: Vec<I> = (0..n).map(|i| {
+        let theta = phase + dtheta * i as f32;
+        let p = c + r*vec2(theta.cos(), theta.sin());
+        buf.push_vertex(XYZRGBA { xyz: vec3(p.x, p.y, depth), rgba: col })
+    }).collect();
+    for i in 0..n {
+        buf.push_triangle(centre, rim[i], rim[(i + n - 1) % n]);
+    }
+}
+
+pub fn transform_mesh(v: &mut [XYZRGBA], mat: &[f32; 16]) {
+    for vert in v.iter_mut() {
+        vert.xyz = mat4_trans_homog(vert.xyz, mat);
+    }
+}
+
+/// v scaled to length 1, or None if it has no length. minvect's normalize divides by the squared length
+fn unit(v: Vec2) -> Option<Vec2> {
+    let len = v.dot(v).sqrt();
+    if len > 0.0 && len.is_finite() { Some(v / len) } else { None }
+}
+
+/// v turned a quarter turn, from +x towards +y
+fn perp(v: Vec2) -> Vec2 {
+    vec2(-v.y, v.x)
+}
+
+/// triangles from pivot to an arc around centre, starting at from and sweeping angle radians (positive goes from +x
+/// towards +y) to end up exactly at to
+#[allow(clippy::too_many_arguments)]
+fn put_arc_fan(buf: &mut Vec<XYZRGBA>, pivot: Vec2, centre: Vec2, from: Vec2, to: Vec2, angle: f32, tolerance: f32, col: Vec4, depth: f32) {
+    let r = from - centre;
+    let steps = arc_steps(r.dot(r).sqrt(), angle, tolerance);
+    let mut prev = from;
+    for i in 1..=steps {
+        let next = if i == steps {
+            to
+        } else {
+            let (s, c) = (angle * i as f32 / steps as f32).sin_cos();
+            centre + vec2(r.x * c - r.y * s, r.x * s + r.y * c)
+        };
+        put_triangle(buf, pivot, prev, next, col, depth);
+        prev = next;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Join {
+    /// corners stay sharp until they get longer than miter_limit, then bevel
+    Miter,
+    Round,
+    /// corners cut off flat
+    Bevel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cap {
+    /// stops flat at the end point
+    Butt,
+    /// flat, half the width past the end point
+    Square,
+    Round,
+}
+
+/// how put_polyline draws, with the same meanings as svg's stroke properties
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StrokeStyle {
+    /// full width, half on each side of the points
+    pub width: f32,
+    pub join: Join,
+    /// miter joins longer than this many widths become bevels
+    pub miter_limit: f32,
+    /// ends of open lines. Zero length lines get a dot if it's Square or Round
+    pub cap: Cap,
+    /// join the last point back to the first instead of capping
+    pub closed: bool,
+    /// how far round joins and caps can stray from a true circle, in the same units as the points
+    pub tolerance: f32,
+}
+
+impl Default for StrokeStyle {
+    /// 1 wide, miter joins limited to 4 like svg, butt caps, open, 0.25 tolerance which suits pixel units
+    fn default() -> Self {
+        StrokeStyle { width: 1.0, join: Join::Miter, miter_limit: 4.0, cap: Cap::Butt, closed: false, tolerance: 0.25 }
+    }
+}
+
+impl StrokeStyle {
+    pub fn new(width: f32) -> Self {
+        StrokeStyle { width, ..Default::default() }
+    }
+    pub fn with_join(self, join: Join) -> Self {
+        StrokeStyle { join, ..self }
+    }
+    pub fn with_miter_limit(self, miter_limit: f32) -> Self {
+        StrokeStyle { miter_limit, ..self }
+    }
+    pub fn with_cap(self, cap: Cap) -> Self {
+        StrokeStyle { cap, ..self }
+    }
+    pub fn with_closed(self, closed: bool) -> Self {
+        StrokeStyle { closed, ..self }
+    }
+    pub fn with_tolerance(self, tolerance: f32) -> Self {
+        StrokeStyle { tolerance, ..self }
+    }
+}
+
+/// stroke a line through points. Neighbouring segments share their corner vertices and the joins and caps
+/// fill in around them, so there are no cracks. Translucent lines can still double up where they cross
+/// themselves, or at sharp corners between segments shorter than the line is wide
+pub fn put_polyline(buf: &mut Vec<XYZRGBA>, points: &[Vec2], style: &StrokeStyle, col: Vec4, depth: f32) {
+    let hw = style.width * 0.5;
+    if hw <= 0.0 || !hw.is_finite() {
+        return;
+    }
+    // repeated points have no direction, so drop them
+    let mut pts: Vec<Vec2> = Vec::with_capacity(points.len());
+    for &p in points {
+        if pts.last() != Some(&p) {
+            pts.push(p);
+        }
+    }
+    if style.closed && pts.len() > 1 && pts.first() == pts.last() {
+        pts.pop();
+    }
+    match pts.len() {
+        0 => return,
+        1 => return put_dot(buf, pts[0], hw, style, col, depth),
+        _ => {},
+    }
+
+    let n = pts.len();
+    let num_segments = if style.closed { n } else { n - 1 };
+    let mut dirs = Vec::with_capacity(num_segments);
+    let mut lens = Vec::with_capacity(num_segments);
+    // (left, right) corners at each end of each segment
+    let mut starts = Vec::with_capacity(num_segments);
+    let mut ends = Vec::with_capacity(num_segments);
+    for i in 0..num_segments {
+        let (a, b) = (pts[i], pts[(i + 1) % n]);
+        let d = b - a;
+        let dir = unit(d).unwrap_or(vec2(1.0, 0.0));
+        let u = hw * perp(dir);
+        dirs.push(dir);
+        lens.push(d.dot(d).sqrt());
+        starts.push((a + u, a - u));
+        ends.push((b + u, b - u));
+    }
+
+    let joints = if style.closed { 0..n } else { 1..n - 1 };
+    for j in joints {
+        let (i0, i1) = ((j + num_segments - 1) % num_segments, j % num_segments);
+        let max_inset = 0.5 * lens[i0].min(lens[i1]);
+        let (end, start) = (&mut ends[i0], &mut starts[i1]);
+        put_join(buf, pts[j], dirs[i0], dirs[i1], hw, max_inset, style, end, start, col, depth);
+    }
+
+    if !style.closed {
+        let last = num_segments - 1;
+        let (d0, d1) = (dirs[0], dirs[last]);
+        match style.cap {
+            Cap::Butt => {},
+            Cap::Square => {
+                starts[0] = (starts[0].0 - hw * d0, starts[0].1 - hw * d0);
+                ends[last] = (ends[last].0 + hw * d1, ends[last].1 + hw * d1);
+            },
+            Cap::Round => {
+                put_arc_fan(buf, pts[0], pts[0], starts[0].1, starts[0].0, -PI, style.tolerance, col, depth);
+                put_arc_fan(buf, pts[n - 1], pts[n - 1], ends[last].0, ends[last].1, -PI, style.tolerance, col, depth);
+            },
+        }
+    }
+
+    for (s, e) in starts.iter().zip(ends.iter()) {
+        put_quad(buf, s.0, e.0, e.1, s.1, col, depth);
+    }
+}
+
+/// what a zero length line looks like with round or square caps
+fn put_dot(buf: &mut Vec<XYZRGBA>, p: Vec2, hw: f32, style: &StrokeStyle, col: Vec4, depth: f32) {
+    match style.cap {
+        Cap::Butt => {},
+        Cap::Square => put_rect(buf, rect(p.x - hw, p.y - hw, 2.0 * hw, 2.0 * hw), col, depth),
+        Cap::Round => {
+            let from = p + vec2(hw, 0.0);
+            put_arc_fan(buf, p, p, from, from, 2.0 * PI, style.tolerance, col, depth);
+        },
+    }
+}
+
+/// fill the corner at p between a segment going d0 and the next going d1. Where the inside edges meet
+/// within max_inset of p, both segments' inside corners move to that point so they don't overlap
+#[allow(clippy::too_many_arguments)]
+fn put_join(buf: &mut Vec<XYZRGBA>, p: Vec2, d0: Vec2, d1: Vec2, hw: f32, max_inset: f32, style: &StrokeStyle, end: &mut (Vec2, Vec2), start: &mut (Vec2, Vec2), col: Vec4, depth: f32) {
+    let cross = d0.cross(d1);
+    let dot = d0.dot(d1);
+    if cross.abs() < 1e-6 && dot > 0.0 {
+        // straight on
+        *start = *end;
+        return;
+    }
+    // +1 if the inside of the turn is on the left
+    let side = if cross > 0.0 { 1.0 } else { -1.0 };
+    let (n0, n1) = (perp(d0), perp(d1));
+    let outer0 = p - side * hw * n0;
+    let outer1 = p - side * hw * n1;
+    // n0 + n1 over this is the miter, 1 / cos(half the turn) long
+    let denom = 1.0 + dot;
+    let inset = if denom > 1e-6 { hw * cross.abs() / denom } else { f32::INFINITY };
+    let pivot = if inset <= max_inset {
+        let inner = p + side * hw * (n0 + n1) / denom;
+        if side > 0.0 {
+            end.0 = inner;
+            start.0 = inner;
+        } else {
+            end.1 = inner;
+            start.1 = inner;
+        }
+        inner
+    } else {
+        p
+    };
+    match style.join {
+        Join::Miter if denom > 1e-6 && (2.0 / denom).sqrt() <= style.miter_limit => {
+            let tip = p - side * hw * (n0 + n1) / denom;
+            put_triangle(buf, pivot, outer0, tip, col, depth);
+            put_triangle(buf, pivot, tip, outer1, col, depth);
+        },
+        Join::Miter | Join::Bevel => put_triangle(buf, pivot, outer0, outer1, col, depth),
+        Join::Round => {
+            let angle = side * cross.abs().atan2(dot);
+            put_arc_fan(buf, pivot, p, outer0, outer1, angle, style.tolerance, col, depth);
+        },
+    }
+}
+
+/// put_polyline but only the "on" parts of a dash pattern, like svg's stroke-dasharray and stroke-dashoffset.
+/// dashes alternates on and off lengths starting with on, and is repeated twice if it has an odd length.
+/// offset is how far into the pattern the line starts. The pattern follows the line around corners, and each
+/// dash gets the style's caps and joins, so dots are zero length dashes with round caps, eg. &[0.0, 4.0].
+/// An empty, negative or all zero pattern draws the line solid, and so do dashes too short to place along a line that long
+pub fn put_dashed_polyline(buf: &mut Vec<XYZRGBA>, points: &[Vec2], style: &StrokeStyle, dashes: &[f32], offset: f32, col: Vec4, depth: f32) {
+    let mut pattern = dashes.to_vec();
+    if pattern.len() % 2 == 1 {
+        pattern.extend_from_slice(dashes);
+    }
+    let total: f32 = pattern.iter().sum();
+    if pattern.iter().any(|&d| d < 0.0) || total <= 0.0 || !total.is_finite() || !offset.is_finite() {
+        return put_polyline(buf, points, style, col, depth);
+    }
+    let mut pts = points.to_vec();
+    if style.closed && pts.len() > 1 {
+        pts.push(points[0]);
+    }
+    if pts.is_empty() {
+        return;
     }
-}
\ No newline at end of file
+    let lens: Vec<f32> = pts.windows(2).map(|w| (w[1] - w[0]).dot(w[1] - w[0]).sqrt()).collect();
+    // dashes too short to place along a line this long in f32 would just be the line
+    let line_len: f32 = lens.iter().sum();
+    if pattern.iter().any(|&d| d > 0.0 && d < line_len * f32::EPSILON) {
+        return put_polyline(buf, points, style, col, depth);
+    }
+    let dash_style = StrokeStyle { closed: false, ..*style };
+
+    // where each dash or gap ends within the pattern
+    let ends: Vec<f32> = pattern.iter().scan(0.0, |sum, &d| {
+        *sum += d;
+        Some(*sum)
+    }).collect();
+    // find where offset lands in the pattern, k counts whole repeats
+    let (mut i, mut k) = (0, 0);
+    let phase = offset.rem_euclid(total);
+    while phase >= k as f32 * total + ends[i] && phase > 0.0 {
+        i += 1;
+        if i == pattern.len() {
+            i = 0;
+            k += 1;
+        }
+    }
+    // measured from the start of the line. Worked out from k rather than added up, so it always moves on
+    let end = |k: usize, i: usize| k as f32 * total + ends[i] - phase;
+    let mut dash = if i % 2 == 0 { vec![pts[0]] } else { vec![] };
+
+    let mut start = 0.0;
+    for (w, &len) in pts.windows(2).zip(&lens) {
+        if len == 0.0 {
+            continue;
+        }
+        let (a, b) = (w[0], w[1]);
+        while end(k, i) <= start + len {
+            dash.push(a + (b - a) * ((end(k, i) - start) / len).max(0.0));
+            if i % 2 == 0 {
+                put_polyline(buf, &dash, &dash_style, col, depth);
+                dash.clear();
+            }
+            i += 1;
+            if i == pattern.len() {
+                i = 0;
+                k += 1;
+            }
+        }
+        if i % 2 == 0 {
+            dash.push(b);
+        }
+        start += len;
+    }
+    if i % 2 == 0 {
+        put_polyline(buf, &dash, &dash_style, col, depth);
+    }
+}
+
+/// stroke a quadratic bezier, flattened to within style.tolerance
+pub fn put_quadratic(buf: &mut Vec<XYZRGBA>, p0: Vec2, p1: Vec2, p2: Vec2, style: &StrokeStyle, col: Vec4, depth: f32) {
+    let mut points = vec![p0];
+    flatten_quadratic(&mut points, p0, p1, p2, style.tolerance);
+    put_polyline(buf, &points, style, col, depth);
+}
+
+/// stroke a cubic bezier, flattened to within style.tolerance
+#[allow(clippy::too_many_arguments)]
+pub fn put_cubic(buf: &mut Vec<XYZRGBA>, p0: Vec2, p1: Vec2, p2: Vec2, p3: Vec2, style: &StrokeStyle, col: Vec4, depth: f32) {
+    let mut points = vec![p0];
+    flatten_cubic(&mut points, p0, p1, p2, p3, style.tolerance);
+    put_polyline(buf, &points, style, col, depth);
+}
+
+/// stroke part of an ellipse with radii turned by rotation, from angle start by sweep radians
+#[allow(clippy::too_many_arguments)]
+pub fn put_arc(buf: &mut Vec<XYZRGBA>, centre: Vec2, radii: Vec2, rotation: f32, start: f32, sweep: f32, style: &StrokeStyle, col: Vec4, depth: f32) {
+    let mut points = vec![ellipse_point(centre, radii, rotation, start)];
+    flatten_arc(&mut points, centre, radii, rotation, start, sweep, style.tolerance);
+    put_polyline(buf, &points, style, col, depth);
+}
+
+/// fill an ellipse with radii turned by rotation
+pub fn put_ellipse(buf: &mut Vec<XYZRGBA>, centre: Vec2, radii: Vec2, rotation: f32, tolerance: f32, col: Vec4, depth: f32) {
+    let from = ellipse_point(centre, radii, rotation, 0.0);
+    let mut points = vec![from];
+    flatten_arc(&mut points, centre, radii, rotation, 0.0, 2.0 * PI, tolerance);
+    *points.last_mut().unwrap() = from;
+    for w in points.windows(2) {
+        put_triangle(buf, centre, w[0], w[1], col, depth);
+    }
+}
+
+/// fill a path, curves flattened to within tolerance. Every subpath counts as closed
+pub fn put_path(buf: &mut Vec<XYZRGBA>, path: &Path, rule: FillRule, tolerance: f32, col: Vec4, depth: f32) {
+    for [a, b, c] in path.fill(rule, tolerance) {
+        put_triangle(buf, a, b, c, col, depth);
+    }
+}
+
+/// stroke each subpath of a path, closed ones joined up and open ones capped. Curves are flattened to within style.tolerance
+pub fn put_path_stroke(buf: &mut Vec<XYZRGBA>, path: &Path, style: &StrokeStyle, col: Vec4, depth: f32) {
+    for subpath in path.flatten(style.tolerance) {
+        put_polyline(buf, &subpath.points, &StrokeStyle { closed: subpath.closed, ..*style }, col, depth);
+    }
+}
+
+/// what put_svg_path does with the path
+#[derive(Debug, Clone, Copy)]
+pub enum PathStyle {
+    /// curves flattened to within tolerance
+    Fill { col: Vec4, tolerance: f32 },
+    Stroke { stroke: StrokeStyle, col: Vec4 },
+}
+
+/// fill or stroke svg path data, eg. a d attribute from Inkscape. rule only matters for filling. Nothing is added
+/// if d doesnt parse, see Path::from_svg
+pub fn put_svg_path(buf: &mut Vec<XYZRGBA>, d: &str, rule: FillRule, style: &PathStyle, depth: f32) -> Result<(), GlowMeshError> {
+    let path = Path::from_svg(d)?;
+    match *style {
+        PathStyle::Fill { col, tolerance } => put_path(buf, &path, rule, tolerance, col, depth),
+        PathStyle::Stroke { stroke, col } => put_path_stroke(buf, &path, &stroke, col, depth),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_render_matches;
+
+    const IDENTITY: [f32; 16] = [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0];
+    const RED: Vec4 = Vec4 { x: 1.0, y: 0.0, z: 0.0, w: 1.0 };
+    const GREEN: Vec4 = Vec4 { x: 0.0, y: 1.0, z: 0.0, w: 1.0 };
+    const BLUE: Vec4 = Vec4 { x: 0.0, y: 0.0, z: 1.0, w: 0.5 };
+
+    #[test]
+    fn put_line_snapshot() {
+        let mut buf = vec![];
+        put_line(&mut buf, vec2(-0.8, 0.6), vec2(0.8, 0.6), 0.02, RED, 0.0);
+        put_line(&mut buf, vec2(-0.8, -0.8), vec2(0.8, 0.4), 0.05, GREEN, 0.0);
+        put_line(&mut buf, vec2(-0.5, -0.9), vec2(-0.4, 0.9), 0.15, BLUE, 0.0);
+        assert_render_matches!(buf, IDENTITY, "snapshots/put_line.png", 64, 64);
+    }
+
+    #[test]
+    fn put_poly_snapshot() {
+        let mut buf = vec![];
+        put_poly(&mut buf, vec2(-0.5, 0.5), 0.4, 3, 0.0, RED, 0.0);
+        put_poly(&mut buf, vec2(0.5, 0.5), 0.4, 7, 0.3, GREEN, 0.0);
+        put_poly(&mut buf, vec2(0.0, -0.4), 0.5, 32, 0.0, BLUE, 0.0);
+        assert_render_matches!(buf, IDENTITY, "snapshots/put_poly.png", 64, 64);
+    }
+
+    #[test]
+    fn put_polyline_join_snapshots() {
+        // a right angle, an obtuse bend and a very sharp one, which falls back to a bevel with Miter
+        let points = [vec2(-0.8, -0.7), vec2(-0.8, 0.6), vec2(0.0, 0.2), vec2(0.6, 0.7), vec2(0.7, -0.8)];
+        for (join, name) in [(Join::Miter, "miter"), (Join::Round, "round"), (Join::Bevel, "bevel")] {
+            let mut buf = vec![];
+            put_polyline(&mut buf, &points, &StrokeStyle::new(0.2).with_join(join).with_tolerance(0.01), RED, 0.0);
+            assert_render_matches!(buf, IDENTITY, format!("snapshots/put_polyline_join_{}.png", name), 64, 64);
+        }
+    }
+
+    #[test]
+    fn put_polyline_cap_snapshots() {
+        let points = [vec2(-0.5, -0.6), vec2(-0.5, 0.3), vec2(0.5, 0.3)];
+        for (cap, name) in [(Cap::Butt, "butt"), (Cap::Square, "square"), (Cap::Round, "round")] {
+            let mut buf = vec![];
+            put_polyline(&mut buf, &points, &StrokeStyle::new(0.3).with_cap(cap).with_tolerance(0.01), GREEN, 0.0);
+            // where the line ends, caps go past it
+            put_line(&mut buf, vec2(-0.9, -0.6), vec2(0.9, -0.6), 0.02, BLUE, 0.0);
+            put_line(&mut buf, vec2(0.5, -0.9), vec2(0.5, 0.9), 0.02, BLUE, 0.0);
+            assert_render_matches!(buf, IDENTITY, format!("snapshots/put_polyline_cap_{}.png", name), 64, 64);
+        }
+    }
+
+    fn covered(buf: &[XYZRGBA], p: Vec2) -> bool {
+        buf.chunks_exact(3).any(|t| {
+            let [a, b, c] = [0, 1, 2].map(|i| vec2(t[i].xyz.x, t[i].xyz.y));
+            let side = |u: Vec2, v: Vec2| (v - u).cross(p - u);
+            let (d0, d1, d2) = (side(a, b), side(b, c), side(c, a));
+            (d0 >= 0.0 && d1 >= 0.0 && d2 >= 0.0) || (d0 <= 0.0 && d1 <= 0.0 && d2 <= 0.0)
+        })
+    }
+
+    /// which of xs along y = 0 a dashed line from 0 to 10 covers
+    fn dashed(dashes: &[f32], offset: f32, xs: &[f32]) -> Vec<bool> {
+        let mut buf = vec![];
+        put_dashed_polyline(&mut buf, &[vec2(0.0, 0.0), vec2(10.0, 0.0)], &StrokeStyle::new(0.2), dashes, offset, RED, 0.0);
+        xs.iter().map(|&x| covered(&buf, vec2(x, 0.0))).collect()
+    }
+
+    #[test]
+    fn dashes_odd_length_repeats() {
+        assert_eq!(dashed(&[1.0], 0.0, &[0.5, 1.5, 2.5, 3.5]), vec![true, false, true, false]);
+        // 1 on, 2 off, 3 on, 1 off, 2 on, 3 off
+        assert_eq!(dashed(&[1.0, 2.0, 3.0], 0.0, &[0.5, 2.0, 4.5, 6.5, 8.0, 9.5]), vec![true, false, true, false, true, false]);
+    }
+
+    #[test]
+    fn dashes_without_length_draw_solid() {
+        let points = [vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(10.0, 5.0)];
+        let style = StrokeStyle::new(0.2);
+        let mut solid = vec![];
+        put_polyline(&mut solid, &points, &style, RED, 0.0);
+        for dashes in [&[][..], &[0.0, 0.0], &[-1.0, 2.0], &[f32::INFINITY, 1.0]] {
+            let mut buf = vec![];
+            put_dashed_polyline(&mut buf, &points, &style, dashes, 0.0, RED, 0.0);
+            let xy = |buf: &[XYZRGBA]| buf.iter().map(|v| (v.xyz.x, v.xyz.y)).collect::<Vec<_>>();
+            assert_eq!(xy(&buf), xy(&solid), "{:?}", dashes);
+        }
+    }
+
+    #[test]
+    fn dash_offset() {
+        let xs = [0.5, 1.5, 2.5, 3.5, 4.5];
+        assert_eq!(dashed(&[2.0, 1.0], 0.0, &xs), vec![true, true, false, true, true]);
+        // starts one into the first dash
+        assert_eq!(dashed(&[2.0, 1.0], 1.0, &xs), vec![true, false, true, true, false]);
+        // negative goes the other way, starting in the gap
+        assert_eq!(dashed(&[2.0, 1.0], -1.0, &xs), vec![false, true, true, false, true]);
+        // a whole pattern is no offset
+        assert_eq!(dashed(&[2.0, 1.0], 3.0, &xs), dashed(&[2.0, 1.0], 0.0, &xs));
+    }
+
+    #[test]
+    fn tiny_dashes_on_a_long_line() {
+        let points = [vec2(0.0, 0.0), vec2(100.0, 0.0)];
+        let style = StrokeStyle::new(0.2);
+        let mut solid = vec![];
+        put_polyline(&mut solid, &points, &style, RED, 0.0);
+        // too short to tell apart this far along, drawn solid rather than piling up at one spot
+        let mut buf = vec![];
+        put_dashed_polyline(&mut buf, &points, &style, &[1e-6, 1e-6], 0.0, RED, 0.0);
+        assert_eq!(buf.len(), solid.len());
+
+        // short ones still land where they should at the far end
+        let mut one = vec![];
+        put_dashed_polyline(&mut one, &[vec2(0.0, 0.0), vec2(0.1, 0.0)], &style, &[0.1, 0.1], 0.0, RED, 0.0);
+        let mut buf = vec![];
+        put_dashed_polyline(&mut buf, &[vec2(0.0, 0.0), vec2(1000.0, 0.0)], &style, &[0.1, 0.1], 0.0, RED, 0.0);
+        assert_eq!(buf.len(), 5000 * one.len());
+        assert!(covered(&buf, vec2(999.85, 0.0)));
+        assert!(!covered(&buf, vec2(999.95, 0.0)));
+    }
+
+    #[test]
+    fn dashes_cross_vertices() {
+        let points = [vec2(0.0, 0.0), vec2(4.0, 0.0), vec2(4.0, 4.0)];
+        let style = StrokeStyle::new(0.2).with_join(Join::Miter);
+        let mut buf = vec![];
+        // on 0-2, off 2-3, on 3-5 going round the corner at 4
+        put_dashed_polyline(&mut buf, &points, &style, &[2.0, 1.0], 0.0, RED, 0.0);
+        assert!(covered(&buf, vec2(1.0, 0.0)));
+        assert!(!covered(&buf, vec2(2.5, 0.0)));
+        assert!(covered(&buf, vec2(3.5, 0.0)));
+        assert!(covered(&buf, vec2(4.0, 0.5)));
+        assert!(!covered(&buf, vec2(4.0, 1.5)));
+        // the dash round the corner is joined, not two butt ends
+        assert!(covered(&buf, vec2(4.09, -0.09)));
+
+        // a gap across the corner
+        let mut buf = vec![];
+        put_dashed_polyline(&mut buf, &points, &style, &[3.0, 2.0], 0.0, RED, 0.0);
+        assert!(covered(&buf, vec2(2.5, 0.0)));
+        assert!(!covered(&buf, vec2(3.5, 0.0)));
+        assert!(!covered(&buf, vec2(4.0, 0.5)));
+        assert!(covered(&buf, vec2(4.0, 1.5)));
+    }
+
+    #[test]
+    fn zero_length_line_is_empty() {
+        let p = vec2(0.3, 0.3);
+        let mut buf = vec![];
+        put_line(&mut buf, p, p, 0.1, RED, 0.0);
+        put_polyline(&mut buf, &[p, p], &StrokeStyle::new(0.1), RED, 0.0);
+        put_polyline(&mut buf, &[p, p, p], &StrokeStyle::new(0.1).with_join(Join::Round), RED, 0.0);
+        put_polyline(&mut buf, &[p], &StrokeStyle::new(0.1), RED, 0.0);
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn zero_length_polyline_caps_are_dots() {
+        let p = vec2(0.3, 0.3);
+        let xy = |buf: &[XYZRGBA]| buf.iter().map(|v| (v.xyz.x, v.xyz.y)).collect::<Vec<_>>();
+
+        let mut buf = vec![];
+        put_polyline(&mut buf, &[p, p], &StrokeStyle::new(0.1).with_cap(Cap::Square), RED, 0.0);
+        assert_eq!(buf.len(), 6);
+        let mut square = vec![];
+        put_rect(&mut square, rect(0.25, 0.25, 0.1, 0.1), RED, 0.0);
+        assert_eq!(xy(&buf), xy(&square));
+
+        let mut buf = vec![];
+        put_polyline(&mut buf, &[p, p], &StrokeStyle::new(0.1).with_cap(Cap::Round).with_tolerance(0.001), RED, 0.0);
+        assert_eq!(buf.len() % 3, 0);
+        assert!(buf.len() >= 3 * 8, "{}", buf.len());
+        let dist = |v: &XYZRGBA| (vec2(v.xyz.x, v.xyz.y) - p).dot(vec2(v.xyz.x, v.xyz.y) - p).sqrt();
+        assert!(buf.iter().all(|v| dist(v) <= 0.05 + 1e-6));
+        let extent = |f: fn(&XYZRGBA) -> f32| buf.iter().map(f).fold(f32::NEG_INFINITY, f32::max) - buf.iter().map(f).fold(f32::INFINITY, f32::min);
+        assert!((extent(|v| v.xyz.x) - 0.1).abs() < 1e-3);
+        assert!((extent(|v| v.xyz.y) - 0.1).abs() < 1e-3);
+        // short of the circle by at most the tolerance all round
+        let circle = PI * 0.05 * 0.05;
+        assert!(area(&buf) < circle && area(&buf) > circle - 2.0 * PI * 0.05 * 0.001, "{}", area(&buf));
+
+        // a repeated point in a longer line is just skipped
+        let style = StrokeStyle::new(0.1).with_join(Join::Round).with_cap(Cap::Round);
+        let mut repeated = vec![];
+        put_polyline(&mut repeated, &[p, p, vec2(0.5, 0.3)], &style, RED, 0.0);
+        let mut once = vec![];
+        put_polyline(&mut once, &[p, vec2(0.5, 0.3)], &style, RED, 0.0);
+        assert_eq!(xy(&repeated), xy(&once));
+    }
+
+    fn area(buf: &[XYZRGBA]) -> f32 {
+        buf.chunks_exact(3).map(|t| {
+            let [a, b, c] = [0, 1, 2].map(|i| vec2(t[i].xyz.x, t[i].xyz.y));
+            (b - a).cross(c - a).abs() / 2.0
+        }).sum()
+    }
+
+    #[test]
+    fn ellipse_fill() {
+        let mut buf = vec![];
+        put_ellipse(&mut buf, vec2(1.0, 2.0), vec2(4.0, 2.0), 0.3, 0.01, RED, 0.0);
+        assert_eq!(buf.len() % 3, 0);
+        // flattening only cuts corners off, by at most the tolerance
+        let a = area(&buf);
+        assert!(a < 8.0 * PI && a > 8.0 * PI - 0.01 * 12.0 * PI, "{}", a);
+        assert!(covered(&buf, vec2(1.0, 2.0)));
+        let along = vec2(0.3f32.cos(), 0.3f32.sin());
+        assert!(covered(&buf, vec2(1.0, 2.0) + along * 3.9));
+        assert!(!covered(&buf, vec2(1.0, 2.0) + along * 4.1));
+    }
+
+    fn square_with_hole(path: &mut Path, clockwise_hole: bool) {
+        path.move_to(vec2(0.0, 0.0)).line_to(vec2(4.0, 0.0)).line_to(vec2(4.0, 4.0)).line_to(vec2(0.0, 4.0)).close();
+        let (b, d) = if clockwise_hole { (vec2(1.0, 3.0), vec2(3.0, 1.0)) } else { (vec2(3.0, 1.0), vec2(1.0, 3.0)) };
+        path.move_to(vec2(1.0, 1.0)).line_to(b).line_to(vec2(3.0, 3.0)).line_to(d).close();
+    }
+
+    #[test]
+    fn path_fill() {
+        for (clockwise_hole, rule, expected) in [
+            (true, FillRule::NonZero, 12.0),
+            (true, FillRule::EvenOdd, 12.0),
+            (false, FillRule::NonZero, 16.0),
+            (false, FillRule::EvenOdd, 12.0),
+        ] {
+            let mut path = Path::new();
+            square_with_hole(&mut path, clockwise_hole);
+            let mut buf = vec![];
+            put_path(&mut buf, &path, rule, 0.1, RED, 0.5);
+            assert_eq!(buf.len() % 3, 0);
+            assert!((area(&buf) - expected).abs() < 1e-4, "{:?} {:?}: {}", clockwise_hole, rule, area(&buf));
+            assert!(buf.iter().all(|v| v.xyz.z == 0.5));
+        }
+    }
+
+    #[test]
+    fn path_stroke() {
+        // an open subpath gets caps, a closed one gets joined all the way round
+        let mut path = Path::new();
+        path.move_to(vec2(0.0, -2.0)).line_to(vec2(4.0, -2.0));
+        path.move_to(vec2(0.0, 0.0)).line_to(vec2(4.0, 0.0)).line_to(vec2(4.0, 4.0)).line_to(vec2(0.0, 4.0)).close();
+        let style = StrokeStyle::new(0.2).with_cap(Cap::Square).with_join(Join::Miter);
+        let mut buf = vec![];
+        put_path_stroke(&mut buf, &path, &style, RED, 0.0);
+        assert_eq!(buf.len() % 3, 0);
+        assert!(covered(&buf, vec2(-0.05, -2.0)));
+        assert!(covered(&buf, vec2(4.05, -2.0)));
+        assert!(covered(&buf, vec2(2.0, 0.05)));
+        assert!(covered(&buf, vec2(-0.05, 2.0)));
+        assert!(!covered(&buf, vec2(2.0, 2.0)));
+        // mitered where the close joins the start, not capped
+        assert!(covered(&buf, vec2(-0.09, -0.09)));
+        assert!(!covered(&buf, vec2(-0.11, 0.0)));
+    }
+
+    #[test]
+    fn svg_path_fill_and_stroke() {
+        let d = "M0 0 H4 V4 H0 Z M1 1 H3 V3 H1 Z";
+        let fill = PathStyle::Fill { col: RED, tolerance: 0.1 };
+        let mut buf = vec![];
+        put_svg_path(&mut buf, d, FillRule::NonZero, &fill, 0.0).unwrap();
+        assert!((area(&buf) - 16.0).abs() < 1e-4);
+        let mut buf = vec![];
+        put_svg_path(&mut buf, d, FillRule::EvenOdd, &fill, 0.0).unwrap();
+        assert!((area(&buf) - 12.0).abs() < 1e-4);
+
+        let stroke = PathStyle::Stroke { stroke: StrokeStyle::new(0.2), col: GREEN };
+        let mut buf = vec![];
+        put_svg_path(&mut buf, "M0 0 h4", FillRule::NonZero, &stroke, 0.0).unwrap();
+        assert!((area(&buf) - 0.8).abs() < 1e-4);
+        assert!(buf.iter().all(|v| v.rgba.y == 1.0));
+    }
+
+    #[test]
+    fn bad_svg_path_adds_nothing() {
+        let mut buf = vec![];
+        put_rect(&mut buf, rect(0.0, 0.0, 1.0, 1.0), RED, 0.0);
+        let before = buf.len();
+        for d in ["M0 0 L1", "M0 0 H4 V4 X", "L1 1"] {
+            for style in [PathStyle::Fill { col: RED, tolerance: 0.1 }, PathStyle::Stroke { stroke: StrokeStyle::new(0.2), col: RED }] {
+                assert!(matches!(put_svg_path(&mut buf, d, FillRule::NonZero, &style, 0.0), Err(GlowMeshError::Parse { .. })));
+                assert_eq!(buf.len(), before, "{}", d);
+            }
+        }
+    }
+}
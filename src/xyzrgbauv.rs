@@ -1,8 +1,13 @@
 use glow::HasContext;
 use minvect::*;
-use minimg::*;
-
-#[derive(Debug, Clone)]
+use crate::program::*;
+use crate::mesh::*;
+use crate::error::*;
+use crate::gc::*;
+use crate::texture::*;
+use crate::Vertex;
+
+#[derive(Debug, Clone, Vertex)]
 #[repr(C, packed)]
 pub struct XYZRGBAUV {
     pub xyz: Vec3,
@@ -10,109 +15,80 @@ pub struct XYZRGBAUV {
     pub uv: Vec2,
 }
 
-pub struct ProgramXYZRGBAUV {
-    program: glow::NativeProgram,
-    texture: glow::NativeTexture,
+pub struct ProgramXYZRGBAUV<G: HasContext = glow::Context> {
+    program: Program<G>,
 }
 
-impl ProgramXYZRGBAUV {
-    pub unsafe fn new(gl: &glow::Context, vert: &str, frag: &str, image: &ImageBuffer) -> Self {
-        let program = gl.create_program().expect("Cannot create program");
-    
-        let vs = gl.create_shader(glow::VERTEX_SHADER).expect("cannot create vertex shader");
-        gl.shader_source(vs, vert);
-        gl.compile_shader(vs);
-        if !gl.get_shader_compile_status(vs) {
-            panic!("{}", gl.get_shader_info_log(vs));
-        }
-        gl.attach_shader(program, vs);
-
-        let fs = gl.create_shader(glow::FRAGMENT_SHADER).expect("cannot create fragment shader");
-        gl.shader_source(fs, frag);
-        gl.compile_shader(fs);
-        if !gl.get_shader_compile_status(fs) {
-            panic!("{}", gl.get_shader_info_log(fs));
-        }
-        gl.attach_shader(program, fs);
-
-        gl.link_program(program);
-        if !gl.get_program_link_status(program) {
-            panic!("{}", gl.get_program_info_log(program));
-        }
-        gl.detach_shader(program, fs);
-        gl.delete_shader(fs);
-        gl.detach_shader(program, vs);
-        gl.delete_shader(vs);
-
-        gl.use_program(Some(program));
-
-        let texture = gl.create_texture().unwrap();
-        gl.bind_texture(glow::TEXTURE_2D, Some(texture));
-        gl.tex_image_2d(glow::TEXTURE_2D, 0, glow::RGBA as i32, image.w as i32, image.h as i32, 0, glow::RGBA, glow::UNSIGNED_BYTE, Some(&image.data));
-        gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MIN_FILTER, glow::NEAREST as i32);
-        gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MAG_FILTER, glow::NEAREST as i32);
-        gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_S, glow::CLAMP_TO_EDGE as i32);
-        gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_T, glow::CLAMP_TO_EDGE as i32);
-        gl.generate_mipmap(glow::TEXTURE_2D);
-
-        ProgramXYZRGBAUV {
-            program,
-            texture,
-        }
+impl<G: HasContext> ProgramXYZRGBAUV<G> {
+    /// # Safety
+    /// see Program::new
+    pub unsafe fn new(gl: &G, vert: &str, frag: &str) -> Result<Self, GlowMeshError> {
+        Ok(ProgramXYZRGBAUV {
+            program: Program::new(gl, vert, frag)?,
+        })
     }
-    pub unsafe fn default(gl: &glow::Context, image: &ImageBuffer) -> Self {
-        Self::new(gl, DEFAULT_VS, DEFAULT_FS, image)
+    /// # Safety
+    /// see Program::from_source
+    pub unsafe fn from_source(gl: &G, source: &ProgramSource) -> Result<Self, GlowMeshError> {
+        Ok(ProgramXYZRGBAUV {
+            program: Program::from_source(gl, source)?,
+        })
     }
-    pub unsafe fn bind(&self, gl: &glow::Context) {
-        gl.use_program(Some(self.program));
-        gl.bind_texture(glow::TEXTURE_2D, Some(self.texture));
+    /// # Safety
+    /// see Program::new
+    pub unsafe fn default(gl: &G) -> Result<Self, GlowMeshError> {
+        Self::new(gl, DEFAULT_VS, DEFAULT_FS)
     }
-    pub unsafe fn set_proj(&self, proj: &[f32; 16], gl: &glow::Context) {
-        gl.uniform_matrix_4_f32_slice(gl.get_uniform_location(self.program, "projection").as_ref(), true, proj);
+    /// see Program::poll_reload
+    ///
+    /// # Safety
+    /// see Program::poll_reload
+    pub unsafe fn poll_reload(&mut self, gl: &G) -> Result<bool, GlowMeshError> {
+        self.program.poll_reload(gl)
+    }
+    /// # Safety
+    /// see Program::bind
+    pub unsafe fn bind(&self, gl: &G) {
+        self.program.bind(gl);
+    }
+    /// use texture for `tex` in the next draws. Program must be bound.
+    ///
+    /// # Safety
+    /// the program has to be bound, and texture made on the same context
+    pub unsafe fn bind_texture(&self, texture: &Texture<G>, gl: &G) {
+        self.bind_native_texture(texture.texture, gl);
+    }
+    /// bind_texture for a raw handle, eg. one that isnt a Texture or is only known by its handle
+    ///
+    /// # Safety
+    /// the program has to be bound, and texture has to be a live texture on the same context
+    pub unsafe fn bind_native_texture(&self, texture: G::Texture, gl: &G) {
+        gl.active_texture(glow::TEXTURE0);
+        gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+        gl.uniform_1_i32(gl.get_uniform_location(self.program.native(), "tex").as_ref(), 0);
+    }
+    /// # Safety
+    /// see Program::set_proj
+    pub unsafe fn set_proj(&self, proj: &[f32; 16], gl: &G) {
+        self.program.set_proj(proj, gl)
+    }
+    pub fn free(&self, gl: &G) {
+        self.program.free(gl)
     }
 }
 
-pub unsafe fn upload_xyzrgbauv_mesh(mesh: &[XYZRGBAUV], gl: &glow::Context) -> HandleXYZRGBAUV {
-    let vbo = gl.create_buffer().unwrap();
-    gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
-    let vao = gl.create_vertex_array().unwrap();
-    gl.bind_vertex_array(Some(vao));
-    let vert_size = std::mem::size_of::<XYZRGBAUV>();
-
-    gl.vertex_attrib_pointer_f32(0, 3, glow::FLOAT, false, vert_size as i32, 0);
-    gl.enable_vertex_attrib_array(0);
-    gl.vertex_attrib_pointer_f32(1, 4, glow::FLOAT, false, vert_size as i32, 3*4);
-    gl.enable_vertex_attrib_array(1);
-    gl.vertex_attrib_pointer_f32(2, 2, glow::FLOAT, false, vert_size as i32, 7*4);
-    gl.enable_vertex_attrib_array(2);
-    
-    let vert_bytes: &[u8] = std::slice::from_raw_parts(
-        mesh.as_ptr() as *const u8,
-        mesh.len() * vert_size,
-    );
-    gl.buffer_data_u8_slice(glow::ARRAY_BUFFER, vert_bytes, glow::STATIC_DRAW);
-    HandleXYZRGBAUV {vao, vbo, num_verts: mesh.len()}
-}
-
-pub struct HandleXYZRGBAUV {
-    pub vao: glow::NativeVertexArray,
-    pub vbo: glow::NativeBuffer,
-    pub num_verts: usize,
+impl<G: HasContext> Free<G> for ProgramXYZRGBAUV<G> {
+    fn free(&self, gl: &G) {
+        ProgramXYZRGBAUV::free(self, gl)
+    }
 }
 
-impl HandleXYZRGBAUV {
-    pub unsafe fn render(&self, gl: &glow::Context) {
-        gl.bind_vertex_array(Some(self.vao));
-        gl.draw_arrays(glow::TRIANGLES, 0, self.num_verts as i32);
-    }
+pub type HandleXYZRGBAUV<G = glow::Context> = Mesh<XYZRGBAUV, G>;
 
-    /// definitely want to call this when dropping the buffer. not impling drop because how to reference opengl context hey?
-    pub fn free(&self, gl: &glow::Context) {
-        unsafe {
-            gl.delete_vertex_array(self.vao);
-            gl.delete_buffer(self.vbo);
-        }
-    }
+/// # Safety
+/// see upload_mesh
+pub unsafe fn upload_xyzrgbauv_mesh<G: HasContext>(mesh: &[XYZRGBAUV], gl: &G) -> Result<HandleXYZRGBAUV<G>, GlowMeshError> {
+    upload_mesh(mesh, gl)
 }
 
 pub const DEFAULT_FS: &str = r#"#version 330 core
@@ -144,6 +120,7 @@ void main() {
 }
 "#;
 
+#[allow(clippy::too_many_arguments)]
 pub fn put_triangle(buf: &mut Vec<XYZRGBAUV>, a: Vec2, a_uv: Vec2, b: Vec2, b_uv: Vec2, c: Vec2, c_uv: Vec2, col: Vec4, depth: f32) {
     buf.push(XYZRGBAUV {
         xyz: vec3(a.x, a.y, depth),
@@ -162,6 +139,7 @@ pub fn put_triangle(buf: &mut Vec<XYZRGBAUV>, a: Vec2, a_uv: Vec2, b: Vec2, b_uv
     });
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn put_quad(buf: &mut Vec<XYZRGBAUV>, a: Vec2, b: Vec2, c: Vec2, d: Vec2, col: Vec4, uv_lo: Vec2, uv_hi: Vec2, depth: f32) {
     let a_uv = uv_lo;
     let b_uv = vec2(uv_hi.x, uv_lo.y);
@@ -180,8 +158,23 @@ pub fn put_rect(buf: &mut Vec<XYZRGBAUV>, r: Rect, r_uv: Rect, col: Vec4, depth:
     put_quad(buf, a, b, c, d, col, r_uv.tl(), r_uv.br(), depth);
 }
 
-pub fn transform_mesh(v: &mut Vec<XYZRGBAUV>, mat: &[f32; 16]) {
-    for i in 0..v.len() {
-        v[i].xyz = mat4_trans_homog(v[i].xyz, mat);
+/// 4 vertices instead of put_quad's 6
+#[allow(clippy::too_many_arguments)]
+pub fn put_quad_indexed<I: Index>(buf: &mut IndexedMesh<XYZRGBAUV, I>, a: Vec2, b: Vec2, c: Vec2, d: Vec2, col: Vec4, uv_lo: Vec2, uv_hi: Vec2, depth: f32) {
+    let ia = buf.push_vertex(XYZRGBAUV { xyz: vec3(a.x, a.y, depth), rgba: col, uv: uv_lo });
+    let ib = buf.push_vertex(XYZRGBAUV { xyz: vec3(b.x, b.y, depth), rgba: col, uv: vec2(uv_hi.x, uv_lo.y) });
+    let ic = buf.push_vertex(XYZRGBAUV { xyz: vec3(c.x, c.y, depth), rgba: col, uv: uv_hi });
+    let id = buf.push_vertex(XYZRGBAUV { xyz: vec3(d.x, d.y, depth), rgba: col, uv: vec2(uv_lo.x, uv_hi.y) });
+    buf.push_triangle(ia, ib, ic);
+    buf.push_triangle(ia, ic, id);
+}
+
+pub fn put_rect_indexed<I: Index>(buf: &mut IndexedMesh<XYZRGBAUV, I>, r: Rect, r_uv: Rect, col: Vec4, depth: f32) {
+    put_quad_indexed(buf, r.tl(), r.tr(), r.br(), r.bl(), col, r_uv.tl(), r_uv.br(), depth);
+}
+
+pub fn transform_mesh(v: &mut [XYZRGBAUV], mat: &[f32; 16]) {
+    for vert in v.iter_mut() {
+        vert.xyz = mat4_trans_homog(vert.xyz, mat);
     }
-}
\ No newline at end of file
+}